@@ -0,0 +1,179 @@
+// A/B experiment runner for market design research.
+//
+//   npm run experiment -- --a <config> --b <config> [--profile <name>] [--seed 42] [--steps 300] [--json]
+//
+// Runs the engine headless twice with the same seed and duration, once per configuration,
+// and prints volatility, spreads, volume and agent P&L distributions side by side. A config
+// is a JSON file or inline JSON of engine config overrides; nested objects are merged, so
+// '{"stress":{"enabled":true}}' only switches that one setting. Omit --a for the defaults.
+// --profile sets the baseline both arms start from (see profiles.js).
+//
+// The engine draws from Math.random and Date.now directly, so both are replaced with a
+// seeded generator and a clock that advances one updateFrequency per step.
+
+import { readFileSync, existsSync } from 'node:fs'
+import { createPinia, setActivePinia } from 'pinia'
+import { useMarketEngineStore, EngineEvent } from '../src/stores/marketEngine.js'
+import { getPipSize } from '../src/stores/instruments.js'
+import { applyProfile } from '../src/stores/profiles.js'
+import { createRng, mergeConfig, withQuietLogs } from './harness.js'
+
+const args = process.argv.slice(2)
+const option = (name, fallback = null) => {
+  const index = args.indexOf(`--${name}`)
+  return index !== -1 && index + 1 < args.length ? args[index + 1] : fallback
+}
+
+const seed = Number(option('seed', 42))
+const steps = Number(option('steps', 300))
+const profile = option('profile')
+const asJson = args.includes('--json')
+
+const loadConfig = (value) => {
+  if (!value) return {}
+  return JSON.parse(existsSync(value) ? readFileSync(value, 'utf8') : value)
+}
+
+const mean = (values) => values.length > 0 ? values.reduce((sum, v) => sum + v, 0) / values.length : 0
+const stdev = (values) => {
+  if (values.length < 2) return 0
+  const m = mean(values)
+  return Math.sqrt(values.reduce((sum, v) => sum + (v - m) ** 2, 0) / (values.length - 1))
+}
+const percentile = (sorted, p) => sorted.length > 0 ? sorted[Math.min(sorted.length - 1, Math.floor(p * sorted.length))] : 0
+
+const runArm = async (name, overrides) => {
+  const realRandom = Math.random
+  const realNow = Date.now
+  const startTime = Date.UTC(2024, 0, 2, 8, 0, 0)
+  let clock = startTime
+  Math.random = createRng(seed)
+  Date.now = () => clock
+
+  try {
+    setActivePinia(createPinia())
+    const engine = useMarketEngineStore()
+    const profileResult = profile ? applyProfile(engine, profile) : { success: true }
+    if (!profileResult.success) throw new Error(`Invalid profile: ${profileResult.errors.join('; ')}`)
+    const result = engine.updateConfig(mergeConfig(engine.config, overrides))
+    if (!result.success) {
+      throw new Error(`Invalid config for ${name}: ${result.errors.join('; ')}`)
+    }
+    engine.initializeMarket()
+
+    const symbols = engine.config.symbols
+    const series = Object.fromEntries(symbols.map(symbol => [symbol, { mids: [], spreads: [], volume: 0, trades: 0 }]))
+    const unsubscribe = engine.subscribe(event => {
+      if (event.type !== EngineEvent.TRADE_EXECUTED) return
+      const entry = series[event.trade.symbol]
+      if (!entry) return
+      entry.volume += event.trade.volume
+      entry.trades += 1
+    })
+
+    for (let step = 0; step < steps; step++) {
+      clock += engine.config.updateFrequency
+      await engine.runSimulationStep()
+
+      symbols.forEach(symbol => {
+        const book = engine.getOrderBook(symbol)
+        const bid = book.getBestBid()
+        const ask = book.getBestAsk()
+        if (!bid || !ask) return
+        series[symbol].mids.push((bid + ask) / 2)
+        series[symbol].spreads.push((ask - bid) / getPipSize(symbol))
+      })
+    }
+    unsubscribe()
+
+    const markets = symbols.map(symbol => {
+      const { mids, spreads, volume, trades } = series[symbol]
+      const returns = mids.slice(1).map((mid, i) => Math.log(mid / mids[i]))
+      const sortedSpreads = [...spreads].sort((a, b) => a - b)
+      return {
+        symbol,
+        volatilityBps: stdev(returns) * 10000, // per step
+        rangePips: mids.length > 0 ? (Math.max(...mids) - Math.min(...mids)) / getPipSize(symbol) : 0,
+        meanSpreadPips: mean(spreads),
+        medianSpreadPips: percentile(sortedSpreads, 0.5),
+        p95SpreadPips: percentile(sortedSpreads, 0.95),
+        oneSidedSteps: steps - spreads.length,
+        volume,
+        trades
+      }
+    })
+
+    // Mark every open position to the final mid before reading P&L
+    const finalMids = Object.fromEntries(symbols.map(symbol => [symbol, series[symbol].mids[series[symbol].mids.length - 1]]))
+    const pnlByType = new Map()
+    engine.participants.forEach(participant => {
+      if (participant.id === 'user_trader') return
+      Object.entries(finalMids).forEach(([symbol, mid]) => {
+        if (mid) participant.updatePositionPrice(symbol, mid)
+      })
+      const pnl = participant.realizedPnl + participant.getUnrealizedPnl()
+      if (!pnlByType.has(participant.participantType)) pnlByType.set(participant.participantType, [])
+      pnlByType.get(participant.participantType).push(pnl)
+    })
+    const agents = Array.from(pnlByType.entries()).map(([type, values]) => {
+      const sorted = [...values].sort((a, b) => a - b)
+      return {
+        type,
+        count: values.length,
+        meanPnl: mean(values),
+        p10Pnl: percentile(sorted, 0.1),
+        medianPnl: percentile(sorted, 0.5),
+        p90Pnl: percentile(sorted, 0.9),
+        profitableShare: values.filter(v => v > 0).length / values.length,
+        totalPnl: values.reduce((sum, v) => sum + v, 0)
+      }
+    })
+
+    return { name, overrides, markets, agents }
+  } finally {
+    Math.random = realRandom
+    Date.now = realNow
+  }
+}
+
+const compare = (a, b, key, fields) => a.map(rowA => {
+  const rowB = b.find(row => row[key] === rowA[key]) || {}
+  return Object.fromEntries([
+    [key, rowA[key]],
+    ...fields.flatMap(field => [
+      [`${field}.A`, rowA[field]],
+      [`${field}.B`, rowB[field]],
+      [`${field}.diff`, rowB[field] - rowA[field]]
+    ])
+  ])
+})
+
+const format = (value) => typeof value === 'number' ? Number(value.toPrecision(6)) : value
+
+const printTable = (title, rows) => {
+  console.log(`\n${title}`)
+  console.table(rows.map(row => Object.fromEntries(Object.entries(row).map(([k, v]) => [k, format(v)]))))
+}
+
+const a = await withQuietLogs(() => runArm(option('a') ? 'A' : 'A (defaults)', loadConfig(option('a'))))
+const b = await withQuietLogs(() => runArm('B', loadConfig(option('b'))))
+
+const report = {
+  seed,
+  steps,
+  arms: { A: a, B: b },
+  markets: compare(a.markets, b.markets, 'symbol', ['volatilityBps', 'meanSpreadPips', 'p95SpreadPips', 'volume', 'trades']),
+  agents: compare(a.agents, b.agents, 'type', ['meanPnl', 'medianPnl', 'p10Pnl', 'p90Pnl', 'profitableShare'])
+}
+
+if (asJson) {
+  console.log(JSON.stringify(report, null, 2))
+} else {
+  console.log(`A/B experiment: seed ${seed}, ${steps} steps`)
+  console.log(`A: ${JSON.stringify(a.overrides)}`)
+  console.log(`B: ${JSON.stringify(b.overrides)}`)
+  printTable('Markets', report.markets)
+  printTable('Agent P&L', report.agents)
+}
+
+process.exit(0)
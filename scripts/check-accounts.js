@@ -0,0 +1,55 @@
+// Checks where a user account's resting orders rejoin their books after an account switch.
+//
+//   npm run check-accounts
+//
+// The user rests a EURUSD limit order, then the account is switched away
+// (saveUserParticipant) and another participant joins the same price. When the account is
+// switched back (restoreUserParticipant) the user's order must be live again, with the same
+// id, and sit behind the order that arrived while it was parked.
+
+import { createPinia, setActivePinia } from 'pinia'
+import { useMarketEngineStore, OrderSide, OrderType } from '../src/stores/marketEngine.js'
+
+// Quiet the engine's own progress logging
+const log = console.log
+console.log = () => {}
+
+setActivePinia(createPinia())
+const engine = useMarketEngineStore()
+engine.initializeMarket()
+
+const failures = []
+const expect = (condition, message) => {
+  if (!condition) failures.push(message)
+}
+
+// Far enough below the touch that nothing fills, so both orders rest
+const book = engine.getOrderBook('EURUSD')
+const price = Number(((book.getBestBid() || engine.getReferencePrice('EURUSD')) * 0.95).toFixed(5))
+const otherId = Array.from(engine.participants.keys()).find(id => id !== 'user_trader')
+
+const userOrderId = await engine.placeOrder('EURUSD', OrderSide.BUY, 1000, 'user_trader', OrderType.LIMIT, price)
+expect(userOrderId && book.locateOrder(userOrderId), 'the user order should rest')
+
+const parked = engine.saveUserParticipant()
+expect(!book.locateOrder(userOrderId), 'the parked order is still in the book')
+
+const otherOrderId = await engine.placeOrder('EURUSD', OrderSide.BUY, 1000, otherId, OrderType.LIMIT, price)
+expect(otherOrderId && book.locateOrder(otherOrderId), `the ${otherId} order should rest`)
+
+engine.restoreUserParticipant(parked)
+const queue = engine.getQueuePosition(userOrderId)
+const otherQueue = engine.getQueuePosition(otherOrderId)
+expect(queue, 'the restored order is not back in the book')
+expect(engine.activeOrders.has(userOrderId), 'the restored order is not active')
+if (queue && otherQueue) {
+  expect(queue.position > otherQueue.position, `the restored order is at position ${queue.position}, ahead of the ${otherId} order at ${otherQueue.position}`)
+  expect(queue.position === queue.ordersAhead + 1 && queue.ordersAhead === book.bids.get(price).length - 1, `the restored order should be last at its level, has ${queue.ordersAhead} orders ahead`)
+}
+
+if (failures.length > 0) {
+  failures.forEach(failure => log(`FAIL ${failure}`))
+  process.exit(1)
+}
+log('accounts: ok (restored order rejoined its level at the back)')
+process.exit(0)
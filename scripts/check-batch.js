@@ -0,0 +1,77 @@
+// Checks placeOrderBatch when an order is refused partway through a symbol group.
+//
+//   npm run check-batch
+//
+// Places five resting EURUSD limit orders as one batch while a pre-trade check refuses the
+// third. The first two must be cancelled, the third reported with the refusal, the last two
+// never sent, and none of the batch may be left resting in the book. A separate GBPUSD
+// group in the same batch must be unaffected.
+
+import { createPinia, setActivePinia } from 'pinia'
+import { useMarketEngineStore, OrderSide, OrderType } from '../src/stores/marketEngine.js'
+
+// Quiet the engine's own progress logging
+const log = console.log
+console.log = () => {}
+
+setActivePinia(createPinia())
+const engine = useMarketEngineStore()
+engine.initializeMarket()
+
+const participantId = 'user_trader'
+const failures = []
+const expect = (condition, message) => {
+  if (!condition) failures.push(message)
+}
+
+// Far enough below the touch that nothing fills, so every placed order rests
+const restingPrice = (symbol) => {
+  const book = engine.getOrderBook(symbol)
+  return Number(((book.getBestBid() || engine.getReferencePrice(symbol)) * 0.95).toFixed(5))
+}
+
+const eurusd = restingPrice('EURUSD')
+const gbpusd = restingPrice('GBPUSD')
+const orders = [
+  ...[1000, 2000, 3000, 4000, 5000].map((amount, i) => ({
+    symbol: 'EURUSD',
+    side: OrderSide.BUY,
+    amount,
+    orderType: OrderType.LIMIT,
+    price: eurusd,
+    clientOrderId: `eur_${i}`
+  })),
+  { symbol: 'GBPUSD', side: OrderSide.BUY, amount: 1000, orderType: OrderType.LIMIT, price: gbpusd, clientOrderId: 'gbp_0' }
+]
+
+engine.setPreTradeCheck(participantId, (order) => (order.symbol === 'EURUSD' && order.amount === 3000 ? 'Refused by check' : null))
+const results = await engine.placeOrderBatch(orders, participantId)
+engine.setPreTradeCheck(participantId, null)
+
+expect(results.length === orders.length, `expected ${orders.length} results, got ${results.length}`)
+results.forEach((result, index) => expect(result.index === index, `result ${index} reports index ${result.index}`))
+
+for (const index of [0, 1]) {
+  const result = results[index]
+  expect(!result.success && result.orderId, `order ${index} should be reported as placed then cancelled`)
+  expect(result.filledAmount === 0, `order ${index} should report no fills, got ${result.filledAmount}`)
+  expect(!engine.getOrderBook('EURUSD').locateOrder(result.orderId), `order ${index} is still resting`)
+}
+expect(!results[2].success && results[2].error === 'Refused by check', `order 2 should carry the refusal, got ${results[2].error}`)
+for (const index of [3, 4]) {
+  expect(!results[index].success && !results[index].orderId, `order ${index} should not have been sent`)
+}
+
+const gbp = results[5]
+expect(gbp.success && engine.getOrderBook('GBPUSD').locateOrder(gbp.orderId), 'the GBPUSD group should be placed and resting')
+
+const leftovers = Array.from(engine.activeOrders.values())
+  .filter(order => order.participantId === participantId && order.symbol === 'EURUSD')
+expect(leftovers.length === 0, `${leftovers.length} EURUSD batch orders left active`)
+
+if (failures.length > 0) {
+  failures.forEach(failure => log(`FAIL ${failure}`))
+  process.exit(1)
+}
+log('batch: ok (refused order 3 of 5 left nothing of its group resting)')
+process.exit(0)
@@ -0,0 +1,142 @@
+// Randomized correctness checks for the incremental candle series.
+//
+//   npm run check-candles -- [iterations] [seed]
+//
+// Feeds seeded random tick streams, some delivered late, into the stored candle series
+// and verifies that every timeframe equals a reference aggregation of the time-sorted
+// ticks, that each higher timeframe equals the aggregation of the 1m candles, that
+// series stay strictly ordered when trimmed, and that backfill leaves no missing bars.
+
+import {
+  CANDLE_TIMEFRAMES,
+  CandleBackfill,
+  createCandleSeries,
+  updateCandleSeries,
+  aggregateCandles
+} from '../src/stores/candles.js'
+import { createRng } from './harness.js'
+
+const EPSILON = 1e-9
+
+// Ticks roughly a second apart over a few hours; a few arrive up to ten minutes late
+const generateTicks = (rng, count) => {
+  const ticks = []
+  let clock = Date.UTC(2024, 0, 2, 0, 0, 0) / 1000 + Math.floor(rng() * 86400)
+  let price = 1.1
+  for (let i = 0; i < count; i++) {
+    clock += rng() * 2
+    price *= 1 + (rng() - 0.5) * 0.0004
+    // Occasional stalls of several minutes
+    if (rng() < 0.001) clock += 60 + rng() * 1800
+    // Whole-second times now and then so ties within a candle get exercised
+    const timestamp = rng() < 0.2 ? Math.floor(clock) : clock
+    ticks.push({ price, volume: Math.floor(rng() * 1000), timestamp })
+  }
+  const delivered = [...ticks]
+  for (let i = 0; i < count / 50; i++) {
+    const from = Math.floor(rng() * delivered.length)
+    const to = Math.min(delivered.length - 1, from + Math.floor(rng() * 600))
+    const [tick] = delivered.splice(from, 1)
+    delivered.splice(to, 0, tick)
+  }
+  return delivered
+}
+
+const reference = (ticks, seconds) => {
+  // Stable sort keeps arrival order between ticks with the same time
+  const sorted = ticks.map((tick, i) => ({ ...tick, i })).sort((a, b) => a.timestamp - b.timestamp || a.i - b.i)
+  return aggregateCandles(sorted.map(tick => ({
+    timestamp: tick.timestamp,
+    open: tick.price,
+    high: tick.price,
+    low: tick.price,
+    close: tick.price,
+    volume: tick.volume
+  })), seconds)
+}
+
+const sameCandles = (actual, expected) => {
+  if (actual.length !== expected.length) return `length ${actual.length} != ${expected.length}`
+  for (let i = 0; i < actual.length; i++) {
+    const a = actual[i]
+    const e = expected[i]
+    if (a.timestamp !== e.timestamp) return `candle ${i}: timestamp ${a.timestamp} != ${e.timestamp}`
+    for (const field of ['open', 'high', 'low', 'close', 'volume']) {
+      if (Math.abs(a[field] - e[field]) > EPSILON) return `candle ${a.timestamp}: ${field} ${a[field]} != ${e[field]}`
+    }
+  }
+  return null
+}
+
+const runSequence = (seed, count) => {
+  const rng = createRng(seed)
+  const ticks = generateTicks(rng, count)
+  const failures = []
+
+  // Untrimmed: exact agreement with the references
+  const series = createCandleSeries()
+  const unlimited = Object.fromEntries(Object.keys(CANDLE_TIMEFRAMES).map(tf => [tf, Infinity]))
+  ticks.forEach(tick => updateCandleSeries(series, tick, unlimited, null))
+  Object.entries(CANDLE_TIMEFRAMES).forEach(([tf, seconds]) => {
+    const vsTicks = sameCandles(series[tf], reference(ticks, seconds))
+    if (vsTicks) failures.push(`${tf} vs ticks: ${vsTicks}`)
+    const vsMinutes = sameCandles(series[tf], aggregateCandles(series['1m'], seconds))
+    if (vsMinutes) failures.push(`${tf} vs 1m: ${vsMinutes}`)
+  })
+
+  // Trimmed: bounded and strictly increasing
+  const limits = Object.fromEntries(Object.keys(CANDLE_TIMEFRAMES).map(tf => [tf, 20]))
+  const trimmed = createCandleSeries()
+  ticks.forEach(tick => updateCandleSeries(trimmed, tick, limits, null))
+  Object.keys(CANDLE_TIMEFRAMES).forEach(tf => {
+    const candles = trimmed[tf]
+    if (candles.length > 20) failures.push(`${tf} trimmed series holds ${candles.length} candles`)
+    for (let i = 1; i < candles.length; i++) {
+      if (candles[i].timestamp <= candles[i - 1].timestamp) {
+        failures.push(`${tf} trimmed series out of order at ${i}`)
+        break
+      }
+    }
+  })
+
+  // Backfilled: contiguous bars, fillers carry no volume and stay within their neighbours
+  for (const mode of Object.values(CandleBackfill)) {
+    const filled = createCandleSeries()
+    ticks.forEach(tick => updateCandleSeries(filled, tick, unlimited, { enabled: true, mode, maxGapCandles: Infinity }))
+    Object.entries(CANDLE_TIMEFRAMES).forEach(([tf, seconds]) => {
+      const candles = filled[tf]
+      for (let i = 1; i < candles.length; i++) {
+        if (candles[i].timestamp !== candles[i - 1].timestamp + seconds) {
+          failures.push(`${mode} ${tf}: gap after ${candles[i - 1].timestamp}`)
+          break
+        }
+        if (candles[i].filled && (candles[i].volume !== 0 || Math.abs(candles[i].open - candles[i - 1].close) > EPSILON)) {
+          failures.push(`${mode} ${tf}: filler at ${candles[i].timestamp} doesn't continue the previous close`)
+          break
+        }
+      }
+      const real = candles.filter(candle => !candle.filled)
+      const vsUnfilled = sameCandles(real, series[tf])
+      if (vsUnfilled) failures.push(`${mode} ${tf}: real bars changed by backfill: ${vsUnfilled}`)
+    })
+  }
+
+  return failures
+}
+
+const iterations = parseInt(process.argv[2] || '200', 10)
+const baseSeed = parseInt(process.argv[3] || `${Date.now() % 100000}`, 10)
+
+let failed = 0
+for (let i = 0; i < iterations; i++) {
+  const seed = baseSeed + i
+  const failures = runSequence(seed, 5000)
+  if (failures.length > 0) {
+    failed += 1
+    console.error(`seed ${seed} failed:`)
+    failures.slice(0, 10).forEach(f => console.error(`  ${f}`))
+  }
+}
+
+console.log(`${iterations - failed}/${iterations} sequences passed (base seed ${baseSeed})`)
+process.exit(failed > 0 ? 1 : 0)
@@ -0,0 +1,56 @@
+// Checks a limit order that credit keeps from trading at the touch it would cross.
+//
+//   npm run check-credit
+//
+// With credit enabled and the user's limit to every owner of the best EURUSD ask set to
+// zero, a limit buy at that ask can neither fill nor rest. It must be cancelled with the
+// WouldCross reason: not left in activeOrders or the book, and shown as done by the
+// participant inspector.
+
+import { createPinia, setActivePinia } from 'pinia'
+import { useMarketEngineStore, EngineEvent, OrderSide, OrderType, BookRejectReason } from '../src/stores/marketEngine.js'
+
+// Quiet the engine's own progress logging
+const log = console.log
+console.log = () => {}
+
+setActivePinia(createPinia())
+const engine = useMarketEngineStore()
+engine.config.credit.enabled = true
+engine.initializeMarket()
+
+const failures = []
+const expect = (condition, message) => {
+  if (!condition) failures.push(message)
+}
+
+const participantId = 'user_trader'
+const book = engine.getOrderBook('EURUSD')
+const ask = book.getBestAsk()
+const askOwners = new Set(book.asks.get(ask).map(order => order.participantId))
+askOwners.forEach(ownerId => engine.setCreditLimit(participantId, ownerId, 0))
+
+const cancels = []
+engine.subscribe(event => {
+  if (event.type === EngineEvent.ORDER_CANCELLED && event.order.participantId === participantId) cancels.push(event)
+})
+
+const orderId = await engine.placeOrder('EURUSD', OrderSide.BUY, 1000, participantId, OrderType.LIMIT, ask)
+expect(orderId, 'the order should be accepted before its remainder is cancelled')
+expect(!engine.activeOrders.has(orderId), 'the unrested order is still in activeOrders')
+expect(!book.locateOrder(orderId), 'the order is resting in the book')
+expect(book.getBestAsk() === ask, `the best ask moved from ${ask} to ${book.getBestAsk()}, so the order traded`)
+
+const cancel = cancels.find(event => event.order.id === orderId)
+expect(cancel, 'no OrderCancelled event for the order')
+expect(cancel?.reason === BookRejectReason.WOULD_CROSS, `the cancel should carry ${BookRejectReason.WOULD_CROSS}, got ${cancel?.reason}`)
+
+const inspected = engine.inspectParticipant(participantId)?.recentOrders.find(order => order.id === orderId)
+expect(inspected?.status === 'Done', `the inspector shows the order as ${inspected?.status}`)
+
+if (failures.length > 0) {
+  failures.forEach(failure => log(`FAIL ${failure}`))
+  process.exit(1)
+}
+log('credit: ok (a limit buy at a credit-blocked ask was cancelled, not rested)')
+process.exit(0)
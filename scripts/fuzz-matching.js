@@ -0,0 +1,166 @@
+// Randomized property checks for the matching engine.
+//
+//   npm run fuzz -- [iterations] [seed]
+//
+// Feeds seeded random order sequences into OrderBook and verifies that quantity is
+// conserved, no resting order or fill goes non-positive, the book stays internally
+// consistent, limit orders never trade through their price, and a book restored from
+// exportState() behaves identically to the original. FIFO books must fill a level oldest
+// order first, and under time priority no resting order's queue position gets worse as
+// newer orders arrive. Seeds rotate through the matching algorithms.
+//
+// Balances are not checked here: OrderBook has no accounts, and a Participant's balance is
+// realized P&L with no solvency rule (losses are booked in full, nothing stops an agent
+// out), so "never negative" isn't an invariant the engine claims.
+
+import { Order, OrderBook, OrderSide, OrderType, MatchingAlgorithm } from '../src/stores/marketEngine.js'
+import { createRng } from './harness.js'
+
+const EPSILON = 1e-6
+
+const randomOrder = (rng, index) => {
+  const roll = rng()
+  const orderType = roll < 0.6
+    ? OrderType.LIMIT
+    : roll < 0.85
+      ? OrderType.MARKET
+      : roll < 0.95
+        ? OrderType.STOP
+        : OrderType.STOP_LIMIT
+
+  return new Order({
+    id: `fuzz_${index}`,
+    symbol: 'EURUSD',
+    side: rng() < 0.5 ? OrderSide.BUY : OrderSide.SELL,
+    amount: 1 + Math.floor(rng() * 1000),
+    // Coarse tick grid so orders frequently share and cross levels
+    price: Number((1.09 + Math.floor(rng() * 40) * 0.0005).toFixed(4)),
+    participantId: `p${Math.floor(rng() * 20)}`,
+    orderType,
+    timestamp: index
+  })
+}
+
+const restingVolume = (book) => {
+  const sum = (side) => Array.from(side.values())
+    .reduce((total, orders) => total + orders.reduce((s, order) => s + order.amount, 0), 0)
+  return { bids: sum(book.bids), asks: sum(book.asks) }
+}
+
+const describeTrades = (trades) => (trades || [])
+  .map(t => `${t.price}:${t.volume}:${t.buyerId}:${t.sellerId}`)
+  .join('|')
+
+// arrivals: order id -> position in the sequence, to check the order fills come in
+const checkOrder = (book, order, arrivals, failures) => {
+  const before = restingVolume(book)
+  const opposite = order.side === OrderSide.BUY ? 'asks' : 'bids'
+  const own = order.side === OrderSide.BUY ? 'bids' : 'asks'
+
+  const aheadBefore = new Map(Array.from(book.orderIndex.keys(), id => [id, book.getQueuePosition(id).ordersAhead]))
+
+  const trades = book.addOrder(new Order({ ...order })) || []
+  const after = restingVolume(book)
+  const traded = trades.reduce((sum, t) => sum + t.volume, 0)
+
+  trades.forEach(t => {
+    if (!(t.volume > 0)) failures.push(`order ${order.id}: non-positive fill ${t.volume}`)
+  })
+  // A limit order (or a triggered stop-limit) never trades through its price
+  if (order.orderType === OrderType.LIMIT || order.orderType === OrderType.STOP_LIMIT) {
+    const through = trades.find(t => order.side === OrderSide.BUY ? t.price > order.price : t.price < order.price)
+    if (through) failures.push(`order ${order.id}: limit ${order.price} traded through at ${through.price}`)
+  }
+  // Under FIFO, fills at one price go to the resting orders in the order they arrived
+  if (book.matchingAlgorithm === MatchingAlgorithm.FIFO) {
+    trades.forEach((t, i) => {
+      const previous = trades[i - 1]
+      if (!previous || previous.price !== t.price) return
+      const restingId = (trade) => trade.buyOrderId === order.id ? trade.sellOrderId : trade.buyOrderId
+      if (arrivals.get(restingId(t)) < arrivals.get(restingId(previous))) {
+        failures.push(`order ${order.id}: filled ${restingId(t)} at ${t.price} after the newer ${restingId(previous)}`)
+      }
+    })
+  }
+  if (traded > order.amount + EPSILON) {
+    failures.push(`order ${order.id}: filled ${traded} of ${order.amount}`)
+  }
+  if (Math.abs(before[opposite] - traded - after[opposite]) > EPSILON) {
+    failures.push(`order ${order.id}: ${opposite} changed by ${before[opposite] - after[opposite]}, traded ${traded}`)
+  }
+
+  // Only limit orders (or triggered stop-limits) leave a resting remainder
+  const rested = after[own] - before[own]
+  if (rested < -EPSILON || rested > order.amount - traded + EPSILON) {
+    failures.push(`order ${order.id}: ${own} changed by ${rested} after trading ${traded}`)
+  }
+  if (order.orderType === OrderType.MARKET && Math.abs(rested) > EPSILON) {
+    failures.push(`order ${order.id}: market order left ${rested} resting`)
+  }
+
+  // Time priority (FIFO, and pro-rata's reported queue) only ever moves an order forward:
+  // what arrives later joins behind it
+  if (book.matchingAlgorithm !== MatchingAlgorithm.SIZE_PRIORITY) {
+    aheadBefore.forEach((ahead, id) => {
+      const queue = book.getQueuePosition(id)
+      if (queue && queue.ordersAhead > ahead) {
+        failures.push(`order ${order.id}: ${id} went from ${ahead} to ${queue.ordersAhead} orders ahead`)
+      }
+    })
+  }
+
+  book.checkIntegrity().forEach(v => failures.push(`order ${order.id}: ${v}`))
+  return trades
+}
+
+const runSequence = (seed, length) => {
+  const rng = createRng(seed)
+  const orders = Array.from({ length }, (_, i) => randomOrder(rng, i))
+  const restoreAt = Math.floor(rng() * length)
+  const failures = []
+  const arrivals = new Map(orders.map((order, i) => [order.id, i]))
+
+  const book = new OrderBook('EURUSD')
+  const algorithms = Object.values(MatchingAlgorithm)
+  book.matchingAlgorithm = algorithms[seed % algorithms.length]
+  book.allocationLot = 10
+  let restored = null
+
+  orders.forEach((order, i) => {
+    if (i === restoreAt) {
+      restored = OrderBook.fromState(book.exportState())
+    }
+
+    const trades = checkOrder(book, order, arrivals, failures)
+
+    if (restored) {
+      const replayed = restored.addOrder(new Order({ ...order })) || []
+      if (describeTrades(trades) !== describeTrades(replayed)) {
+        failures.push(`order ${order.id}: restored book produced different fills`)
+      }
+    }
+  })
+
+  if (restored && JSON.stringify(book.snapshot()) !== JSON.stringify(restored.snapshot())) {
+    failures.push('restored book diverged from original')
+  }
+
+  return failures
+}
+
+const iterations = parseInt(process.argv[2] || '500', 10)
+const baseSeed = parseInt(process.argv[3] || `${Date.now() % 100000}`, 10)
+
+let failed = 0
+for (let i = 0; i < iterations; i++) {
+  const seed = baseSeed + i
+  const failures = runSequence(seed, 200)
+  if (failures.length > 0) {
+    failed += 1
+    console.error(`seed ${seed} failed:`)
+    failures.slice(0, 10).forEach(f => console.error(`  ${f}`))
+  }
+}
+
+console.log(`${iterations - failed}/${iterations} sequences passed (base seed ${baseSeed})`)
+process.exit(failed > 0 ? 1 : 0)
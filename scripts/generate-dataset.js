@@ -0,0 +1,194 @@
+// Synthetic dataset generator for ML training.
+//
+//   npm run generate-dataset -- [--sessions 10] [--seed 42] [--steps 500] [--levels 5]
+//     [--windows 1,5,20] [--horizon 1] [--symbols EURUSD,USDJPY] [--profile <name>] [--config <json>]
+//     [--format parquet|csv] [--out dataset.parquet]
+//
+// Runs N seeded headless sessions (session i uses seed + i) and writes one row per symbol
+// per step: order book depth features, trailing return/volatility/flow features over each
+// window (in steps), and the label: the log return of the mid `horizon` steps ahead, in
+// basis points, with its sign. Rows whose label would run past the session end are dropped.
+// --profile and --config take a named profile and engine config overrides like the
+// experiment runner.
+//
+// As in the experiment runner, Math.random and Date.now are replaced with a seeded generator
+// and a clock that advances one updateFrequency per step, so a seed reproduces its dataset.
+
+import { readFileSync, writeFileSync, existsSync } from 'node:fs'
+import { createPinia, setActivePinia } from 'pinia'
+import { useMarketEngineStore, EngineEvent } from '../src/stores/marketEngine.js'
+import { getPipSize } from '../src/stores/instruments.js'
+import { applyProfile } from '../src/stores/profiles.js'
+import { writeParquet } from './parquet.js'
+import { createRng, mergeConfig, withQuietLogs } from './harness.js'
+
+const args = process.argv.slice(2)
+const option = (name, fallback = null) => {
+  const index = args.indexOf(`--${name}`)
+  return index !== -1 && index + 1 < args.length ? args[index + 1] : fallback
+}
+
+const sessions = Number(option('sessions', 10))
+const seed = Number(option('seed', 42))
+const steps = Number(option('steps', 500))
+const levels = Number(option('levels', 5))
+const windows = option('windows', '1,5,20').split(',').map(Number).filter(w => w > 0)
+const horizon = Number(option('horizon', 1))
+const format = option('format', 'parquet')
+const out = option('out', `dataset.${format}`)
+const profile = option('profile')
+const configArg = option('config')
+const overrides = configArg ? JSON.parse(existsSync(configArg) ? readFileSync(configArg, 'utf8') : configArg) : {}
+
+if (!['parquet', 'csv'].includes(format)) {
+  console.error(`Unknown format ${format}, use parquet or csv`)
+  process.exit(1)
+}
+
+const columns = [
+  { name: 'session', type: 'int64' },
+  { name: 'step', type: 'int64' },
+  { name: 'timestamp', type: 'int64' },
+  { name: 'symbol_id', type: 'int64' },
+  { name: 'mid', type: 'double' },
+  { name: 'spread_pips', type: 'double' },
+  { name: 'microprice_offset_pips', type: 'double' },
+  ...Array.from({ length: levels }, (_, i) => [
+    { name: `bid_volume_${i + 1}`, type: 'double' },
+    { name: `ask_volume_${i + 1}`, type: 'double' },
+    { name: `imbalance_${i + 1}`, type: 'double' } // cumulative to this level, -1..1
+  ]).flat(),
+  ...windows.map(w => [
+    { name: `return_${w}_bps`, type: 'double' },
+    { name: `volatility_${w}_bps`, type: 'double' },
+    { name: `signed_flow_${w}`, type: 'double' }, // buyer- minus seller-initiated volume
+    { name: `trades_${w}`, type: 'double' }
+  ]).flat(),
+  { name: `label_return_${horizon}_bps`, type: 'double' },
+  { name: `label_direction_${horizon}`, type: 'int64' }
+]
+
+const depthFeatures = (book, symbol) => {
+  const bids = book.getBids(levels)
+  const asks = book.getAsks(levels)
+  const bestBid = bids[0]?.[0]
+  const bestAsk = asks[0]?.[0]
+  if (!bestBid || !bestAsk) return null
+
+  const mid = (bestBid + bestAsk) / 2
+  const pip = getPipSize(symbol)
+  const topBid = bids[0][1]
+  const topAsk = asks[0][1]
+  const microprice = (bestBid * topAsk + bestAsk * topBid) / (topBid + topAsk)
+
+  const features = [mid, (bestAsk - bestBid) / pip, (microprice - mid) / pip]
+  let bidTotal = 0
+  let askTotal = 0
+  for (let i = 0; i < levels; i++) {
+    const bidVolume = bids[i]?.[1] || 0
+    const askVolume = asks[i]?.[1] || 0
+    bidTotal += bidVolume
+    askTotal += askVolume
+    features.push(bidVolume, askVolume, bidTotal + askTotal > 0 ? (bidTotal - askTotal) / (bidTotal + askTotal) : 0)
+  }
+  return features
+}
+
+const windowFeatures = (mids, flows, step, w) => {
+  const start = Math.max(0, step - w)
+  const returns = []
+  for (let i = start + 1; i <= step; i++) returns.push(Math.log(mids[i] / mids[i - 1]) * 10000)
+  const mean = returns.length > 0 ? returns.reduce((sum, r) => sum + r, 0) / returns.length : 0
+  const variance = returns.length > 1 ? returns.reduce((sum, r) => sum + (r - mean) ** 2, 0) / (returns.length - 1) : 0
+  let signed = 0
+  let trades = 0
+  for (let i = start + 1; i <= step; i++) {
+    signed += flows[i].signed
+    trades += flows[i].trades
+  }
+  return [Math.log(mids[step] / mids[start]) * 10000, Math.sqrt(variance), signed, trades]
+}
+
+const runSession = async (session) => {
+  const realRandom = Math.random
+  const realNow = Date.now
+  let clock = Date.UTC(2024, 0, 2, 8, 0, 0)
+  Math.random = createRng(seed + session)
+  Date.now = () => clock
+
+  try {
+    setActivePinia(createPinia())
+    const engine = useMarketEngineStore()
+    const profileResult = profile ? applyProfile(engine, profile) : { success: true }
+    if (!profileResult.success) throw new Error(`Invalid profile: ${profileResult.errors.join('; ')}`)
+    const result = engine.updateConfig(mergeConfig(engine.config, overrides))
+    if (!result.success) throw new Error(`Invalid config: ${result.errors.join('; ')}`)
+    engine.initializeMarket()
+
+    const symbols = option('symbols')?.split(',') || engine.config.symbols
+    const series = Object.fromEntries(symbols.map(symbol => [symbol, { depth: [], mids: [], flows: [], times: [] }]))
+    let pending = Object.fromEntries(symbols.map(symbol => [symbol, { signed: 0, trades: 0 }]))
+    const unsubscribe = engine.subscribe(event => {
+      if (event.type !== EngineEvent.TRADE_EXECUTED || !pending[event.trade.symbol]) return
+      const flow = pending[event.trade.symbol]
+      flow.signed += event.trade.aggressorSide === 'Sell' ? -event.trade.volume : event.trade.volume
+      flow.trades += 1
+    })
+
+    for (let step = 0; step < steps; step++) {
+      clock += engine.config.updateFrequency
+      await engine.runSimulationStep()
+      symbols.forEach(symbol => {
+        const entry = series[symbol]
+        const features = depthFeatures(engine.getOrderBook(symbol), symbol)
+        // A one-sided book carries the last state forward so steps stay aligned
+        const depth = features || entry.depth[entry.depth.length - 1] || null
+        entry.depth.push(depth)
+        entry.mids.push(depth ? depth[0] : null)
+        entry.flows.push(pending[symbol])
+        entry.times.push(clock)
+      })
+      pending = Object.fromEntries(symbols.map(symbol => [symbol, { signed: 0, trades: 0 }]))
+    }
+    unsubscribe()
+
+    const rows = []
+    symbols.forEach(symbol => {
+      const { depth, mids, flows, times } = series[symbol]
+      const first = mids.findIndex(mid => mid !== null)
+      if (first === -1) return
+      const quotedMids = mids.slice(first)
+      const quotedFlows = flows.slice(first)
+      for (let step = first; step + horizon < steps; step++) {
+        const label = Math.log(mids[step + horizon] / mids[step]) * 10000
+        const windowed = windows.flatMap(w => windowFeatures(quotedMids, quotedFlows, step - first, w))
+        rows.push([
+          session,
+          step,
+          times[step],
+          engine.config.symbols.indexOf(symbol),
+          ...depth[step],
+          ...windowed,
+          label,
+          Math.sign(Math.round(label * 1e6))
+        ])
+      }
+    })
+    return rows
+  } finally {
+    Math.random = realRandom
+    Date.now = realNow
+  }
+}
+
+const rows = []
+for (let session = 0; session < sessions; session++) {
+  rows.push(...await withQuietLogs(() => runSession(session)))
+}
+
+if (format === 'parquet') {
+  writeFileSync(out, writeParquet(columns, rows))
+} else {
+  writeFileSync(out, [columns.map(c => c.name).join(','), ...rows.map(row => row.join(','))].join('\n') + '\n')
+}
+console.log(`Wrote ${rows.length} rows x ${columns.length} columns from ${sessions} sessions to ${out}`)
@@ -0,0 +1,48 @@
+// Helpers shared by the headless scripts: the seeded generator behind every reproducible
+// run, and the config and logging plumbing of the ones that drive the engine.
+
+// Small deterministic PRNG (mulberry32) so runs and failures can be replayed from the seed
+export const createRng = (seed) => {
+  let state = seed >>> 0
+  return () => {
+    state = (state + 0x6d2b79f5) >>> 0
+    let t = state
+    t = Math.imul(t ^ (t >>> 15), t | 1)
+    t ^= t + Math.imul(t ^ (t >>> 7), t | 61)
+    return ((t ^ (t >>> 14)) >>> 0) / 4294967296
+  }
+}
+
+const isPlainObject = (value) => value && typeof value === 'object' && !Array.isArray(value)
+
+// The settings of `overrides` merged into `base`, ready for engine.updateConfig. Nested
+// objects are merged, so { stress: { enabled: true } } only switches that one setting.
+export const mergeConfig = (base, overrides) => {
+  const merged = {}
+  Object.entries(overrides).forEach(([key, value]) => {
+    merged[key] = isPlainObject(value) && isPlainObject(base[key])
+      ? { ...base[key], ...mergeConfig(base[key], value) }
+      : value
+  })
+  return merged
+}
+
+// Silences console.log, so the engine's own progress logging stays out of the script's
+// output, until the returned function is called
+export const quietLogs = () => {
+  const log = console.log
+  console.log = () => {}
+  return () => {
+    console.log = log
+  }
+}
+
+// Runs fn with console.log silenced and resolves to what it returns
+export const withQuietLogs = async (fn) => {
+  const restoreLogs = quietLogs()
+  try {
+    return await fn()
+  } finally {
+    restoreLogs()
+  }
+}
@@ -0,0 +1,167 @@
+// Minimal Parquet writer for flat numeric tables, so dataset exports need no dependencies.
+// Writes one row group with one uncompressed PLAIN data page per column; every column is
+// REQUIRED and either DOUBLE or INT64. Metadata is Thrift compact protocol as the format
+// specifies (https://github.com/apache/parquet-format).
+
+const MAGIC = Buffer.from('PAR1')
+
+// Parquet physical types and enums used here
+const ParquetType = { INT64: 2, DOUBLE: 5 }
+const Repetition = { REQUIRED: 0 }
+const Encoding = { PLAIN: 0, RLE: 3 }
+const PageType = { DATA_PAGE: 0 }
+const Codec = { UNCOMPRESSED: 0 }
+
+// Thrift compact protocol field types
+const T = { I32: 5, I64: 6, BINARY: 8, LIST: 9, STRUCT: 12 }
+
+class CompactWriter {
+  constructor() {
+    this.bytes = []
+  }
+
+  varint(value) {
+    let v = BigInt(value)
+    while (v >= 0x80n) {
+      this.bytes.push(Number((v & 0x7fn) | 0x80n))
+      v >>= 7n
+    }
+    this.bytes.push(Number(v))
+  }
+
+  zigzag(value) {
+    const v = BigInt(value)
+    this.varint(v >= 0n ? v << 1n : ((-v) << 1n) - 1n)
+  }
+
+  binary(value) {
+    const data = Buffer.from(value, 'utf8')
+    this.varint(data.length)
+    this.bytes.push(...data)
+  }
+
+  // fields: [[id, type, value, elementType?], ...] in ascending id order; undefined values are skipped
+  struct(fields) {
+    let lastId = 0
+    for (const [id, type, value, elementType] of fields) {
+      if (value === undefined || value === null) continue
+      const delta = id - lastId
+      if (delta > 0 && delta <= 15) {
+        this.bytes.push((delta << 4) | type)
+      } else {
+        this.bytes.push(type)
+        this.zigzag(id)
+      }
+      lastId = id
+      this.value(type, value, elementType)
+    }
+    this.bytes.push(0)
+  }
+
+  list(elementType, values) {
+    if (values.length < 15) this.bytes.push((values.length << 4) | elementType)
+    else {
+      this.bytes.push(0xf0 | elementType)
+      this.varint(values.length)
+    }
+    values.forEach(value => this.value(elementType, value))
+  }
+
+  value(type, value, elementType) {
+    switch (type) {
+      case T.I32:
+      case T.I64:
+        return this.zigzag(value)
+      case T.BINARY:
+        return this.binary(value)
+      case T.LIST:
+        return this.list(elementType, value)
+      case T.STRUCT:
+        return this.struct(value)
+      default:
+        throw new Error(`Unsupported thrift type ${type}`)
+    }
+  }
+
+  toBuffer() {
+    return Buffer.from(this.bytes)
+  }
+}
+
+const encodeStruct = (fields) => {
+  const writer = new CompactWriter()
+  writer.struct(fields)
+  return writer.toBuffer()
+}
+
+const encodeColumn = (type, values) => {
+  const data = Buffer.alloc(values.length * 8)
+  values.forEach((value, i) => {
+    if (type === ParquetType.INT64) data.writeBigInt64LE(BigInt(Math.trunc(value)), i * 8)
+    else data.writeDoubleLE(value, i * 8)
+  })
+  return data
+}
+
+// columns: [{ name, type: 'double' | 'int64' }]; rows: arrays of numbers in column order.
+// Returns the file contents as a Buffer.
+export const writeParquet = (columns, rows) => {
+  const chunks = [MAGIC]
+  let offset = MAGIC.length
+  const columnChunks = []
+
+  columns.forEach((column, index) => {
+    const type = column.type === 'int64' ? ParquetType.INT64 : ParquetType.DOUBLE
+    const data = encodeColumn(type, rows.map(row => row[index]))
+    const header = encodeStruct([
+      [1, T.I32, PageType.DATA_PAGE],
+      [2, T.I32, data.length],
+      [3, T.I32, data.length],
+      [5, T.STRUCT, [
+        [1, T.I32, rows.length],
+        [2, T.I32, Encoding.PLAIN],
+        [3, T.I32, Encoding.RLE],
+        [4, T.I32, Encoding.RLE]
+      ]]
+    ])
+    const size = header.length + data.length
+    columnChunks.push([
+      [2, T.I64, offset],
+      [3, T.STRUCT, [
+        [1, T.I32, type],
+        [2, T.LIST, [Encoding.PLAIN], T.I32],
+        [3, T.LIST, [column.name], T.BINARY],
+        [4, T.I32, Codec.UNCOMPRESSED],
+        [5, T.I64, rows.length],
+        [6, T.I64, size],
+        [7, T.I64, size],
+        [9, T.I64, offset]
+      ]]
+    ])
+    chunks.push(header, data)
+    offset += size
+  })
+
+  const metadata = encodeStruct([
+    [1, T.I32, 1],
+    [2, T.LIST, [
+      [[4, T.BINARY, 'schema'], [5, T.I32, columns.length]],
+      ...columns.map(column => [
+        [1, T.I32, column.type === 'int64' ? ParquetType.INT64 : ParquetType.DOUBLE],
+        [3, T.I32, Repetition.REQUIRED],
+        [4, T.BINARY, column.name]
+      ])
+    ], T.STRUCT],
+    [3, T.I64, rows.length],
+    [4, T.LIST, [[
+      [1, T.LIST, columnChunks, T.STRUCT],
+      [2, T.I64, offset - MAGIC.length],
+      [3, T.I64, rows.length]
+    ]], T.STRUCT],
+    [6, T.BINARY, 'FXMarketSim generate-dataset']
+  ])
+  const length = Buffer.alloc(4)
+  length.writeUInt32LE(metadata.length)
+  chunks.push(metadata, length, MAGIC)
+  return Buffer.concat(chunks)
+}
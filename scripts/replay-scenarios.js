@@ -0,0 +1,99 @@
+// Golden-scenario regression check.
+//
+//   npm run scenarios            compare every scenario against its golden output
+//   npm run scenarios -- --update  rewrite the golden outputs after an intended change
+//
+// Each scenarios/<name>.events.json holds a recorded order event log. Replaying it through
+// OrderBook must reproduce scenarios/<name>.golden.json (fills and 1-minute candles) exactly.
+
+import { readdirSync, readFileSync, writeFileSync, existsSync } from 'node:fs'
+import { fileURLToPath } from 'node:url'
+import { dirname, join } from 'node:path'
+import { Order, OrderBook } from '../src/stores/marketEngine.js'
+
+const scenarioDir = join(dirname(fileURLToPath(import.meta.url)), '..', 'scenarios')
+const update = process.argv.includes('--update')
+
+const round = (value) => Number(value.toFixed(8))
+
+const replay = (scenario) => {
+  const book = new OrderBook(scenario.symbol)
+  const fills = []
+
+  scenario.events.forEach((event, index) => {
+    const trades = book.addOrder(new Order({
+      id: `${scenario.name}_${index}`,
+      symbol: scenario.symbol,
+      side: event.side,
+      amount: event.amount,
+      price: event.price,
+      participantId: event.participantId,
+      orderType: event.orderType,
+      timestamp: event.t
+    }))
+
+    // Fills are stamped with the event time so output doesn't depend on the wall clock
+    ;(trades || []).forEach(trade => {
+      fills.push({
+        t: event.t,
+        price: trade.price,
+        volume: round(trade.volume),
+        buyerId: trade.buyerId,
+        sellerId: trade.sellerId
+      })
+    })
+  })
+
+  const candles = []
+  fills.forEach(fill => {
+    const bucket = Math.floor(fill.t / 60000) * 60000
+    const last = candles[candles.length - 1]
+    if (!last || last.t !== bucket) {
+      candles.push({ t: bucket, open: fill.price, high: fill.price, low: fill.price, close: fill.price, volume: fill.volume })
+    } else {
+      last.high = Math.max(last.high, fill.price)
+      last.low = Math.min(last.low, fill.price)
+      last.close = fill.price
+      last.volume = round(last.volume + fill.volume)
+    }
+  })
+
+  return { name: scenario.name, symbol: scenario.symbol, fills, candles, book: book.snapshot() }
+}
+
+const firstDifference = (expected, actual) => {
+  for (const key of ['fills', 'candles']) {
+    const length = Math.max(expected[key].length, actual[key].length)
+    for (let i = 0; i < length; i++) {
+      const a = JSON.stringify(expected[key][i])
+      const b = JSON.stringify(actual[key][i])
+      if (a !== b) return `${key}[${i}]: expected ${a}, got ${b}`
+    }
+  }
+  return 'final book differs'
+}
+
+let failed = 0
+const files = readdirSync(scenarioDir).filter(file => file.endsWith('.events.json')).sort()
+
+for (const file of files) {
+  const scenario = JSON.parse(readFileSync(join(scenarioDir, file), 'utf8'))
+  const goldenPath = join(scenarioDir, file.replace('.events.json', '.golden.json'))
+  const result = replay(scenario)
+
+  if (update || !existsSync(goldenPath)) {
+    writeFileSync(goldenPath, JSON.stringify(result, null, 2) + '\n')
+    console.log(`${scenario.name}: golden output written (${result.fills.length} fills)`)
+    continue
+  }
+
+  const golden = JSON.parse(readFileSync(goldenPath, 'utf8'))
+  if (JSON.stringify(golden) === JSON.stringify(result)) {
+    console.log(`${scenario.name}: ok (${result.fills.length} fills, ${result.candles.length} candles)`)
+  } else {
+    failed += 1
+    console.error(`${scenario.name}: MISMATCH ${firstDifference(golden, result)}`)
+  }
+}
+
+process.exit(failed > 0 ? 1 : 0)
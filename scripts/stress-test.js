@@ -0,0 +1,238 @@
+// Load generator for capacity planning and engine benchmarking.
+//
+//   npm run stress-test -- [--rate 2000] [--duration 10] [--mix market=0.3,limit=0.5,cancel=0.2]
+//     [--symbols EURUSD,USDJPY] [--no-agents] [--profile <name>] [--config <json>]
+//     [--watch-config <file>] [--json]
+//
+// Initializes the engine as the app does and, for `duration` seconds of wall-clock time,
+// injects `rate` commands per second drawn from the mix: market orders, limit orders within
+// a few pips of the touch, and cancels of resting limit orders placed by the run. Orders go
+// through placeOrder/cancelOrder for randomly picked registered participants, so they take
+// the same path (participant and credit checks included) as agent flow. Unless --no-agents
+// is given the simulation keeps stepping every updateFrequency alongside the load.
+//
+// Latency is measured per command from submission until its promise settles, i.e. command
+// queue wait plus processing. Schedule lag is how far submissions fell behind the target
+// rate; a lag that keeps growing means the engine can't sustain the rate.
+//
+// --profile starts from a named profile (see profiles.js), --config overrides on top of it.
+// With --watch-config, edits to the JSON file are hot-reloaded into the running engine
+// (see reloadConfig), so settings can be tuned under load; each reload's diff or
+// validation errors are printed to stderr.
+
+import { readFileSync, existsSync, watchFile, unwatchFile } from 'node:fs'
+import { performance } from 'node:perf_hooks'
+import { createPinia, setActivePinia } from 'pinia'
+import { useMarketEngineStore, EngineEvent, OrderSide, OrderType } from '../src/stores/marketEngine.js'
+import { getPipSize } from '../src/stores/instruments.js'
+import { applyProfile } from '../src/stores/profiles.js'
+import { mergeConfig, quietLogs } from './harness.js'
+
+const args = process.argv.slice(2)
+const option = (name, fallback = null) => {
+  const index = args.indexOf(`--${name}`)
+  return index !== -1 && index + 1 < args.length ? args[index + 1] : fallback
+}
+
+const rate = Number(option('rate', 2000))
+const duration = Number(option('duration', 10))
+const withAgents = !args.includes('--no-agents')
+const asJson = args.includes('--json')
+const profile = option('profile')
+const configArg = option('config')
+const watchConfig = option('watch-config')
+const overrides = configArg ? JSON.parse(existsSync(configArg) ? readFileSync(configArg, 'utf8') : configArg) : {}
+
+const mix = Object.fromEntries(option('mix', 'market=0.3,limit=0.5,cancel=0.2').split(',').map(entry => {
+  const [kind, weight] = entry.split('=')
+  return [kind, Number(weight)]
+}))
+const unknownKinds = Object.keys(mix).filter(kind => !['market', 'limit', 'cancel'].includes(kind))
+if (unknownKinds.length > 0 || !(rate > 0) || !(duration > 0)) {
+  console.error(`Invalid options${unknownKinds.length > 0 ? `: unknown mix entries ${unknownKinds.join(', ')}` : ''}`)
+  process.exit(1)
+}
+const mixTotal = Object.values(mix).reduce((sum, weight) => sum + weight, 0)
+
+const percentile = (sorted, p) => sorted.length > 0 ? sorted[Math.min(sorted.length - 1, Math.floor(p * sorted.length))] : 0
+
+const pickKind = () => {
+  let draw = Math.random() * mixTotal
+  for (const [kind, weight] of Object.entries(mix)) {
+    draw -= weight
+    if (draw < 0) return kind
+  }
+  return 'market'
+}
+
+// Quiet the engine's own progress logging so the report stays readable
+const restoreLogs = quietLogs()
+
+setActivePinia(createPinia())
+const engine = useMarketEngineStore()
+const profileResult = profile ? applyProfile(engine, profile) : { success: true }
+if (!profileResult.success) {
+  console.error(`Invalid profile: ${profileResult.errors.join('; ')}`)
+  process.exit(1)
+}
+const result = engine.updateConfig(mergeConfig(engine.config, overrides))
+if (!result.success) {
+  console.error(`Invalid config: ${result.errors.join('; ')}`)
+  process.exit(1)
+}
+engine.initializeMarket()
+
+if (watchConfig) {
+  watchFile(watchConfig, { interval: 500 }, () => {
+    try {
+      const reload = engine.reloadConfig(JSON.parse(readFileSync(watchConfig, 'utf8')), { source: watchConfig })
+      if (!reload.success) console.error(`Config reload rejected: ${reload.errors.join('; ')}`)
+      else reload.diff.forEach(({ path, from, to }) => console.error(`Config ${path}: ${JSON.stringify(from)} -> ${JSON.stringify(to)}`))
+    } catch (error) {
+      console.error(`Config reload failed: ${error.message}`)
+    }
+  })
+}
+
+const symbols = option('symbols')?.split(',') || engine.config.symbols
+const participantIds = Array.from(engine.participants.keys())
+const restingOrders = [] // limit order ids placed by this run that may still rest
+
+let trades = 0
+const unsubscribe = engine.subscribe(event => {
+  if (event.type === EngineEvent.TRADE_EXECUTED) trades += 1
+})
+
+// kind -> { sent, ok, rejected, latencies }
+const stats = Object.fromEntries(['market', 'limit', 'cancel'].map(kind => [kind, { sent: 0, ok: 0, rejected: 0, latencies: [] }]))
+const perSecond = []
+const pending = new Set()
+let maxLagMs = 0
+
+const submit = (kind) => {
+  const submittedAt = performance.now()
+  const symbol = symbols[Math.floor(Math.random() * symbols.length)]
+  const side = Math.random() < 0.5 ? OrderSide.BUY : OrderSide.SELL
+  const participantId = participantIds[Math.floor(Math.random() * participantIds.length)]
+  const amount = engine.getSizeScale(symbol) * 100000 * (1 + Math.floor(Math.random() * 10))
+
+  let command
+  if (kind === 'cancel' && restingOrders.length > 0) {
+    const index = Math.floor(Math.random() * restingOrders.length)
+    const [orderId] = restingOrders.splice(index, 1)
+    command = engine.cancelOrder(orderId)
+  } else if (kind === 'limit' || kind === 'cancel') {
+    // Nothing to cancel yet: place the order a later cancel can target
+    const touch = engine.getMarketPrice(symbol, side)
+    const offset = Math.floor(Math.random() * 5) * getPipSize(symbol)
+    const price = side === OrderSide.BUY ? touch - offset : touch + offset
+    kind = 'limit'
+    command = engine.placeOrder(symbol, side, amount, participantId, OrderType.LIMIT, price)
+      .then(orderId => {
+        if (engine.activeOrders.has(orderId)) restingOrders.push(orderId)
+        return orderId
+      })
+  } else {
+    command = engine.placeOrder(symbol, side, amount, participantId, OrderType.MARKET)
+  }
+
+  const entry = stats[kind]
+  entry.sent += 1
+  const tracked = command
+    .then(() => { entry.ok += 1 }, () => { entry.rejected += 1 })
+    .finally(() => {
+      entry.latencies.push(performance.now() - submittedAt)
+      pending.delete(tracked)
+    })
+  pending.add(tracked)
+}
+
+const yieldToEventLoop = () => new Promise(resolve => setImmediate(resolve))
+
+const start = performance.now()
+const end = start + duration * 1000
+let sent = 0
+let lastStep = start
+let second = { sent: 0, trades: 0, startedAt: start }
+
+while (performance.now() < end) {
+  const now = performance.now()
+  // Submissions owed so far; a burst after a stall catches up to the schedule
+  const due = Math.min(Math.floor((now - start) / 1000 * rate), Math.floor(duration * rate))
+  if (sent < due) maxLagMs = Math.max(maxLagMs, now - (start + (sent / rate) * 1000))
+  while (sent < due) {
+    submit(pickKind())
+    sent += 1
+    second.sent += 1
+  }
+
+  if (withAgents && now - lastStep >= engine.config.updateFrequency) {
+    lastStep = now
+    try {
+      await engine.runSimulationStep()
+    } catch (error) {
+      console.error('Simulation step failed:', error.message)
+    }
+  }
+
+  if (now - second.startedAt >= 1000) {
+    perSecond.push({ second: perSecond.length + 1, commands: second.sent, trades: trades - second.trades })
+    second = { sent: 0, trades, startedAt: now }
+  }
+  await yieldToEventLoop()
+}
+await Promise.all(pending)
+const elapsed = (performance.now() - start) / 1000
+unsubscribe()
+if (watchConfig) unwatchFile(watchConfig)
+restoreLogs()
+
+const summarize = (kind, entry) => {
+  const sorted = entry.latencies.slice().sort((a, b) => a - b)
+  return {
+    kind,
+    sent: entry.sent,
+    ok: entry.ok,
+    rejected: entry.rejected,
+    p50Ms: percentile(sorted, 0.5),
+    p95Ms: percentile(sorted, 0.95),
+    p99Ms: percentile(sorted, 0.99),
+    maxMs: sorted.length > 0 ? sorted[sorted.length - 1] : 0
+  }
+}
+
+const all = { sent: 0, ok: 0, rejected: 0, latencies: [] }
+Object.values(stats).forEach(entry => {
+  all.sent += entry.sent
+  all.ok += entry.ok
+  all.rejected += entry.rejected
+  all.latencies.push(...entry.latencies)
+})
+
+const report = {
+  profile,
+  targetRate: rate,
+  durationSeconds: elapsed,
+  agents: withAgents,
+  symbols,
+  mix,
+  throughput: all.sent / elapsed,
+  tradesPerSecond: trades / elapsed,
+  maxScheduleLagMs: maxLagMs,
+  restingOrdersLeft: restingOrders.length,
+  configReloads: engine.getConfigChangeLog().length,
+  latency: [...Object.entries(stats).map(([kind, entry]) => summarize(kind, entry)), summarize('all', all)],
+  perSecond
+}
+
+if (asJson) {
+  console.log(JSON.stringify(report, null, 2))
+} else {
+  const format = (value) => typeof value === 'number' ? Number(value.toPrecision(4)) : value
+  console.log(`Stress test: ${rate} commands/s target for ${duration}s, ${withAgents ? 'with' : 'without'} agents, ${symbols.length} symbols`)
+  console.log(`Throughput ${format(report.throughput)} commands/s, ${format(report.tradesPerSecond)} trades/s, max schedule lag ${format(maxLagMs)} ms`)
+  console.table(report.latency.map(row => Object.fromEntries(Object.entries(row).map(([k, v]) => [k, format(v)]))))
+  console.table(perSecond)
+}
+
+process.exit(0)
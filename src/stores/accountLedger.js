@@ -0,0 +1,160 @@
+// Append-only account ledger. Balances and open positions are derived by folding
+// these events, so the account can always be rebuilt from its history.
+
+// Account Event Types
+export const AccountEventType = {
+  DEPOSIT: 'Deposit',
+  WITHDRAWAL: 'Withdrawal',
+  BALANCE_SET: 'BalanceSet',
+  RESET: 'Reset',
+  POSITION_OPENED: 'PositionOpened',
+  POSITION_CLOSED: 'PositionClosed',
+  FEE: 'Fee',
+  SWAP: 'Swap',
+  LIQUIDATION: 'Liquidation'
+}
+
+export const createAccountEvent = (events, type, data = {}) => {
+  const last = events[events.length - 1]
+  return {
+    seq: last ? last.seq + 1 : 1,
+    type,
+    timestamp: new Date().toISOString(),
+    ...data
+  }
+}
+
+// Applies one event to a derived { balance, leverage, positions } state
+export const applyAccountEvent = (state, event) => {
+  switch (event.type) {
+    case AccountEventType.DEPOSIT:
+      state.balance += event.amount
+      break
+    case AccountEventType.WITHDRAWAL:
+    case AccountEventType.FEE:
+      state.balance -= event.amount
+      break
+    case AccountEventType.SWAP:
+      state.balance += event.amount
+      break
+    case AccountEventType.BALANCE_SET:
+      state.balance = event.balance
+      break
+    case AccountEventType.RESET:
+      state.balance = event.balance
+      state.leverage = event.leverage
+      state.positions = []
+      break
+    case AccountEventType.POSITION_OPENED:
+      state.positions.push({ ...event.position })
+      break
+    case AccountEventType.POSITION_CLOSED:
+    case AccountEventType.LIQUIDATION:
+      state.balance += event.realizedPnl
+      state.positions = state.positions.filter(p => p.id !== event.positionId)
+      break
+    default:
+      throw new Error(`Unknown account event ${event.type}`)
+  }
+  return state
+}
+
+// A reset is appended rather than clearing the ledger, so the account as it stands is
+// what follows the latest reset (the whole ledger when there is none)
+export const eventsSinceReset = (events) => {
+  for (let i = events.length - 1; i >= 0; i--) {
+    if (events[i].type === AccountEventType.RESET) return events.slice(i)
+  }
+  return events
+}
+
+// Notional traded with a broker in a sim month (YYYY-MM), from the commission fees charged
+// since the latest reset. Trades that paid no commission don't count.
+export const getMonthlyTradedNotional = (events, brokerId, month) => {
+  return eventsSinceReset(events)
+    .filter(event => event.type === AccountEventType.FEE && event.brokerId === brokerId && event.simDate?.startsWith(month))
+    .reduce((sum, event) => sum + (event.notional || 0), 0)
+}
+
+export const replayAccountEvents = (events, initial = { balance: 0, leverage: 100 }) => {
+  const state = { balance: initial.balance, leverage: initial.leverage, positions: [] }
+  eventsSinceReset(events).forEach(event => applyAccountEvent(state, event))
+  return state
+}
+
+// Every position the ledger has seen, open or closed, so closed positions stay queryable
+// after they leave the live book. Closed entries keep the position as opened plus
+// status, close_price, realized_pnl, closed_at and close_type (PositionClosed or
+// Liquidation). Positions still open at a reset are dropped, as in replay.
+export const buildPositionHistory = (events) => {
+  const history = new Map()
+  events.forEach(event => {
+    switch (event.type) {
+      case AccountEventType.RESET:
+        for (const [id, position] of history) {
+          if (position.status === 'Open') history.delete(id)
+        }
+        break
+      case AccountEventType.POSITION_OPENED:
+        history.set(event.position.id, { ...event.position, status: 'Open' })
+        break
+      case AccountEventType.POSITION_CLOSED:
+      case AccountEventType.LIQUIDATION: {
+        const position = history.get(event.positionId)
+        if (!position) break
+        Object.assign(position, {
+          status: 'Closed',
+          close_price: event.closePrice,
+          realized_pnl: event.realizedPnl,
+          closed_at: event.timestamp,
+          close_type: event.type
+        })
+        break
+      }
+      default:
+        break
+    }
+  })
+  return Array.from(history.values())
+}
+
+// Filled orders behind the ledger's positions: one fill opening each position and one
+// closing it. The closing fill is on the opposite side at the close price.
+export const buildFillHistory = (events) => {
+  const fills = []
+  const opened = new Map()
+  events.forEach(event => {
+    if (event.type === AccountEventType.POSITION_OPENED) {
+      const position = event.position
+      opened.set(position.id, position)
+      fills.push({
+        id: position.id,
+        client_order_id: position.client_order_id || null,
+        position_id: position.id,
+        symbol: position.symbol,
+        side: position.side,
+        volume: position.volume,
+        price: position.entry_price,
+        venue_id: position.venue_id || null,
+        intent: 'Open',
+        timestamp: event.timestamp
+      })
+    } else if (event.type === AccountEventType.POSITION_CLOSED || event.type === AccountEventType.LIQUIDATION) {
+      const position = opened.get(event.positionId)
+      if (!position) return
+      fills.push({
+        id: `${position.id}:close`,
+        client_order_id: null,
+        position_id: position.id,
+        symbol: position.symbol,
+        side: position.side === 'Buy' ? 'Sell' : 'Buy',
+        volume: position.volume,
+        price: event.closePrice,
+        venue_id: position.venue_id || null,
+        intent: event.type === AccountEventType.LIQUIDATION ? 'Liquidation' : 'Close',
+        timestamp: event.timestamp
+      })
+    }
+  })
+  return fills
+}
@@ -0,0 +1,301 @@
+import { ref, computed } from 'vue'
+import { defineStore } from 'pinia'
+import { useMarketEngineStore, EngineEvent, OrderSide, OrderType } from './marketEngine'
+
+// Execution Algorithm Types
+export const AlgoType = {
+  TWAP: 'TWAP', // equal slices over a fixed duration
+  VWAP: 'VWAP', // participation rate of observed market volume
+  ICEBERG: 'Iceberg' // shows displaySize at a limit price, refills as it fills
+}
+
+// Algo Status
+export const AlgoStatus = {
+  RUNNING: 'Running',
+  COMPLETED: 'Completed',
+  CANCELLED: 'Cancelled',
+  FAILED: 'Failed'
+}
+
+// Algo Events (sent to progress subscribers)
+export const AlgoEvent = {
+  PROGRESS: 'AlgoProgress',
+  FINISHED: 'AlgoFinished'
+}
+
+const EPSILON = 1e-9
+
+// Execution Algorithm Store
+export const useAlgoStore = defineStore('algos', () => {
+  const marketEngineStore = useMarketEngineStore()
+
+  // State
+  const algos = ref(new Map())
+  const stepIntervalMs = ref(1000)
+  const progressSubscribers = new Map() // handler -> participantId filter (null = all)
+  const childOrderIndex = new Map() // child order id -> algo id
+  let stepTimer = null
+  let algoCounter = 0
+  let unsubscribeEngine = null
+
+  // Computed
+  const algoList = computed(() => Array.from(algos.value.values()))
+  const activeAlgos = computed(() => algoList.value.filter(algo => algo.status === AlgoStatus.RUNNING))
+
+  // Actions
+  // Private channel: pass a participantId to only receive that participant's algo reports
+  const subscribe = (handler, participantId = null) => {
+    progressSubscribers.set(handler, participantId)
+    return () => progressSubscribers.delete(handler)
+  }
+
+  const report = (type, algo) => {
+    const event = { type, ...getAlgoProgress(algo.id) }
+    for (const [handler, participantId] of progressSubscribers) {
+      if (participantId && participantId !== algo.participantId) continue
+      try {
+        handler(event)
+      } catch (error) {
+        console.warn('Algo progress handler failed:', error)
+      }
+    }
+  }
+
+  const validateParent = (parent) => {
+    if (!Object.values(AlgoType).includes(parent.type)) return `Unknown algo type ${parent.type}`
+    if (!marketEngineStore.getOrderBook(parent.symbol)) return `Symbol ${parent.symbol} not found`
+    if (!Object.values(OrderSide).includes(parent.side)) return `Invalid side ${parent.side}`
+    if (!(parent.amount > 0)) return 'Amount must be positive'
+    if (parent.type === AlgoType.VWAP && !(parent.participationRate > 0 && parent.participationRate <= 1)) {
+      return 'Participation rate must be between 0 and 1'
+    }
+    if (parent.type === AlgoType.ICEBERG && !(parent.displaySize > 0 && parent.limitPrice > 0)) {
+      return 'Iceberg orders need a display size and limit price'
+    }
+    return null
+  }
+
+  const submitAlgo = (parent) => {
+    const error = validateParent(parent)
+    if (error) return { success: false, error }
+
+    const now = Date.now()
+    const id = `algo_${++algoCounter}_${now.toString(36)}`
+    const durationMs = parent.durationMs ?? 10 * 60000
+
+    algos.value.set(id, {
+      id,
+      type: parent.type,
+      symbol: parent.symbol,
+      side: parent.side,
+      amount: parent.amount,
+      participantId: parent.participantId || 'user_trader',
+      limitPrice: parent.limitPrice ?? null,
+      participationRate: parent.participationRate ?? null,
+      displaySize: parent.displaySize ?? null,
+      startedAt: now,
+      endsAt: parent.type === AlgoType.ICEBERG && !parent.durationMs ? null : now + durationMs,
+      status: AlgoStatus.RUNNING,
+      submitted: 0,
+      filled: 0,
+      notional: 0,
+      marketVolume: 0, // volume traded by others on the symbol since start (VWAP)
+      childOrders: [],
+      liveChildId: null,
+      lastError: null,
+      finishedAt: null
+    })
+
+    ensureRunning()
+    stepAlgo(algos.value.get(id))
+    return { success: true, data: getAlgoProgress(id) }
+  }
+
+  const cancelAlgo = async (algoId) => {
+    const algo = algos.value.get(algoId)
+    if (!algo || algo.status !== AlgoStatus.RUNNING) return false
+
+    if (algo.liveChildId) {
+      await marketEngineStore.cancelOrder(algo.liveChildId)
+    }
+    finishAlgo(algo, AlgoStatus.CANCELLED)
+    return true
+  }
+
+  const getAlgoProgress = (algoId) => {
+    const algo = algos.value.get(algoId)
+    if (!algo) return null
+
+    return {
+      algoId: algo.id,
+      type: algo.type,
+      participantId: algo.participantId,
+      symbol: algo.symbol,
+      side: algo.side,
+      amount: algo.amount,
+      filled: algo.filled,
+      remaining: Math.max(0, algo.amount - algo.filled),
+      percentComplete: algo.filled / algo.amount * 100,
+      averagePrice: algo.filled > 0 ? algo.notional / algo.filled : null,
+      childOrders: algo.childOrders.length,
+      status: algo.status,
+      lastError: algo.lastError
+    }
+  }
+
+  const placeChild = async (algo, amount, orderType, price = null) => {
+    try {
+      const orderId = await marketEngineStore.placeOrder(
+        algo.symbol,
+        algo.side,
+        amount,
+        algo.participantId,
+        orderType,
+        price
+      )
+      childOrderIndex.set(orderId, algo.id)
+      algo.childOrders.push(orderId)
+      algo.submitted += amount
+
+      // Immediate fills happen while the order is placed, before the id is known here
+      const immediate = marketEngineStore.tradeHistory.filter(t => t.buyOrderId === orderId || t.sellOrderId === orderId)
+      immediate.forEach(trade => recordFill(algo, trade))
+      return orderId
+    } catch (error) {
+      algo.lastError = error.message
+      finishAlgo(algo, AlgoStatus.FAILED)
+      return null
+    }
+  }
+
+  // Respects the optional limit price by skipping slices when the touch is through it
+  const touchAllowed = (algo) => {
+    if (!algo.limitPrice) return true
+    const orderbook = marketEngineStore.getOrderBook(algo.symbol)
+    const touch = algo.side === OrderSide.BUY ? orderbook.getBestAsk() : orderbook.getBestBid()
+    if (touch === null) return false
+    return algo.side === OrderSide.BUY ? touch <= algo.limitPrice : touch >= algo.limitPrice
+  }
+
+  const stepAlgo = async (algo, now = Date.now()) => {
+    if (algo.status !== AlgoStatus.RUNNING) return
+
+    const remaining = algo.amount - algo.filled
+    if (remaining <= EPSILON) {
+      finishAlgo(algo, AlgoStatus.COMPLETED)
+      return
+    }
+
+    switch (algo.type) {
+      case AlgoType.TWAP: {
+        const elapsed = Math.min(1, (now - algo.startedAt) / (algo.endsAt - algo.startedAt))
+        const slice = Math.min(remaining, algo.amount * elapsed - algo.submitted)
+        if (slice > EPSILON && touchAllowed(algo)) {
+          await placeChild(algo, slice, OrderType.MARKET)
+        }
+        break
+      }
+      case AlgoType.VWAP: {
+        const slice = Math.min(remaining, algo.participationRate * algo.marketVolume - algo.submitted)
+        if (slice > EPSILON && touchAllowed(algo)) {
+          await placeChild(algo, slice, OrderType.MARKET)
+        }
+        break
+      }
+      case AlgoType.ICEBERG: {
+        const live = algo.liveChildId && marketEngineStore.getQueuePosition(algo.liveChildId)
+        if (!live) {
+          algo.liveChildId = await placeChild(
+            algo,
+            Math.min(algo.displaySize, remaining),
+            OrderType.LIMIT,
+            algo.limitPrice
+          )
+        }
+        break
+      }
+    }
+
+    if (algo.status === AlgoStatus.RUNNING && algo.endsAt && now >= algo.endsAt) {
+      if (algo.liveChildId) {
+        await marketEngineStore.cancelOrder(algo.liveChildId)
+      }
+      finishAlgo(algo, algo.amount - algo.filled <= EPSILON ? AlgoStatus.COMPLETED : AlgoStatus.CANCELLED)
+    }
+  }
+
+  const finishAlgo = (algo, status) => {
+    if (algo.status !== AlgoStatus.RUNNING) return
+    algo.status = status
+    algo.finishedAt = Date.now()
+    algo.liveChildId = null
+    report(AlgoEvent.FINISHED, algo)
+
+    if (activeAlgos.value.length === 0) {
+      stopRunning()
+    }
+  }
+
+  const recordFill = (algo, trade) => {
+    algo.filled += trade.volume
+    algo.notional += trade.volume * trade.price
+    report(AlgoEvent.PROGRESS, algo)
+  }
+
+  const handleEngineEvent = (event) => {
+    if (event.type !== EngineEvent.TRADE_EXECUTED) return
+    const trade = event.trade
+
+    for (const orderId of [trade.buyOrderId, trade.sellOrderId]) {
+      const algo = algos.value.get(childOrderIndex.get(orderId))
+      if (algo) {
+        recordFill(algo, trade)
+        return
+      }
+    }
+
+    // Everyone else's volume feeds the participation-rate algos on that symbol
+    for (const algo of activeAlgos.value) {
+      if (algo.type === AlgoType.VWAP && algo.symbol === trade.symbol) {
+        algo.marketVolume += trade.volume
+      }
+    }
+  }
+
+  const ensureRunning = () => {
+    if (!unsubscribeEngine) {
+      unsubscribeEngine = marketEngineStore.subscribe(handleEngineEvent)
+    }
+    if (!stepTimer) {
+      stepTimer = setInterval(() => {
+        activeAlgos.value.forEach(algo => stepAlgo(algo))
+      }, stepIntervalMs.value)
+    }
+  }
+
+  const stopRunning = () => {
+    clearInterval(stepTimer)
+    stepTimer = null
+  }
+
+  return {
+    // State
+    algos,
+    stepIntervalMs,
+
+    // Computed
+    algoList,
+    activeAlgos,
+
+    // Actions
+    submitAlgo,
+    cancelAlgo,
+    getAlgoProgress,
+    subscribe,
+
+    // Enums
+    AlgoType,
+    AlgoStatus,
+    AlgoEvent
+  }
+})
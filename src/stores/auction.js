@@ -0,0 +1,100 @@
+// Call auctions. Orders collected during the auction window are uncrossed at a single
+// price: the one that maximizes matched volume, then minimizes the imbalance left over,
+// then lies closest to the reference price. Market orders take part at any price. The same
+// calculation run mid-window gives the indicative price.
+//
+// Orders here are { side, amount, price, timestamp }, price null for market orders.
+
+// Auction Types
+export const AuctionType = {
+  OPEN: 'Open', // reopening after the weekend
+  FIX: 'Fix' // benchmark fixing, e.g. the 4pm fix
+}
+
+const isBuy = (order) => order.side === 'Buy'
+
+// Whether an order would trade at `price`
+const executableAt = (order, price) => {
+  if (order.price === null || order.price === undefined) return true
+  return isBuy(order) ? order.price >= price : order.price <= price
+}
+
+const isBetter = (candidate, best, referencePrice) => {
+  if (candidate.matchedVolume !== best.matchedVolume) return candidate.matchedVolume > best.matchedVolume
+  const imbalance = Math.abs(candidate.imbalance)
+  const bestImbalance = Math.abs(best.imbalance)
+  if (imbalance !== bestImbalance) return imbalance < bestImbalance
+  if (referencePrice > 0) {
+    const distance = Math.abs(candidate.price - referencePrice)
+    const bestDistance = Math.abs(best.price - referencePrice)
+    if (distance !== bestDistance) return distance < bestDistance
+  }
+  return candidate.price < best.price
+}
+
+// { price, matchedVolume, buyVolume, sellVolume, imbalance } where imbalance is buy minus
+// sell volume executable at the price. price is null when nothing crosses.
+export const computeUncross = (orders, referencePrice = null) => {
+  const candidates = new Set(orders.filter(order => order.price > 0).map(order => order.price))
+  // Market orders on both sides and no limits still uncross, at the reference price
+  if (referencePrice > 0) candidates.add(referencePrice)
+
+  let best = null
+  for (const price of candidates) {
+    let buyVolume = 0
+    let sellVolume = 0
+    for (const order of orders) {
+      if (!executableAt(order, price)) continue
+      if (isBuy(order)) buyVolume += order.amount
+      else sellVolume += order.amount
+    }
+    const candidate = {
+      price,
+      matchedVolume: Math.min(buyVolume, sellVolume),
+      buyVolume,
+      sellVolume,
+      imbalance: buyVolume - sellVolume
+    }
+    if (!best || isBetter(candidate, best, referencePrice)) best = candidate
+  }
+
+  if (!best || best.matchedVolume <= 0) {
+    const volume = (side) => orders.filter(order => order.side === side).reduce((sum, order) => sum + order.amount, 0)
+    return { price: null, matchedVolume: 0, buyVolume: volume('Buy'), sellVolume: volume('Sell'), imbalance: volume('Buy') - volume('Sell') }
+  }
+  return best
+}
+
+// Market orders first, then the better limit price, then the earlier order
+const priority = (a, b) => {
+  const aMarket = a.price === null || a.price === undefined
+  const bMarket = b.price === null || b.price === undefined
+  if (aMarket !== bMarket) return aMarket ? -1 : 1
+  if (!aMarket && a.price !== b.price) return isBuy(a) ? b.price - a.price : a.price - b.price
+  return a.timestamp - b.timestamp
+}
+
+// Pairs buy and sell orders for `volume` at `price`: [{ buy, sell, volume }]. The side with
+// more executable volume is filled in priority order.
+export const allocateUncross = (orders, price, volume) => {
+  const buys = orders.filter(order => isBuy(order) && executableAt(order, price)).sort(priority)
+  const sells = orders.filter(order => !isBuy(order) && executableAt(order, price)).sort(priority)
+  const left = new Map(orders.map(order => [order, order.amount]))
+
+  const fills = []
+  let remaining = volume
+  let b = 0
+  let s = 0
+  while (remaining > 1e-9 && b < buys.length && s < sells.length) {
+    const buy = buys[b]
+    const sell = sells[s]
+    const fill = Math.min(remaining, left.get(buy), left.get(sell))
+    fills.push({ buy, sell, volume: fill })
+    remaining -= fill
+    left.set(buy, left.get(buy) - fill)
+    left.set(sell, left.get(sell) - fill)
+    if (left.get(buy) <= 1e-9) b++
+    if (left.get(sell) <= 1e-9) s++
+  }
+  return fills
+}
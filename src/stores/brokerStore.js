@@ -1,5 +1,6 @@
 import { ref, reactive, computed } from 'vue'
 import { defineStore } from 'pinia'
+import { getPipSize, SWAP_RATES } from './instruments'
 
 // Broker Types
 export const BrokerType = {
@@ -18,6 +19,59 @@ export const ExecutionModel = {
   EXCHANGE_EXECUTION: 'ExchangeExecution'
 }
 
+// Commission Schemes
+export const CommissionScheme = {
+  NONE: 'None',
+  PER_LOT: 'PerLot', // rate in account currency per standard lot
+  PER_MILLION: 'PerMillion', // rate per million notional
+  PERCENTAGE: 'Percentage', // rate as a percent of notional
+  TIERED: 'Tiered' // per-million rate discounted by the account's monthly volume
+}
+
+const STANDARD_LOT = 100000
+
+// Normalizes a commission setting; a bare number is the old flat per-lot rate
+const createCommission = (commission) => {
+  if (typeof commission === 'number') {
+    return { scheme: commission > 0 ? CommissionScheme.PER_LOT : CommissionScheme.NONE, rate: commission, tiers: [] }
+  }
+  return {
+    scheme: commission?.scheme || CommissionScheme.NONE,
+    rate: commission?.rate || 0,
+    tiers: (commission?.tiers || []).map(tier => ({ ...tier }))
+  }
+}
+
+// Slippage shape. Market fills slip against the trader with adverseProbability;
+// stops always slip adversely and by more, especially in fast markets.
+const defaultSlippageModel = {
+  adverseProbability: 0.7,
+  stopMultiplier: 2,
+  fastMarketMultiplier: 3,
+  fastMarketVolatility: 1.5 // engine volatility factor at which a market counts as fast
+}
+
+// How far execution degrades at full market stress (see the engine's getMarketStress)
+const defaultStressProfile = {
+  spreadMultiplier: 3,
+  latencyMultiplier: 4,
+  requoteProbability: 0.3 // added on top of the normal requote rate
+}
+
+// Broker Reject Reasons (order entry refusals, returned as `reason`)
+export const BrokerRejectReason = {
+  SYMBOL_NOT_OFFERED: 'SymbolNotOffered',
+  BROKER_OFFLINE: 'BrokerOffline', // no connection to the trade server
+  OFF_QUOTES: 'OffQuotes' // degraded broker refused the order
+}
+
+// Broker Status (see setBrokerIncident)
+export const BrokerStatus = {
+  ONLINE: 'Online',
+  DEGRADED: 'Degraded', // rejects rejectRate of orders and adds latencyMs to the rest
+  OFFLINE: 'Offline' // refuses every order
+}
+
 // Liquidity Provider Class
 class LiquidityProvider {
   constructor(data) {
@@ -35,10 +89,12 @@ class Broker {
     this.name = data.name
     this.brokerType = data.brokerType
     this.spread = data.spread || 0.0002
-    this.commission = data.commission || 0
+    this.commission = createCommission(data.commission)
     this.executionModel = data.executionModel || this.getDefaultExecutionModel()
     this.liquidityProviders = data.liquidityProviders || this.generateLiquidityProviders()
     this.slippageFactor = data.slippageFactor || this.getDefaultSlippageFactor()
+    this.slippageModel = { ...defaultSlippageModel, ...data.slippageModel }
+    this.stressProfile = { ...defaultStressProfile, ...data.stressProfile }
     this.requoteProbability = data.requoteProbability || this.getDefaultRequoteProbability()
     this.maxLeverage = data.maxLeverage || this.getDefaultMaxLeverage()
     this.minTradeSize = data.minTradeSize || 1000
@@ -268,50 +324,122 @@ class Broker {
     return Math.random() < 0.3 // 30% chance of slippage
   }
 
-  applySlippage(price, side) {
-    const slippage = Math.random() * this.slippageFactor
-    return side === 'Buy' ? price + slippage : price - slippage
+  applySlippage(price, side, { stop = false, fastMarket = false } = {}) {
+    const model = this.slippageModel
+    let slippage = Math.random() * this.slippageFactor
+    if (stop) slippage *= model.stopMultiplier
+    if (fastMarket) slippage *= model.fastMarketMultiplier
+    
+    const adverse = stop || Math.random() < model.adverseProbability
+    return (side === 'Buy') === adverse ? price + slippage : price - slippage
   }
 
-  shouldRequote() {
-    return Math.random() < this.requoteProbability
+  // A triggered stop fills at the first available price. If the market gapped through
+  // the stop level that is the gapped price, then stop slippage comes on top.
+  fillStop(stopPrice, side, marketPrice, { fastMarket = false } = {}) {
+    const through = side === 'Buy' ? Math.max(stopPrice, marketPrice) : Math.min(stopPrice, marketPrice)
+    return this.applySlippage(through, side, { stop: true, fastMarket })
+  }
+
+  shouldRequote(stress = 0) {
+    return Math.random() < this.requoteProbability + this.getStressRequoteProbability(stress)
+  }
+
+  getStressRequoteProbability(stress = 0) {
+    return stress * this.stressProfile.requoteProbability
+  }
+
+  // Linear from 1 at no stress to the profile multiplier at full stress
+  getStressMultiplier(stress, multiplier) {
+    return 1 + stress * (multiplier - 1)
   }
 
   calculateRequoteAdjustment() {
     return (Math.random() - 0.5) * 0.001 // ±0.05% adjustment
   }
 
-  calculateCommission(volume) {
-    switch (this.brokerType) {
-      case BrokerType.ECN:
-        return this.commission * (volume / 100000) // Per lot
-      case BrokerType.DIRECT_ACCESS:
-        return volume * 0.000001 // 0.0001% of volume
+  // Commission for one side of a trade. Notional is in the account currency and
+  // monthlyVolume is the account's notional already traded with this broker this month.
+  calculateCommission(volume, { notional = volume, monthlyVolume = 0 } = {}) {
+    const { scheme, rate } = this.commission
+    switch (scheme) {
+      case CommissionScheme.PER_LOT:
+        return rate * (volume / STANDARD_LOT)
+      case CommissionScheme.PER_MILLION:
+        return rate * (notional / 1000000)
+      case CommissionScheme.PERCENTAGE:
+        return notional * rate / 100
+      case CommissionScheme.TIERED:
+        return this.getTierRate(monthlyVolume) * (notional / 1000000)
       default:
-        return 0 // Spread-based brokers typically don't charge commission
+        return 0
     }
   }
 
-  calculateSwap(symbol, side, volume) {
-    const baseSwapRates = {
-      'EURUSD': { Buy: -0.5, Sell: -2.1 },
-      'GBPUSD': { Buy: 0.8, Sell: -3.2 },
-      'USDJPY': { Buy: 2.1, Sell: -5.4 },
-      'USDCHF': { Buy: 1.2, Sell: -2.8 },
-      'AUDUSD': { Buy: -1.5, Sell: -0.3 },
-      'USDCAD': { Buy: 0.5, Sell: -1.8 }
+  getTierRate(monthlyVolume) {
+    const tier = this.commission.tiers
+      .filter(t => monthlyVolume >= t.minMonthlyVolume)
+      .sort((a, b) => b.minMonthlyVolume - a.minMonthlyVolume)[0]
+    return tier ? tier.ratePerMillion : this.commission.rate
+  }
+
+  describeCommission() {
+    const { scheme, rate } = this.commission
+    switch (scheme) {
+      case CommissionScheme.PER_LOT:
+        return `$${rate.toFixed(2)} / lot`
+      case CommissionScheme.PER_MILLION:
+        return `$${rate.toFixed(2)} / million`
+      case CommissionScheme.PERCENTAGE:
+        return `${rate}%`
+      case CommissionScheme.TIERED:
+        return `Tiered from $${this.getTierRate(0).toFixed(2)} / million`
+      default:
+        return 'None'
     }
-    
-    const rates = baseSwapRates[symbol]
+  }
+
+  calculateSwap(symbol, side, volume) {
+    const rates = SWAP_RATES[symbol]
     if (!rates) return 0
     
     const baseSwapRate = rates[side] || 0
     return (baseSwapRate * volume) / 100000 // Per lot
   }
 
-  getEffectiveSpread(symbol) {
+  // Spread given the underlying book's spread when known, else the static estimate
+  getEffectiveSpread(symbol, stress = 0, underlyingSpread = null) {
+    if (underlyingSpread !== null) {
+      return underlyingSpread + this.getQuoteMarkup(symbol, stress)
+    }
     const baseSpread = this.getBaseSpread(symbol)
-    return baseSpread + this.spread
+    return (baseSpread + this.spread) * this.getStressMultiplier(stress, this.stressProfile.spreadMultiplier)
+  }
+
+  // What the broker adds to the underlying spread: its own spread plus the weighted LP
+  // markups (charged on each side). Configured in 4-decimal pair units, scaled by pip size.
+  getQuoteMarkup(symbol, stress = 0) {
+    const totalWeight = this.liquidityProviders.reduce((sum, provider) => sum + provider.weight, 0)
+    const providerMarkup = totalWeight > 0
+      ? this.liquidityProviders.reduce((sum, provider) => sum + provider.spreadMarkup * provider.weight, 0) / totalWeight
+      : 0
+    const scale = getPipSize(symbol) / 0.0001
+    return (this.spread + 2 * providerMarkup) * scale * this.getStressMultiplier(stress, this.stressProfile.spreadMultiplier)
+  }
+
+  // Broker bid/ask around the live touch, so the quote widens with the book
+  getQuote(symbol, bid, ask, stress = 0) {
+    const markup = this.getQuoteMarkup(symbol, stress)
+    const quote = {
+      bid: bid - markup / 2,
+      ask: ask + markup / 2,
+      underlyingBid: bid,
+      underlyingAsk: ask,
+      underlyingSpread: ask - bid,
+      markup
+    }
+    quote.spread = quote.ask - quote.bid
+    return quote
   }
 
   getBaseSpread(symbol) {
@@ -326,6 +454,10 @@ class Broker {
     return baseSpreads[symbol] || 0.0002
   }
 
+  offersSymbol(symbol) {
+    return this.availableSymbols.includes(symbol)
+  }
+
   canExecuteOrder(order) {
     // Check trade size limits
     if (order.amount < this.minTradeSize || order.amount > this.maxTradeSize) {
@@ -333,7 +465,7 @@ class Broker {
     }
     
     // Check symbol availability
-    if (!this.availableSymbols.includes(order.symbol)) {
+    if (!this.offersSymbol(order.symbol)) {
       return false
     }
     
@@ -347,7 +479,7 @@ class Broker {
     }
   }
 
-  getExecutionSpeedMs() {
+  getExecutionSpeedMs(stress = 0) {
     const baseSpeed = {
       [ExecutionModel.INSTANT_EXECUTION]: () => 1 + Math.random() * 9,
       [ExecutionModel.MARKET_EXECUTION]: () => 10 + Math.random() * 40,
@@ -356,7 +488,8 @@ class Broker {
     }
     
     const speedFn = baseSpeed[this.executionModel]
-    return speedFn ? Math.round(speedFn()) : 50
+    const latency = speedFn ? speedFn() : 50
+    return Math.round(latency * this.getStressMultiplier(stress, this.stressProfile.latencyMultiplier))
   }
 
   getMarginRequirement(symbol, volume, leverage) {
@@ -376,19 +509,30 @@ export const useBrokerStore = defineStore('broker', () => {
   // State
   const brokers = ref(new Map())
   const selectedBrokerId = ref(null)
+  // brokerId -> { status, rejectRate, latencyMs, startedAt, until, message }, for
+  // practicing failover; an incident ends by itself at `until` (null: until cleared)
+  const brokerIncidents = ref(new Map())
   
   // Broker configuration templates
   const brokerTemplates = reactive({
     [BrokerType.DIRECT_ACCESS]: {
       name: 'Direct Access Pro',
       spread: 0.0001,
-      commission: 0,
+      commission: {
+        scheme: CommissionScheme.TIERED,
+        rate: 1.0,
+        tiers: [
+          { minMonthlyVolume: 0, ratePerMillion: 1.0 },
+          { minMonthlyVolume: 50000000, ratePerMillion: 0.8 },
+          { minMonthlyVolume: 250000000, ratePerMillion: 0.6 }
+        ]
+      },
       description: 'Direct access to liquidity pool with minimal spreads and fastest execution'
     },
     [BrokerType.ECN]: {
       name: 'ECN Elite',
       spread: 0.0,
-      commission: 3.5,
+      commission: { scheme: CommissionScheme.PER_LOT, rate: 3.5 },
       description: 'Electronic Communication Network with multiple liquidity providers'
     },
     [BrokerType.MARKET_MAKER]: {
@@ -406,7 +550,7 @@ export const useBrokerStore = defineStore('broker', () => {
     [BrokerType.HYBRID]: {
       name: 'Hybrid Advanced',
       spread: 0.0002,
-      commission: 1.5,
+      commission: { scheme: CommissionScheme.PER_MILLION, rate: 15 },
       description: 'Combination of ECN and Market Maker features'
     }
   })
@@ -433,6 +577,7 @@ export const useBrokerStore = defineStore('broker', () => {
 
   const deleteBroker = (brokerId) => {
     const deleted = brokers.value.delete(brokerId)
+    brokerIncidents.value.delete(brokerId)
     if (selectedBrokerId.value === brokerId) {
       selectedBrokerId.value = null
     }
@@ -509,12 +654,117 @@ export const useBrokerStore = defineStore('broker', () => {
     return broker.processOrder(order)
   }
 
-  const calculateTradingCosts = (brokerId, symbol, side, volume, leverage = 100) => {
+  // Structured refusal for an order in a symbol the broker doesn't offer, null if it does
+  const checkSymbolAvailability = (brokerId, symbol) => {
+    const broker = brokers.value.get(brokerId)
+    if (!broker || broker.offersSymbol(symbol)) return null
+    return {
+      success: false,
+      error: `${symbol} is not offered by ${broker.name}`,
+      reason: BrokerRejectReason.SYMBOL_NOT_OFFERED,
+      brokerId: broker.id,
+      symbol,
+      availableSymbols: [...broker.availableSymbols]
+    }
+  }
+
+  const setBrokerIncident = (brokerId, { status = BrokerStatus.OFFLINE, durationMs = 60000, rejectRate = 0.3, latencyMs = 500, message = null } = {}) => {
+    if (!brokers.value.has(brokerId)) return { success: false, error: `Broker ${brokerId} not found` }
+    if (!Object.values(BrokerStatus).includes(status)) return { success: false, error: `Unknown broker status ${status}` }
+    if (status === BrokerStatus.ONLINE) return clearBrokerIncident(brokerId)
+    if (!(rejectRate >= 0 && rejectRate <= 1)) return { success: false, error: 'rejectRate must be between 0 and 1' }
+    if (!(latencyMs >= 0)) return { success: false, error: 'latencyMs must be >= 0' }
+
+    const now = Date.now()
+    const incident = {
+      status,
+      rejectRate: status === BrokerStatus.OFFLINE ? 1 : rejectRate,
+      latencyMs: status === BrokerStatus.OFFLINE ? 0 : latencyMs,
+      startedAt: now,
+      until: durationMs ? now + durationMs : null,
+      message
+    }
+    brokerIncidents.value.set(brokerId, incident)
+    return { success: true, data: { brokerId, ...incident } }
+  }
+
+  const clearBrokerIncident = (brokerId) => {
+    brokerIncidents.value.delete(brokerId)
+    return { success: true, data: { brokerId, status: BrokerStatus.ONLINE } }
+  }
+
+  const getBrokerIncident = (brokerId) => {
+    const incident = brokerIncidents.value.get(brokerId)
+    if (incident?.until !== null && incident?.until <= Date.now()) {
+      brokerIncidents.value.delete(brokerId)
+      return null
+    }
+    return incident || null
+  }
+
+  const getBrokerStatus = (brokerId) => getBrokerIncident(brokerId)?.status || BrokerStatus.ONLINE
+
+  // Structured refusal when an incident stops the order, null when it gets through. An
+  // order that gets through a degraded broker should wait getIncidentLatencyMs first.
+  const checkBrokerHealth = (brokerId) => {
+    const incident = getBrokerIncident(brokerId)
+    if (!incident) return null
+    const broker = brokers.value.get(brokerId)
+    const retryAfterMs = incident.until !== null ? incident.until - Date.now() : null
+
+    if (incident.status === BrokerStatus.OFFLINE) {
+      return {
+        success: false,
+        error: incident.message || `No connection to ${broker.name} trade server`,
+        reason: BrokerRejectReason.BROKER_OFFLINE,
+        brokerId,
+        retryAfterMs
+      }
+    }
+    if (Math.random() < incident.rejectRate) {
+      return {
+        success: false,
+        error: incident.message || `${broker.name}: off quotes, trade server busy`,
+        reason: BrokerRejectReason.OFF_QUOTES,
+        brokerId,
+        retryAfterMs
+      }
+    }
+    return null
+  }
+
+  const getIncidentLatencyMs = (brokerId) => getBrokerIncident(brokerId)?.latencyMs || 0
+
+  // Every broker with the instruments it offers and its order entry limits
+  const getBrokerDirectory = () => {
+    return brokerList.value.map(broker => ({
+      id: broker.id,
+      name: broker.name,
+      brokerType: broker.brokerType,
+      executionModel: broker.executionModel,
+      commission: broker.describeCommission(),
+      maxLeverage: broker.maxLeverage,
+      minTradeSize: broker.minTradeSize,
+      maxTradeSize: broker.maxTradeSize,
+      availableSymbols: [...broker.availableSymbols],
+      status: getBrokerStatus(broker.id),
+      selected: broker.id === selectedBrokerId.value
+    }))
+  }
+
+  // Commission for a trade; tiered schemes need the account's monthly volume with the broker
+  const calculateCommission = (brokerId, volume, { notional = volume, monthlyVolume = 0 } = {}) => {
+    const broker = brokers.value.get(brokerId)
+    if (!broker) return 0
+    return broker.calculateCommission(volume, { notional, monthlyVolume })
+  }
+
+  const calculateTradingCosts = (brokerId, symbol, side, volume, leverage = 100, { notional = volume, monthlyVolume = 0, stress = 0, underlyingSpread = null } = {}) => {
     const broker = brokers.value.get(brokerId)
     if (!broker) return null
     
-    const commission = broker.calculateCommission(volume)
-    const spread = broker.getEffectiveSpread(symbol)
+    const commission = calculateCommission(brokerId, volume, { notional, monthlyVolume })
+    const spread = broker.getEffectiveSpread(symbol, stress, underlyingSpread)
     const swap = broker.calculateSwap(symbol, side, volume)
     const marginRequired = broker.getMarginRequirement(symbol, volume, leverage)
     
@@ -527,14 +777,21 @@ export const useBrokerStore = defineStore('broker', () => {
     }
   }
 
-  const getBrokerPerformanceMetrics = (brokerId) => {
+  // Broker-specific bid/ask for a symbol given the underlying book's touch
+  const getBrokerQuote = (brokerId, symbol, { bid, ask }, stress = 0) => {
+    const broker = brokers.value.get(brokerId)
+    if (!broker) return null
+    return { brokerId, symbol, ...broker.getQuote(symbol, bid, ask, stress) }
+  }
+
+  const getBrokerPerformanceMetrics = (brokerId, stress = 0) => {
     const broker = brokers.value.get(brokerId)
     if (!broker) return null
     
     return {
-      executionSpeed: broker.getExecutionSpeedMs(),
+      executionSpeed: broker.getExecutionSpeedMs(stress),
       slippageFactor: broker.slippageFactor,
-      requoteProbability: broker.requoteProbability,
+      requoteProbability: broker.requoteProbability + broker.getStressRequoteProbability(stress),
       maxLeverage: broker.maxLeverage,
       symbolCount: broker.availableSymbols.length,
       liquidityProviders: broker.liquidityProviders.length
@@ -549,8 +806,10 @@ export const useBrokerStore = defineStore('broker', () => {
         name: broker.name,
         brokerType: broker.brokerType,
         spread: broker.spread,
-        commission: broker.commission,
+        commission: { ...broker.commission, tiers: broker.commission.tiers.map(tier => ({ ...tier })) },
         slippageFactor: broker.slippageFactor,
+        slippageModel: { ...broker.slippageModel },
+        stressProfile: { ...broker.stressProfile },
         requoteProbability: broker.requoteProbability,
         maxLeverage: broker.maxLeverage,
         minTradeSize: broker.minTradeSize,
@@ -574,6 +833,7 @@ export const useBrokerStore = defineStore('broker', () => {
   }
 
   const resetToDefaults = () => {
+    brokerIncidents.value.clear()
     initializeDefaultBrokers()
   }
 
@@ -582,6 +842,7 @@ export const useBrokerStore = defineStore('broker', () => {
     brokers,
     selectedBrokerId,
     brokerTemplates,
+    brokerIncidents,
 
     // Computed
     selectedBroker,
@@ -596,7 +857,16 @@ export const useBrokerStore = defineStore('broker', () => {
     getBroker,
     initializeDefaultBrokers,
     processOrderWithBroker,
+    checkSymbolAvailability,
+    getBrokerDirectory,
+    setBrokerIncident,
+    clearBrokerIncident,
+    getBrokerStatus,
+    checkBrokerHealth,
+    getIncidentLatencyMs,
     calculateTradingCosts,
+    getBrokerQuote,
+    calculateCommission,
     getBrokerPerformanceMetrics,
     exportBrokerConfig,
     importBrokerConfig,
@@ -608,6 +878,9 @@ export const useBrokerStore = defineStore('broker', () => {
 
     // Enums
     BrokerType,
-    ExecutionModel
+    ExecutionModel,
+    CommissionScheme,
+    BrokerRejectReason,
+    BrokerStatus
   }
 })
\ No newline at end of file
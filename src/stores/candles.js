@@ -0,0 +1,193 @@
+// Candle series per timeframe, maintained incrementally from ticks so every timeframe is
+// stored and served as-is instead of being rebuilt from 1m data. Timestamps are epoch
+// seconds aligned to the timeframe boundary. Each candle remembers the times of the ticks
+// that set its open and close (firstTick/lastTick), so a tick delivered late still lands
+// in time order: a candle always equals the time-ordered aggregation of its ticks, and a
+// higher timeframe always equals the aggregation of its 1m candles.
+
+export const CANDLE_TIMEFRAMES = {
+  '1m': 60,
+  '5m': 300,
+  '15m': 900,
+  '1h': 3600,
+  '4h': 14400,
+  '1d': 86400
+}
+
+// Candles kept per timeframe
+export const CANDLE_LIMITS = {
+  '1m': 1000,
+  '5m': 500,
+  '15m': 500,
+  '1h': 500,
+  '4h': 500,
+  '1d': 500
+}
+
+// How missing bars are filled when ticks stop arriving for a while
+export const CandleBackfill = {
+  FLAT: 'Flat', // repeat the last close
+  INTERPOLATED: 'Interpolated' // walk linearly from the last close to the next tick
+}
+
+// Backfilled candles carry `filled: true` and no volume. Gaps longer than maxGapCandles
+// are only filled for the bars right before the tick that ends them.
+export const candleBackfillSettings = {
+  enabled: true,
+  mode: CandleBackfill.FLAT,
+  maxGapCandles: 1440
+}
+
+export const createCandleSeries = () => {
+  return Object.fromEntries(Object.keys(CANDLE_TIMEFRAMES).map(tf => [tf, []]))
+}
+
+// Index of the first candle at or after `timestamp`
+const lowerBound = (candles, timestamp) => {
+  let lo = 0
+  let hi = candles.length
+  while (lo < hi) {
+    const mid = (lo + hi) >> 1
+    if (candles[mid].timestamp < timestamp) lo = mid + 1
+    else hi = mid
+  }
+  return lo
+}
+
+const newCandle = (bucket, price, volume, timestamp) => ({
+  timestamp: bucket,
+  open: price,
+  high: price,
+  low: price,
+  close: price,
+  volume,
+  firstTick: timestamp,
+  lastTick: timestamp
+})
+
+// Flat or interpolated bars for the buckets strictly between `from` and the new tick
+const backfillCandles = (from, bucket, seconds, price, settings) => {
+  const missing = Math.round((bucket - from.timestamp) / seconds) - 1
+  const count = Math.min(missing, settings.maxGapCandles)
+  const fillers = []
+  let previous = from.close
+  for (let k = missing - count + 1; k <= missing; k++) {
+    const close = settings.mode === CandleBackfill.INTERPOLATED
+      ? from.close + (price - from.close) * k / (missing + 1)
+      : from.close
+    const timestamp = from.timestamp + k * seconds
+    fillers.push({
+      timestamp,
+      open: previous,
+      high: Math.max(previous, close),
+      low: Math.min(previous, close),
+      close,
+      volume: 0,
+      firstTick: timestamp,
+      lastTick: timestamp,
+      filled: true
+    })
+    previous = close
+  }
+  return fillers
+}
+
+// Rebuilds the fillers on both sides of the real candle at `index` after it changed, so
+// they keep leading from the previous close to the next real bar
+const refreshFillers = (candles, index, seconds, backfill) => {
+  const span = (from, to) => {
+    if (to - from < 2 || !candles.slice(from + 1, to).every(candle => candle.filled)) return
+    const fillers = backfillCandles(candles[from], candles[to].timestamp, seconds, candles[to].open, backfill)
+    candles.splice(from + 1, to - from - 1, ...fillers)
+  }
+  let next = index + 1
+  while (next < candles.length && candles[next].filled) next += 1
+  if (next < candles.length) span(index, next)
+  let previous = index - 1
+  while (previous >= 0 && candles[previous].filled) previous -= 1
+  if (previous >= 0) span(previous, index)
+}
+
+// Folds one tick ({ price, volume, timestamp }) into a single timeframe's candles
+export const applyTick = (candles, seconds, { price, volume = 0, timestamp }, maxCandles = Infinity, backfill = null) => {
+  const bucket = Math.floor(timestamp / seconds) * seconds
+  const last = candles[candles.length - 1]
+
+  // The common case: a tick for the current candle or the next one
+  let index = !last || bucket > last.timestamp ? candles.length : lowerBound(candles, bucket)
+  let candle = candles[index]
+  if (!candle || candle.timestamp !== bucket) {
+    // A late tick older than everything kept in a full series has nowhere to go
+    if (index === 0 && candles.length >= maxCandles) return null
+    candle = newCandle(bucket, price, volume, timestamp)
+    const fillers = backfill?.enabled && last && index === candles.length && bucket > last.timestamp + seconds
+      ? backfillCandles(last, bucket, seconds, price, backfill)
+      : []
+    candles.splice(index, 0, ...fillers, candle)
+    if (backfill?.enabled && fillers.length === 0 && index < candles.length - 1) {
+      refreshFillers(candles, index, seconds, backfill)
+    }
+    if (candles.length > maxCandles) candles.splice(0, candles.length - maxCandles)
+    return candle
+  }
+
+  // A real tick in a backfilled bar replaces the filler
+  if (candle.filled) {
+    candle = newCandle(bucket, price, volume, timestamp)
+    candles[index] = candle
+    if (backfill?.enabled) refreshFillers(candles, index, seconds, backfill)
+    return candle
+  }
+
+  candle.high = Math.max(candle.high, price)
+  candle.low = Math.min(candle.low, price)
+  candle.volume += volume
+  if (timestamp < (candle.firstTick ?? candle.timestamp)) {
+    candle.open = price
+    candle.firstTick = timestamp
+  }
+  if (timestamp >= (candle.lastTick ?? candle.timestamp)) {
+    candle.close = price
+    candle.lastTick = timestamp
+    // A late close moves the start of any gap that follows
+    if (backfill?.enabled && candles[index + 1]?.filled) refreshFillers(candles, index, seconds, backfill)
+  }
+  return candle
+}
+
+// Folds a tick into every timeframe of a series from createCandleSeries
+export const updateCandleSeries = (series, tick, limits = CANDLE_LIMITS, backfill = candleBackfillSettings) => {
+  Object.entries(CANDLE_TIMEFRAMES).forEach(([tf, seconds]) => {
+    if (!series[tf]) series[tf] = []
+    applyTick(series[tf], seconds, tick, limits[tf] ?? Infinity, backfill)
+  })
+}
+
+// Reference aggregation of time-ordered candles into a coarser timeframe. Used to build
+// higher timeframes from backfilled 1m history and to check the incremental series.
+export const aggregateCandles = (candles, seconds) => {
+  const aggregated = []
+  candles.forEach(candle => {
+    const bucket = Math.floor(candle.timestamp / seconds) * seconds
+    const last = aggregated[aggregated.length - 1]
+    if (last && last.timestamp === bucket) {
+      last.high = Math.max(last.high, candle.high)
+      last.low = Math.min(last.low, candle.low)
+      last.close = candle.close
+      last.volume += candle.volume || 0
+      last.lastTick = candle.lastTick ?? candle.timestamp
+      return
+    }
+    aggregated.push({
+      timestamp: bucket,
+      open: candle.open,
+      high: candle.high,
+      low: candle.low,
+      close: candle.close,
+      volume: candle.volume || 0,
+      firstTick: candle.firstTick ?? candle.timestamp,
+      lastTick: candle.lastTick ?? candle.timestamp
+    })
+  })
+  return aggregated
+}
@@ -0,0 +1,80 @@
+// Daily account statements. When a sim business day closes, every account gets the day's
+// ledger activity summarized: balance carried in and out, realized P&L, swaps, fees and
+// open positions marked at the closing price. Anything else that moved the balance
+// (deposits, withdrawals, resets) is reported as adjustments, so a statement always
+// reconciles: start + realized + swaps - commissions - otherFees + adjustments = end.
+
+import { AccountEventType } from './accountLedger'
+
+// Notice type on the account channel
+export const DAILY_STATEMENT_NOTICE = 'DailyStatement'
+
+export const dailyStatementSettings = {
+  maxStatements: 260 // kept per account, about a year of business days
+}
+
+// events are the ledger entries booked during the day; previous is the account's last
+// statement, if any, whose ending balance is carried in
+export const buildDailyStatement = ({ accountId, currency, simDate, account, positions, events, previous = null }) => {
+  let realizedPnl = 0
+  let swaps = 0
+  let commissions = 0
+  let otherFees = 0
+  let closedPositions = 0
+  let liquidations = 0
+
+  events.forEach(event => {
+    switch (event.type) {
+      case AccountEventType.POSITION_CLOSED:
+        realizedPnl += event.realizedPnl
+        closedPositions++
+        break
+      case AccountEventType.LIQUIDATION:
+        realizedPnl += event.realizedPnl
+        liquidations++
+        break
+      case AccountEventType.SWAP:
+        swaps += event.amount
+        break
+      case AccountEventType.FEE:
+        if (event.reason === 'Commission') commissions += event.amount
+        else otherFees += event.amount
+        break
+    }
+  })
+
+  const startingBalance = previous ? previous.endingBalance : 0
+  const endingBalance = account.balance
+  const openPositions = positions.map(position => ({
+    positionId: position.id,
+    symbol: position.symbol,
+    side: position.side,
+    volume: position.volume,
+    entryPrice: position.entry_price,
+    closePrice: position.current_price,
+    unrealizedPnl: position.unrealized_pnl
+  }))
+  const unrealizedPnl = openPositions.reduce((sum, position) => sum + position.unrealizedPnl, 0)
+
+  return {
+    accountId,
+    currency,
+    simDate,
+    generatedAt: new Date().toISOString(),
+    startingBalance,
+    endingBalance,
+    realizedPnl,
+    swaps,
+    commissions,
+    otherFees,
+    adjustments: endingBalance - startingBalance - realizedPnl - swaps + commissions + otherFees,
+    closedPositions,
+    liquidations,
+    openPositions,
+    unrealizedPnl,
+    endingEquity: endingBalance + unrealizedPnl,
+    marginUsed: account.margin_used,
+    firstSeq: events.length > 0 ? events[0].seq : null,
+    lastSeq: events.length > 0 ? events[events.length - 1].seq : null
+  }
+}
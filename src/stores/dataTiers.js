@@ -0,0 +1,49 @@
+// Market data entitlements of a subscribing client. Below L3 other participants' order
+// events are withheld and trades arrive as tape entries; L1 also cuts book snapshots down
+// to the best level. A client always sees its own activity in full.
+//
+// Event types are the engine's EngineEvent values (marketEngine.js), matched by value so
+// this module doesn't import the engine.
+
+export const MarketDataTier = {
+  L1: 'L1', // top of book, anonymous trades
+  L2: 'L2', // aggregated depth by price level
+  L3: 'L3' // every order and trade with its participants
+}
+
+// Other participants' order-level events, withheld below L3
+const ORDER_EVENTS = ['OrderAccepted', 'OrderCancelled', 'QueuePositionChanged', 'ConditionalTriggered']
+
+export const isMarketDataTier = (tier) => Object.values(MarketDataTier).includes(tier)
+
+// settings is the engine's dataTiers config: { default, clients: { clientId: tier } }
+export const getClientDataTier = (settings, clientId) => settings?.clients?.[clientId] ?? settings?.default ?? MarketDataTier.L3
+
+// The event as a client on `tier` may see it, or null when it is withheld.
+//   own: whether the client is one of the event's participants
+//   tapeConflated: trades reach sub-L3 clients as conflated TapePrint events instead
+//   toTapeEntry(trade): the trade as a tape entry for this client
+export const applyDataTier = (event, tier, { clientId, own, tapeConflated, toTapeEntry }) => {
+  if (tier === MarketDataTier.L3 || own) return event
+  if (ORDER_EVENTS.includes(event.type)) return null
+
+  switch (event.type) {
+    case 'ExecutionReport':
+      return event.participantId === clientId ? event : null
+    case 'TradeExecuted':
+      if (tapeConflated) return null
+      return { ...event, trade: toTapeEntry(event.trade) }
+    case 'SnapshotTaken':
+      if (tier !== MarketDataTier.L1) return event
+      return {
+        ...event,
+        snapshots: event.snapshots.map(snapshot => ({
+          ...snapshot,
+          bids: snapshot.bids.slice(0, 1),
+          asks: snapshot.asks.slice(0, 1)
+        }))
+      }
+    default:
+      return event
+  }
+}
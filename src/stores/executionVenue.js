@@ -0,0 +1,241 @@
+import { ref, computed } from 'vue'
+import { defineStore } from 'pinia'
+import axios from 'axios'
+import { useMarketEngineStore } from './marketEngine'
+
+// Venue Types
+export const VenueType = {
+  INTERNAL: 'Internal',
+  PAPER_TRADING: 'PaperTrading'
+}
+
+// Execution Venue Base Class
+class ExecutionVenue {
+  constructor(data) {
+    this.id = data.id
+    this.name = data.name
+    this.venueType = data.venueType
+  }
+
+  // Returns { orderId, clientOrderId, fillPrice, filledVolume }
+  async submitOrder() {
+    throw new Error(`Venue ${this.name} does not implement submitOrder`)
+  }
+}
+
+// Internal matching engine venue
+class InternalVenue extends ExecutionVenue {
+  constructor(data, marketEngineStore) {
+    super({ ...data, venueType: VenueType.INTERNAL })
+    this.marketEngineStore = marketEngineStore
+  }
+
+  async submitOrder(order) {
+    const orderId = await this.marketEngineStore.placeOrder(
+      order.symbol,
+      order.side,
+      order.amount,
+      order.participantId,
+      order.orderType,
+      order.price,
+      { clientOrderId: order.clientOrderId }
+    )
+
+    // Report the execution, not the request: the volume-weighted price and total volume of
+    // the engine trades for this order, so partial and walked fills show up. fillPrice is
+    // null when nothing filled.
+    const fills = this.marketEngineStore.tradeHistory.filter(trade => trade.buyOrderId === orderId || trade.sellOrderId === orderId)
+    const filledVolume = fills.reduce((sum, trade) => sum + trade.volume, 0)
+    return {
+      orderId,
+      clientOrderId: order.clientOrderId || null,
+      fillPrice: filledVolume > 0 ? fills.reduce((sum, trade) => sum + trade.price * trade.volume, 0) / filledVolume : null,
+      filledVolume
+    }
+  }
+}
+
+// External paper-trading venue (OANDA practice style REST API)
+class PaperTradingVenue extends ExecutionVenue {
+  constructor(data) {
+    super({ ...data, venueType: VenueType.PAPER_TRADING })
+    this.baseUrl = data.baseUrl || 'https://api-fxpractice.oanda.com'
+    this.accountId = data.accountId || ''
+    this.apiToken = data.apiToken || ''
+    this.timeoutMs = data.timeoutMs || 5000
+  }
+
+  toInstrument(symbol) {
+    return `${symbol.slice(0, 3)}_${symbol.slice(3)}`
+  }
+
+  async submitOrder(order) {
+    if (!this.accountId || !this.apiToken) {
+      throw new Error(`Venue ${this.name} is missing account credentials`)
+    }
+
+    const units = Math.round(order.side === 'Buy' ? order.amount : -order.amount)
+    const response = await axios.post(
+      `${this.baseUrl}/v3/accounts/${this.accountId}/orders`,
+      {
+        order: {
+          type: 'MARKET',
+          instrument: this.toInstrument(order.symbol),
+          units: units.toString(),
+          timeInForce: 'FOK',
+          ...(order.clientOrderId ? { clientExtensions: { id: order.clientOrderId } } : {})
+        }
+      },
+      {
+        headers: { Authorization: `Bearer ${this.apiToken}` },
+        timeout: this.timeoutMs
+      }
+    )
+
+    const fill = response.data?.orderFillTransaction
+    if (!fill) {
+      const reason = response.data?.orderCancelTransaction?.reason || 'Order was not filled'
+      throw new Error(reason)
+    }
+
+    return {
+      orderId: fill.orderID || fill.id,
+      clientOrderId: order.clientOrderId || null,
+      fillPrice: parseFloat(fill.price),
+      filledVolume: Math.abs(parseFloat(fill.units))
+    }
+  }
+}
+
+// Execution Venue Store
+export const useExecutionVenueStore = defineStore('executionVenue', () => {
+  const marketEngineStore = useMarketEngineStore()
+
+  // State
+  const venues = ref(new Map())
+  const selectedVenueId = ref('internal')
+  const executionLog = ref([])
+
+  venues.value.set(
+    'internal',
+    new InternalVenue({ id: 'internal', name: 'Internal Matching Engine' }, marketEngineStore)
+  )
+
+  // Computed
+  const selectedVenue = computed(() => {
+    return venues.value.get(selectedVenueId.value) || venues.value.get('internal')
+  })
+
+  const venueList = computed(() => {
+    return Array.from(venues.value.values())
+  })
+
+  // Actions
+  const addPaperTradingVenue = (venueData) => {
+    const id = venueData.id || `paper_${Date.now().toString(36)}`
+    const venue = new PaperTradingVenue({ ...venueData, id, name: venueData.name || 'Paper Trading' })
+    venues.value.set(id, venue)
+    return venue
+  }
+
+  const removeVenue = (venueId) => {
+    if (venueId === 'internal') return false
+    if (selectedVenueId.value === venueId) {
+      selectedVenueId.value = 'internal'
+    }
+    return venues.value.delete(venueId)
+  }
+
+  const selectVenue = (venueId) => {
+    if (venues.value.has(venueId)) {
+      selectedVenueId.value = venueId
+      return true
+    }
+    return false
+  }
+
+  const routeOrder = async (order, venueId = null) => {
+    const venue = venueId ? venues.value.get(venueId) : selectedVenue.value
+    if (!venue) throw new Error(`Venue ${venueId} not found`)
+
+    const startTime = Date.now()
+    const result = await venue.submitOrder(order)
+
+    // Keep a record so sim and external fills can be compared
+    executionLog.value.push({
+      venueId: venue.id,
+      orderId: result.orderId,
+      clientOrderId: order.clientOrderId || null,
+      venueType: venue.venueType,
+      symbol: order.symbol,
+      side: order.side,
+      amount: order.amount,
+      requestedPrice: order.price,
+      fillPrice: result.fillPrice,
+      filledVolume: result.filledVolume,
+      slippage: result.fillPrice === null || !order.price
+        ? null
+        : order.side === 'Buy' ? result.fillPrice - order.price : order.price - result.fillPrice,
+      latencyMs: Date.now() - startTime,
+      timestamp: startTime
+    })
+    if (executionLog.value.length > 1000) {
+      executionLog.value = executionLog.value.slice(-500)
+    }
+
+    return result
+  }
+
+  const getVenueComparison = () => {
+    const stats = {}
+    executionLog.value.forEach(entry => {
+      if (!stats[entry.venueId]) {
+        stats[entry.venueId] = { orders: 0, filled: 0, totalSlippage: 0, requestedVolume: 0, filledVolume: 0, totalLatencyMs: 0 }
+      }
+      const venueStats = stats[entry.venueId]
+      venueStats.orders += 1
+      venueStats.requestedVolume += entry.amount
+      venueStats.filledVolume += entry.filledVolume
+      venueStats.totalLatencyMs += entry.latencyMs
+      // Unfilled orders have no slippage to average
+      if (entry.slippage !== null) {
+        venueStats.filled += 1
+        venueStats.totalSlippage += entry.slippage
+      }
+    })
+
+    return Object.entries(stats).map(([venueId, s]) => ({
+      venueId,
+      orders: s.orders,
+      averageSlippage: s.filled > 0 ? s.totalSlippage / s.filled : 0,
+      fillRatio: s.requestedVolume > 0 ? s.filledVolume / s.requestedVolume : 0,
+      averageLatencyMs: s.totalLatencyMs / s.orders
+    }))
+  }
+
+  return {
+    // State
+    venues,
+    selectedVenueId,
+    executionLog,
+
+    // Computed
+    selectedVenue,
+    venueList,
+
+    // Actions
+    addPaperTradingVenue,
+    removeVenue,
+    selectVenue,
+    routeOrder,
+    getVenueComparison,
+
+    // Classes for external use
+    ExecutionVenue,
+    InternalVenue,
+    PaperTradingVenue,
+
+    // Enums
+    VenueType
+  }
+})
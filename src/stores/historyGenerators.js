@@ -0,0 +1,161 @@
+// Generators for the synthetic price history shown before the simulation has produced its
+// own. A generator is generate(params, { count, rng }) -> array of count + 1 relative
+// price levels (the first is 1); buildHistory turns the path into 1m candles ending at the
+// current market price, so the chart joins the live feed without a jump.
+
+// History Generators
+export const HistoryGenerator = {
+  RANDOM_WALK: 'RandomWalk',
+  TREND_NOISE: 'TrendNoise',
+  SEASONAL: 'Seasonal',
+  FRACTAL: 'Fractal',
+  IMPORTED: 'Imported'
+}
+
+// Candles generated per symbol, the default generator and per-symbol overrides.
+// volatility is the per-minute relative standard deviation.
+export const historyBootstrapSettings = {
+  candles: 1000,
+  default: { generator: HistoryGenerator.RANDOM_WALK, volatility: 0.0003 },
+  symbols: {}
+}
+
+const sampleNormal = (rng) => {
+  const u = 1 - rng()
+  const v = rng()
+  return Math.sqrt(-2 * Math.log(u)) * Math.cos(2 * Math.PI * v)
+}
+
+const walk = (count, step) => {
+  const path = [1]
+  for (let i = 1; i <= count; i++) {
+    path.push(path[i - 1] * Math.exp(step(i)))
+  }
+  return path
+}
+
+const randomWalk = {
+  generate: (params, { count, rng }) => walk(count, () => (params.volatility ?? 0.0003) * sampleNormal(rng))
+}
+
+// driftPerDay is the relative move over 1440 minutes, e.g. 0.005 for half a percent
+const trendNoise = {
+  generate: (params, { count, rng }) => {
+    const drift = (params.driftPerDay ?? 0.003) / 1440
+    return walk(count, () => drift + (params.volatility ?? 0.0003) * sampleNormal(rng))
+  }
+}
+
+// Noise scaled by an hour-of-day volatility curve (24 multipliers, UTC) plus a slow
+// oscillation of `amplitude` over `periodMinutes`, for visible sessions and swings
+const seasonal = {
+  generate: (params, { count, rng, startTime }) => {
+    const curve = params.curve?.length === 24 ? params.curve : null
+    const amplitude = params.amplitude ?? 0.001
+    const period = params.periodMinutes ?? 240
+    let cycle = 0
+    return walk(count, (i) => {
+      const hour = new Date(startTime + i * 60000).getUTCHours()
+      const scale = curve ? curve[hour] : 1
+      const next = amplitude * Math.sin(2 * Math.PI * i / period)
+      const swing = next - cycle
+      cycle = next
+      return swing + (params.volatility ?? 0.0003) * scale * sampleNormal(rng)
+    })
+  }
+}
+
+// Fractional Brownian motion via Hosking's method: hurst > 0.5 trends (persistent moves),
+// < 0.5 mean-reverts, 0.5 is a plain random walk. O(count^2), fine for a few thousand bars.
+const fractal = {
+  generate: (params, { count, rng }) => {
+    const hurst = Math.min(0.95, Math.max(0.05, params.hurst ?? 0.6))
+    const sigma = params.volatility ?? 0.0003
+    const gamma = (k) => 0.5 * (Math.abs(k + 1) ** (2 * hurst) - 2 * Math.abs(k) ** (2 * hurst) + Math.abs(k - 1) ** (2 * hurst))
+
+    const noise = [sampleNormal(rng)]
+    let phi = []
+    let variance = 1
+    for (let n = 1; n < count; n++) {
+      // Durbin-Levinson update of the partial autocorrelations
+      let numerator = gamma(n)
+      for (let j = 0; j < phi.length; j++) numerator -= phi[j] * gamma(n - 1 - j)
+      const reflection = numerator / variance
+      phi = [...phi.map((value, j) => value - reflection * phi[phi.length - 1 - j]), reflection]
+      variance *= 1 - reflection * reflection
+
+      let mean = 0
+      for (let j = 0; j < phi.length; j++) mean += phi[j] * noise[n - 1 - j]
+      noise.push(mean + Math.sqrt(variance) * sampleNormal(rng))
+    }
+    return walk(count, (i) => sigma * noise[i - 1])
+  }
+}
+
+// Recorded closes: `candles` ([{ close }] or [t, close] pairs) or plain `prices`. The most
+// recent count + 1 are used; shorter files produce shorter histories.
+const imported = {
+  generate: (params, { count }) => {
+    const source = params.candles || params.prices || []
+    const closes = source
+      .map(entry => (Array.isArray(entry) ? entry[1] : typeof entry === 'object' ? entry.close ?? entry.price : entry))
+      .filter(price => price > 0)
+      .slice(-(count + 1))
+    return closes.map(price => price / closes[0])
+  }
+}
+
+export const historyGenerators = {
+  [HistoryGenerator.RANDOM_WALK]: randomWalk,
+  [HistoryGenerator.TREND_NOISE]: trendNoise,
+  [HistoryGenerator.SEASONAL]: seasonal,
+  [HistoryGenerator.FRACTAL]: fractal,
+  [HistoryGenerator.IMPORTED]: imported
+}
+
+// Custom generators can be added alongside the built-in ones
+export const registerHistoryGenerator = (name, generator) => {
+  if (typeof generator?.generate !== 'function') {
+    throw new Error(`History generator ${name} needs generate()`)
+  }
+  historyGenerators[name] = generator
+}
+
+export const getHistorySettings = (symbol) => {
+  return { ...historyBootstrapSettings.default, ...historyBootstrapSettings.symbols[symbol] }
+}
+
+// 1m candles (epoch-second timestamps) for the minutes before `endTime`, the last one
+// closing at `endPrice`. Bars get intraminute wicks and volume from the same noise.
+export const buildHistory = (symbol, { endPrice, endTime = Date.now(), count = historyBootstrapSettings.candles, rng = Math.random, settings = getHistorySettings(symbol) } = {}) => {
+  const generator = historyGenerators[settings.generator]
+  if (!generator) throw new Error(`Unknown history generator: ${settings.generator}`)
+
+  const minuteMs = 60 * 1000
+  const lastMinute = Math.floor(endTime / minuteMs) * minuteMs - minuteMs
+  const path = generator.generate(settings, { count, rng, startTime: lastMinute - (count - 1) * minuteMs })
+  if (path.length < 2) return []
+
+  // Anchor the end of the path on the current price
+  const scale = endPrice / path[path.length - 1]
+  const bars = path.length - 1
+  const volatility = settings.volatility ?? 0.0003
+  const baseVolume = settings.volume ?? 500000
+
+  const candles = []
+  for (let i = 0; i < bars; i++) {
+    const open = path[i] * scale
+    const close = path[i + 1] * scale
+    const wick = () => Math.abs(sampleNormal(rng)) * volatility * 0.5 * open
+    const move = Math.abs(Math.log(close / open)) / volatility
+    candles.push({
+      timestamp: (lastMinute - (bars - 1 - i) * minuteMs) / 1000,
+      open,
+      high: Math.max(open, close) + wick(),
+      low: Math.min(open, close) - wick(),
+      close,
+      volume: Math.floor(baseVolume * (0.5 + rng()) * (1 + move * 0.5))
+    })
+  }
+  return candles
+}
@@ -0,0 +1,121 @@
+// Instrument metadata and unit conversions shared by the engine, stores and components,
+// so lot math and quote precision live in one place.
+
+// Volume Units
+export const VolumeUnit = {
+  UNITS: 'units', // base currency units
+  LOTS: 'lots', // standard lots of contractSize units
+  NOTIONAL: 'notional' // value in the account currency
+}
+
+export const STANDARD_LOT = 100000
+
+// Order volume limits in base currency units
+export const VOLUME_LIMITS = { minVolume: 1000, maxVolume: 100000000, volumeStep: 1000 }
+
+// Margin rate by position notional (base currency units); upTo null is the top tier
+export const MARGIN_TIERS = [
+  { upTo: 1000000, marginRate: 0.01 },
+  { upTo: 5000000, marginRate: 0.02 },
+  { upTo: 20000000, marginRate: 0.05 },
+  { upTo: null, marginRate: 0.1 }
+]
+
+// Overnight swap in account currency per standard lot held, by side
+export const SWAP_RATES = {
+  EURUSD: { Buy: -0.5, Sell: -2.1 },
+  GBPUSD: { Buy: 0.8, Sell: -3.2 },
+  USDJPY: { Buy: 2.1, Sell: -5.4 },
+  USDCHF: { Buy: 1.2, Sell: -2.8 },
+  AUDUSD: { Buy: -1.5, Sell: -0.3 },
+  USDCAD: { Buy: 0.5, Sell: -1.8 }
+}
+
+// Spot FX trades around the clock from the Sunday open to the Friday close, in UTC
+export const TRADING_HOURS = {
+  timezone: 'UTC',
+  open: { day: 'Sunday', time: '22:00' },
+  close: { day: 'Friday', time: '22:00' }
+}
+
+export const getInstrument = (symbol) => {
+  const base = symbol.slice(0, 3)
+  const quote = symbol.slice(3, 6)
+  const isJpy = quote === 'JPY'
+  return {
+    symbol,
+    base,
+    quote,
+    pipSize: isJpy ? 0.01 : 0.0001,
+    tickSize: isJpy ? 0.001 : 0.00001,
+    priceDecimals: isJpy ? 3 : 5,
+    contractSize: STANDARD_LOT
+  }
+}
+
+// Everything a client needs to configure itself for a symbol: precision, volume limits,
+// margin tiers, swap rates and trading hours
+export const getInstrumentMetadata = (symbol) => {
+  return {
+    ...getInstrument(symbol),
+    ...VOLUME_LIMITS,
+    marginTiers: MARGIN_TIERS.map(tier => ({ ...tier })),
+    swapRates: SWAP_RATES[symbol] ? { ...SWAP_RATES[symbol] } : null,
+    tradingHours: { ...TRADING_HOURS, open: { ...TRADING_HOURS.open }, close: { ...TRADING_HOURS.close } }
+  }
+}
+
+// Pip size and quote precision (JPY pairs quote two decimals fewer)
+export const getPipSize = (symbol) => getInstrument(symbol).pipSize
+export const getPriceDecimals = (symbol) => getInstrument(symbol).priceDecimals
+
+export const roundPrice = (symbol, price) => {
+  return price === null || price === undefined
+    ? price
+    : Number(price.toFixed(getPriceDecimals(symbol)))
+}
+
+// Rate to convert one unit of `from` into `to`, using direct, inverse or USD-crossed pairs.
+// `prices` maps symbol -> mid price.
+export const getConversionRate = (from, to, prices) => {
+  if (from === to) return 1
+
+  const price = (symbol) => prices instanceof Map ? prices.get(symbol) : prices?.[symbol]
+  const direct = price(`${from}${to}`)
+  if (direct) return direct
+  const inverse = price(`${to}${from}`)
+  if (inverse) return 1 / inverse
+
+  if (from !== 'USD' && to !== 'USD') {
+    const viaUsd = getConversionRate(from, 'USD', prices)
+    const fromUsd = getConversionRate('USD', to, prices)
+    if (viaUsd && fromUsd) return viaUsd * fromUsd
+  }
+  return null
+}
+
+// Converts a volume in base currency units into the requested reporting unit
+export const convertVolume = (symbol, units, unit = VolumeUnit.UNITS, { prices = null, accountCurrency = 'USD' } = {}) => {
+  const instrument = getInstrument(symbol)
+  switch (unit) {
+    case VolumeUnit.LOTS:
+      return units / instrument.contractSize
+    case VolumeUnit.NOTIONAL: {
+      const rate = getConversionRate(instrument.base, accountCurrency, prices)
+      return rate ? units * rate : null
+    }
+    default:
+      return units
+  }
+}
+
+// Applies reporting options to anything carrying symbol/price/volume fields
+export const toReportUnits = (entry, { volumeUnit = VolumeUnit.UNITS, accountCurrency = 'USD', prices = null } = {}) => {
+  const report = { ...entry, price: roundPrice(entry.symbol, entry.price) }
+  if (entry.volume !== undefined) {
+    report.volume = convertVolume(entry.symbol, entry.volume, volumeUnit, { prices, accountCurrency })
+    report.volumeUnit = volumeUnit
+    if (volumeUnit === VolumeUnit.NOTIONAL) report.currency = accountCurrency
+  }
+  return report
+}
@@ -0,0 +1,285 @@
+import { AccountEventType } from './accountLedger'
+import { getPipSize } from './instruments'
+
+// Trade journal analytics folded from the account ledger. Positions, fees and swaps
+// carry the strategy tag of the order that opened them.
+
+export const UNTAGGED = 'untagged'
+
+const createStats = (tag) => ({
+  strategyTag: tag,
+  opened: 0,
+  closed: 0,
+  wins: 0,
+  losses: 0,
+  volume: 0,
+  grossPnl: 0,
+  fees: 0,
+  swaps: 0,
+  netPnl: 0,
+  unrealizedPnl: 0,
+  openPositions: 0,
+  bestTrade: null,
+  worstTrade: null
+})
+
+// Per-tag P&L, costs and win/loss statistics. Open positions add their unrealized P&L.
+export const buildStrategyAttribution = (events, openPositions = []) => {
+  const stats = new Map()
+  const tagOf = (value) => value || UNTAGGED
+  const get = (tag) => {
+    if (!stats.has(tag)) stats.set(tag, createStats(tag))
+    return stats.get(tag)
+  }
+
+  // Closes and fees reference positions by id, so remember each position's tag
+  const positionTags = new Map()
+
+  events.forEach(event => {
+    switch (event.type) {
+      case AccountEventType.POSITION_OPENED: {
+        const tag = tagOf(event.position.strategy_tag)
+        positionTags.set(event.position.id, tag)
+        const entry = get(tag)
+        entry.opened += 1
+        entry.volume += event.position.volume
+        break
+      }
+      case AccountEventType.POSITION_CLOSED:
+      case AccountEventType.LIQUIDATION: {
+        const entry = get(tagOf(event.strategyTag || positionTags.get(event.positionId)))
+        entry.closed += 1
+        entry.grossPnl += event.realizedPnl
+        if (event.realizedPnl > 0) entry.wins += 1
+        else if (event.realizedPnl < 0) entry.losses += 1
+        entry.bestTrade = entry.bestTrade === null ? event.realizedPnl : Math.max(entry.bestTrade, event.realizedPnl)
+        entry.worstTrade = entry.worstTrade === null ? event.realizedPnl : Math.min(entry.worstTrade, event.realizedPnl)
+        break
+      }
+      case AccountEventType.FEE:
+        if (event.positionId || event.strategyTag) {
+          get(tagOf(event.strategyTag || positionTags.get(event.positionId))).fees += event.amount
+        }
+        break
+      case AccountEventType.SWAP:
+        get(tagOf(event.strategyTag || positionTags.get(event.positionId))).swaps += event.amount
+        break
+    }
+  })
+
+  openPositions.forEach(position => {
+    const entry = get(tagOf(position.strategy_tag))
+    entry.openPositions += 1
+    entry.unrealizedPnl += position.unrealized_pnl
+  })
+
+  return Array.from(stats.values()).map(entry => ({
+    ...entry,
+    netPnl: entry.grossPnl - entry.fees + entry.swaps,
+    winRate: entry.closed > 0 ? entry.wins / entry.closed : null,
+    averagePnl: entry.closed > 0 ? entry.grossPnl / entry.closed : null
+  }))
+}
+
+// External trade import. Columns: timestamp, symbol, side, size, price and an optional
+// strategy_tag. Timestamps are ISO strings or epoch seconds/milliseconds; size is in units.
+export const JOURNAL_CSV_COLUMNS = ['timestamp', 'symbol', 'side', 'size', 'price']
+
+const parseTimestamp = (value) => {
+  if (/^\d+(\.\d+)?$/.test(value)) {
+    const n = Number(value)
+    return n < 1e12 ? n * 1000 : n
+  }
+  return Date.parse(value)
+}
+
+const parseSide = (value) => {
+  const side = value.toLowerCase()
+  if (side === 'buy' || side === 'b') return 'Buy'
+  if (side === 'sell' || side === 's') return 'Sell'
+  return null
+}
+
+// Returns the parsed trades sorted by time and one error per rejected line
+export const parseTradeCsv = (text, { symbols = null } = {}) => {
+  const lines = text.split(/\r?\n/).map(line => line.trim()).filter(Boolean)
+  const trades = []
+  const errors = []
+  if (lines.length === 0) return { trades, errors }
+
+  // The header row is optional; without one the columns are in the documented order
+  let columns = [...JOURNAL_CSV_COLUMNS, 'strategy_tag']
+  let start = 0
+  const first = lines[0].split(',').map(cell => cell.trim().toLowerCase())
+  if (first.includes('timestamp')) {
+    const missing = JOURNAL_CSV_COLUMNS.filter(column => !first.includes(column))
+    if (missing.length > 0) {
+      errors.push({ line: 1, error: `Missing columns: ${missing.join(', ')}` })
+      return { trades, errors }
+    }
+    columns = first
+    start = 1
+  }
+
+  for (let i = start; i < lines.length; i++) {
+    const cells = lines[i].split(',').map(cell => cell.trim())
+    const row = Object.fromEntries(columns.map((column, index) => [column, cells[index] ?? '']))
+    const timestamp = parseTimestamp(row.timestamp)
+    const symbol = row.symbol.replace('/', '').toUpperCase()
+    const side = parseSide(row.side)
+    const size = Number(row.size)
+    const price = Number(row.price)
+
+    let error = null
+    if (!Number.isFinite(timestamp)) error = `Invalid timestamp '${row.timestamp}'`
+    else if (symbols && !symbols.includes(symbol)) error = `Unknown symbol '${row.symbol}'`
+    else if (!side) error = `Invalid side '${row.side}'`
+    else if (!(size > 0)) error = `Invalid size '${row.size}'`
+    else if (!(price > 0)) error = `Invalid price '${row.price}'`
+    if (error) {
+      errors.push({ line: i + 1, error })
+      continue
+    }
+
+    trades.push({
+      id: `import_${i + 1}`,
+      timestamp: new Date(timestamp).toISOString(),
+      symbol,
+      side,
+      volume: size,
+      price,
+      strategyTag: row.strategy_tag || null
+    })
+  }
+
+  trades.sort((a, b) => Date.parse(a.timestamp) - Date.parse(b.timestamp))
+  return { trades, errors }
+}
+
+// The 1m candle containing a time. Candle timestamps are epoch seconds.
+export const findCandleAt = (candles, timestamp) => {
+  const seconds = Date.parse(timestamp) / 1000
+  let lo = 0
+  let hi = candles.length - 1
+  let found = null
+  while (lo <= hi) {
+    const mid = (lo + hi) >> 1
+    if (candles[mid].timestamp <= seconds) {
+      found = candles[mid]
+      lo = mid + 1
+    } else {
+      hi = mid - 1
+    }
+  }
+  return found && seconds - found.timestamp < 60 ? found : null
+}
+
+// Transaction cost analysis. Each fill is measured against the arrival mid (the open of
+// its minute) and the minute's typical price. Positive slippage is a cost to the trader.
+export const buildExecutionAnalysis = (fills, getCandles) => {
+  const rows = fills.map(fill => {
+    const candle = findCandleAt(getCandles(fill.symbol), fill.timestamp)
+    if (!candle) return { ...fill, priced: false }
+    const sign = fill.side === 'Buy' ? 1 : -1
+    const arrival = candle.open
+    const typical = (candle.high + candle.low + candle.close) / 3
+    const pipSize = getPipSize(fill.symbol)
+    return {
+      ...fill,
+      priced: true,
+      arrivalPrice: arrival,
+      typicalPrice: typical,
+      slippagePips: sign * (fill.price - arrival) / pipSize,
+      vsTypicalPips: sign * (fill.price - typical) / pipSize,
+      cost: sign * (fill.price - arrival) * fill.volume, // in quote currency
+      outsideRange: fill.price > candle.high || fill.price < candle.low
+    }
+  })
+
+  const summarize = (group) => {
+    const priced = group.filter(row => row.priced)
+    const volume = priced.reduce((sum, row) => sum + row.volume, 0)
+    return {
+      fills: group.length,
+      priced: priced.length,
+      volume,
+      // Volume-weighted so a large fill counts for more than a small one
+      averageSlippagePips: volume > 0 ? priced.reduce((sum, row) => sum + row.slippagePips * row.volume, 0) / volume : null,
+      averageVsTypicalPips: volume > 0 ? priced.reduce((sum, row) => sum + row.vsTypicalPips * row.volume, 0) / volume : null,
+      outsideRange: priced.filter(row => row.outsideRange).length
+    }
+  }
+  const groupBy = (key) => {
+    const groups = new Map()
+    rows.forEach(row => {
+      const value = key(row)
+      if (!groups.has(value)) groups.set(value, [])
+      groups.get(value).push(row)
+    })
+    return Array.from(groups.entries()).map(([value, group]) => ({ key: value, ...summarize(group) }))
+  }
+
+  return {
+    summary: summarize(rows),
+    bySymbol: groupBy(row => row.symbol),
+    byStrategy: groupBy(row => row.strategyTag || UNTAGGED),
+    fills: rows
+  }
+}
+
+// Nets imported fills FIFO per symbol and tag into the ledger events that native trades
+// produce, so buildStrategyAttribution reports both the same way. What is still open at
+// the end is marked with getMark(symbol).
+export const tradesToLedgerEvents = (trades, { calculatePnl, getMark }) => {
+  const events = []
+  const lots = new Map()
+  let seq = 0
+  const push = (type, data, timestamp) => events.push({ seq: ++seq, type, timestamp, ...data })
+
+  trades.forEach(trade => {
+    const key = `${trade.symbol}:${trade.strategyTag || ''}`
+    const queue = lots.get(key) || []
+    lots.set(key, queue)
+
+    let remaining = trade.volume
+    while (remaining > 0 && queue.length > 0 && queue[0].side !== trade.side) {
+      const lot = queue[0]
+      const volume = Math.min(lot.volume, remaining)
+      push(AccountEventType.POSITION_CLOSED, {
+        positionId: lot.id,
+        symbol: trade.symbol,
+        closePrice: trade.price,
+        realizedPnl: calculatePnl({ ...lot, volume }, trade.price),
+        strategyTag: trade.strategyTag
+      }, trade.timestamp)
+      lot.volume -= volume
+      remaining -= volume
+      if (lot.volume === 0) queue.shift()
+    }
+
+    if (remaining > 0) {
+      const lot = {
+        id: `${trade.id}_lot`,
+        symbol: trade.symbol,
+        side: trade.side,
+        volume: remaining,
+        entry_price: trade.price,
+        strategy_tag: trade.strategyTag,
+        timestamp: trade.timestamp
+      }
+      queue.push(lot)
+      push(AccountEventType.POSITION_OPENED, { position: { ...lot } }, trade.timestamp)
+    }
+  })
+
+  const openPositions = Array.from(lots.values()).flat().map(lot => {
+    const mark = getMark(lot.symbol)
+    return {
+      ...lot,
+      current_price: mark,
+      unrealized_pnl: mark ? calculatePnl(lot, mark) : 0
+    }
+  })
+
+  return { events, openPositions }
+}
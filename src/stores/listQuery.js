@@ -0,0 +1,137 @@
+// Shared query conventions for listings (trades, orders, candles, participants, history),
+// so every list filters, sorts and pages the same way:
+//
+//   { filter, sort, limit, cursor, fields }
+//
+// filter maps a field to a value, an array of accepted values, or operators
+// { eq, ne, in, gt, gte, lt, lte }. A date bound (Date or ISO string) against a numeric
+// field compares as epoch milliseconds.
+// sort is a field or a list of fields, '-' prefixed for descending. The id field is always
+// the final tiebreaker so the order is total.
+// limit caps the page (default 100, at most MAX_LIMIT).
+// cursor is the previous page's nextCursor. It holds the sort key of the last item returned,
+// so the next page resumes after that item even if entries were added in between.
+// fields picks the properties returned for each item.
+//
+// Result: { items, total, limit, nextCursor }, total counting every match and nextCursor
+// null on the last page.
+
+export const DEFAULT_LIMIT = 100
+export const MAX_LIMIT = 1000
+
+const OPERATORS = ['eq', 'ne', 'in', 'gt', 'gte', 'lt', 'lte']
+
+const isPlainObject = (value) => value !== null && typeof value === 'object' && !Array.isArray(value) && !(value instanceof Date)
+
+const coerce = (value, bound) => {
+  if (bound instanceof Date) return [typeof value === 'number' ? value : new Date(value).getTime(), bound.getTime()]
+  if (typeof value === 'number' && typeof bound === 'string' && Number.isNaN(Number(bound))) {
+    return [value, new Date(bound).getTime()]
+  }
+  return [value, bound]
+}
+
+const compareValues = (a, b) => {
+  if (a === b) return 0
+  // Missing values sort last
+  if (a === null || a === undefined) return 1
+  if (b === null || b === undefined) return -1
+  return a < b ? -1 : 1
+}
+
+const matchesCondition = (value, condition) => {
+  if (Array.isArray(condition)) return condition.includes(value)
+  if (!isPlainObject(condition)) return value === condition
+
+  return Object.entries(condition).every(([operator, bound]) => {
+    if (operator === 'in') return bound.includes(value)
+    if (value === null || value === undefined) return operator === 'ne' ? bound !== value : false
+    const [left, right] = coerce(value, bound)
+    switch (operator) {
+      case 'eq': return left === right
+      case 'ne': return left !== right
+      case 'gt': return left > right
+      case 'gte': return left >= right
+      case 'lt': return left < right
+      case 'lte': return left <= right
+      default: return false
+    }
+  })
+}
+
+const parseSort = (sort, idField) => {
+  const fields = (Array.isArray(sort) ? sort : sort ? [sort] : []).map(entry => ({
+    field: entry.startsWith('-') ? entry.slice(1) : entry,
+    direction: entry.startsWith('-') ? -1 : 1
+  }))
+  if (!fields.some(entry => entry.field === idField)) fields.push({ field: idField, direction: 1 })
+  return fields
+}
+
+const sortSignature = (sortFields) => sortFields.map(({ field, direction }) => `${direction < 0 ? '-' : ''}${field}`).join(',')
+
+const compareByKey = (a, b, sortFields) => {
+  for (let i = 0; i < sortFields.length; i++) {
+    const order = compareValues(a[i], b[i]) * sortFields[i].direction
+    if (order !== 0) return order
+  }
+  return 0
+}
+
+export const encodeCursor = (signature, key) => btoa(encodeURIComponent(JSON.stringify({ s: signature, k: key })))
+
+export const decodeCursor = (cursor) => {
+  try {
+    return JSON.parse(decodeURIComponent(atob(cursor)))
+  } catch {
+    return null
+  }
+}
+
+// Throws on a malformed query: unknown filter operators, a bad limit, or a cursor that
+// doesn't belong to this sort order
+export const validateListQuery = ({ filter = null, limit = DEFAULT_LIMIT, cursor = null } = {}) => {
+  const errors = []
+  Object.entries(filter || {}).forEach(([field, condition]) => {
+    if (!isPlainObject(condition)) return
+    const unknown = Object.keys(condition).filter(operator => !OPERATORS.includes(operator))
+    if (unknown.length > 0) errors.push(`Unknown filter operator ${unknown.join(', ')} on ${field}`)
+    if (condition.in !== undefined && !Array.isArray(condition.in)) errors.push(`Filter ${field}.in must be an array`)
+  })
+  if (!(Number.isInteger(limit) && limit > 0 && limit <= MAX_LIMIT)) errors.push(`limit must be an integer from 1 to ${MAX_LIMIT}`)
+  if (cursor !== null && !decodeCursor(cursor)?.k) errors.push('Malformed cursor')
+  return errors
+}
+
+export const queryList = (entries, { filter = null, sort = null, limit = DEFAULT_LIMIT, cursor = null, fields = null } = {}, { idField = 'id' } = {}) => {
+  const errors = validateListQuery({ filter, limit, cursor })
+  if (errors.length > 0) throw new Error(errors.join('; '))
+
+  const sortFields = parseSort(sort, idField)
+  const signature = sortSignature(sortFields)
+  const keyOf = (entry) => sortFields.map(({ field }) => entry[field] ?? null)
+
+  const conditions = Object.entries(filter || {})
+  const matching = entries
+    .filter(entry => conditions.every(([field, condition]) => matchesCondition(entry[field], condition)))
+    .map(entry => ({ entry, key: keyOf(entry) }))
+    .sort((a, b) => compareByKey(a.key, b.key, sortFields))
+
+  let start = 0
+  if (cursor !== null) {
+    const decoded = decodeCursor(cursor)
+    if (decoded.s !== signature) throw new Error('Cursor was issued for a different sort order')
+    start = matching.findIndex(({ key }) => compareByKey(key, decoded.k, sortFields) > 0)
+    if (start === -1) start = matching.length
+  }
+
+  const page = matching.slice(start, start + limit)
+  const hasMore = start + limit < matching.length
+  const pick = (entry) => fields ? Object.fromEntries(fields.map(field => [field, entry[field]])) : entry
+  return {
+    items: page.map(({ entry }) => pick(entry)),
+    total: matching.length,
+    limit,
+    nextCursor: hasMore ? encodeCursor(signature, page[page.length - 1].key) : null
+  }
+}
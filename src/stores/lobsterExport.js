@@ -0,0 +1,117 @@
+// LOBSTER-format recording of an order book, for limit-order-book research tooling. A
+// session yields the two LOBSTER files: the message file (one row per book event) and the
+// orderbook file (the top `levels` after each message, row for row).
+//
+//   message:   Time, Type, Order ID, Size, Price, Direction
+//   orderbook: Ask Price 1, Ask Size 1, Bid Price 1, Bid Size 1, Ask Price 2, ...
+//
+// Time is seconds after midnight UTC. Prices are integers: price * priceScale, where
+// priceScale defaults to 10^priceDecimals of the symbol (LOBSTER's equity files use 10000,
+// which would truncate 5-decimal FX quotes). Sizes are units. Order ids are numbered from 1
+// per session. Executions are reported against the resting order, with its direction.
+
+import { getPriceDecimals } from './instruments.js'
+
+// LOBSTER message types
+export const LobsterEventType = {
+  SUBMISSION: 1,
+  PARTIAL_CANCEL: 2,
+  DELETION: 3,
+  EXECUTION: 4,
+  HIDDEN_EXECUTION: 5,
+  HALT: 7
+}
+
+// Placeholders LOBSTER uses for levels that don't exist
+const EMPTY_ASK_PRICE = 9999999999
+const EMPTY_BID_PRICE = -9999999999
+
+const secondsAfterMidnight = (timestamp) => {
+  const date = new Date(timestamp)
+  return (timestamp - Date.UTC(date.getUTCFullYear(), date.getUTCMonth(), date.getUTCDate())) / 1000
+}
+
+// Attaches to `book` (see OrderBook.addListener) until stop() is called
+export const createLobsterRecorder = (book, { levels = 10, priceScale = 10 ** getPriceDecimals(book.symbol), now = Date.now } = {}) => {
+  const messages = []
+  const rows = []
+  const orderIds = new Map() // engine order id -> LOBSTER order id
+  const startedAt = now()
+  let stoppedAt = null
+
+  const lobsterId = (orderId) => {
+    if (!orderIds.has(orderId)) orderIds.set(orderId, orderIds.size + 1)
+    return orderIds.get(orderId)
+  }
+
+  const toPrice = (price) => Math.round(price * priceScale)
+
+  // The book only reports an order's fills once it has been fully processed, so the levels
+  // are replayed here message by message; each row is the book right after its message
+  const snapshot = book.snapshot()
+  const bids = new Map(snapshot.bids.map(([price, volume]) => [price, volume]))
+  const asks = new Map(snapshot.asks.map(([price, volume]) => [price, volume]))
+
+  const applyToLevel = (side, price, change) => {
+    const levelsOfSide = side === 'Buy' ? bids : asks
+    const volume = (levelsOfSide.get(price) || 0) + change
+    if (volume > 1e-9) levelsOfSide.set(price, volume)
+    else levelsOfSide.delete(price)
+  }
+
+  const bookRow = () => {
+    const sortedAsks = Array.from(asks).sort((a, b) => a[0] - b[0])
+    const sortedBids = Array.from(bids).sort((a, b) => b[0] - a[0])
+    const row = []
+    for (let level = 0; level < levels; level++) {
+      const ask = sortedAsks[level]
+      const bid = sortedBids[level]
+      row.push(ask ? toPrice(ask[0]) : EMPTY_ASK_PRICE, ask ? ask[1] : 0)
+      row.push(bid ? toPrice(bid[0]) : EMPTY_BID_PRICE, bid ? bid[1] : 0)
+    }
+    return row
+  }
+
+  const record = (timestamp, type, orderId, size, price, side) => {
+    applyToLevel(side, price, type === LobsterEventType.SUBMISSION ? size : -size)
+    messages.push([secondsAfterMidnight(timestamp), type, lobsterId(orderId), size, toPrice(price), side === 'Buy' ? 1 : -1])
+    rows.push(bookRow())
+  }
+
+  const listener = {
+    onTrade: (trade) => {
+      const restingSide = trade.aggressorSide === 'Buy' ? 'Sell' : 'Buy'
+      const restingId = restingSide === 'Buy' ? trade.buyOrderId : trade.sellOrderId
+      record(trade.timestamp, LobsterEventType.EXECUTION, restingId, trade.volume, trade.price, restingSide)
+    },
+    onOrderRested: (order) => {
+      record(now(), LobsterEventType.SUBMISSION, order.id, order.amount, order.price, order.side)
+    },
+    onOrderCancelled: (order) => {
+      record(now(), LobsterEventType.DELETION, order.id, order.amount, order.price, order.side)
+    }
+  }
+  const remove = book.addListener(listener)
+
+  const stop = () => {
+    if (stoppedAt === null) {
+      stoppedAt = now()
+      remove()
+    }
+  }
+
+  // { messages, orderbook } as { name, content } CSV files named the LOBSTER way:
+  // SYMBOL_YYYY-MM-DD_start_end_message_LEVELS.csv with start/end in seconds after midnight
+  const getFiles = () => {
+    const date = new Date(startedAt).toISOString().slice(0, 10)
+    const start = Math.floor(secondsAfterMidnight(startedAt))
+    const end = Math.ceil(secondsAfterMidnight(stoppedAt ?? now()))
+    const prefix = `${book.symbol}_${date}_${start}_${end}`
+    return {
+      messages: { name: `${prefix}_message_${levels}.csv`, content: messages.map(row => row.join(',')).join('\n') },
+      orderbook: { name: `${prefix}_orderbook_${levels}.csv`, content: rows.map(row => row.join(',')).join('\n') }
+    }
+  }
+
+  return { symbol: book.symbol, levels, messages, rows, stop, getFiles }
+}
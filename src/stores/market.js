@@ -1,17 +1,54 @@
 import { ref, computed, watch } from 'vue'
 import { defineStore } from 'pinia'
-import { useMarketEngineStore } from './marketEngine'
+import { useMarketEngineStore, EngineEvent, ConditionalStatus } from './marketEngine'
 import { useBrokerStore } from './brokerStore'
+import { useExecutionVenueStore, VenueType } from './executionVenue'
+import {
+  AccountEventType,
+  createAccountEvent,
+  applyAccountEvent,
+  replayAccountEvents,
+  eventsSinceReset,
+  getMonthlyTradedNotional,
+  buildPositionHistory,
+  buildFillHistory
+} from './accountLedger'
+import { queryList } from './listQuery'
+import { createStorage } from './storage'
+import { ChallengeStatus, challengePresets, evaluateChallenge } from './propChallenge'
+import { VolumeUnit, convertVolume, getConversionRate, roundPrice, getInstrumentMetadata } from './instruments'
+import {
+  SettlementReason,
+  addBusinessDays,
+  toBusinessDay,
+  getSpotDate,
+  nightsBetween,
+  createCashFlows,
+  settleDueFlows,
+  summarizeUnsettled
+} from './settlement'
+import {
+  buildStrategyAttribution,
+  buildExecutionAnalysis,
+  parseTradeCsv,
+  tradesToLedgerEvents
+} from './journal'
+import { RiskReportType, RISK_REPORT_NOTICE, riskReportSettings, buildRiskReport } from './riskReport'
+import { DAILY_STATEMENT_NOTICE, dailyStatementSettings, buildDailyStatement } from './dailyStatement'
+import { CANDLE_TIMEFRAMES, CANDLE_LIMITS, createCandleSeries, updateCandleSeries, aggregateCandles } from './candles'
+import { historyBootstrapSettings, buildHistory } from './historyGenerators'
 
 export const useMarketStore = defineStore('market', () => {
   // Get access to the market engine and broker stores
   const marketEngineStore = useMarketEngineStore()
   const brokerStore = useBrokerStore()
+  const executionVenueStore = useExecutionVenueStore()
   
   // State
   const selectedSymbol = ref('EURUSD')
   const chartType = ref('line') // 'line' or 'candlestick'
   const timeframe = ref('1m')
+  const selectedPool = ref('consolidated') // 'consolidated' or a liquidity pool id
   
   // Real-time market data derived from market engine
   const marketPrices = ref(new Map())
@@ -29,6 +66,73 @@ export const useMarketStore = defineStore('market', () => {
   
   const positions = ref([])
   const pendingOrders = ref([])
+  const accountEvents = ref([]) // append-only ledger for the active account
+  const settlementLedger = ref([]) // value-dated cash flows for the active account
+  
+  // Sim-time settlement calendar: one business day passes every dayLengthMs
+  const settlementClock = ref({
+    simDate: toBusinessDay(new Date().toISOString().slice(0, 10)),
+    dayLengthMs: 10 * 60000,
+    dayStartedAt: Date.now(),
+    reportedFor: null // sim date the pre-close risk reports went out for
+  })
+  const riskReports = ref(new Map()) // accountId -> latest pre-close risk report
+  const dailyStatements = ref(new Map()) // accountId -> day-close statements, oldest first
+  const statementCursors = new Map() // accountId -> last ledger event covered by a statement
+  const journalImports = ref([]) // external trades imported for offline analysis
+  let storage = null // see getStorage
+  const accountSubscribers = new Map() // handler -> accountId filter (null = all)
+  
+  // What order entry does with an order that would take net exposure past a limit
+  const ExposureLimitAction = {
+    REJECT: 'Reject',
+    REDUCE: 'Reduce' // fill only up to the limit
+  }
+  
+  // Per-account risk limits; null disables a limit. exposureLimits are the account's own
+  // per-symbol caps on absolute net units, adminExposureLimits are imposed on it and can't
+  // be changed through setRiskLimits. The tightest of these and maxPositionSize applies.
+  const createRiskControls = () => ({
+    maxOrdersPerMinute: null,
+    maxPositionSize: null,
+    maxDailyLoss: null,
+    exposureLimits: {},
+    adminExposureLimits: {},
+    exposureLimitAction: ExposureLimitAction.REJECT,
+    killSwitchActive: false,
+    killSwitchReason: null,
+    recentOrderTimes: [],
+    tradingDay: new Date().toISOString().slice(0, 10),
+    dayStartEquity: null
+  })
+  const riskControls = ref(createRiskControls())
+  
+  // Prop-firm challenge state for the active account (null when not in challenge mode)
+  const challenge = ref(null)
+  
+  // 'standard' sums per-position margin, 'portfolio' nets correlated exposure
+  const marginMode = ref('standard')
+  
+  // Trading accounts held by the user; the active one is mirrored in account/positions
+  const accounts = ref(new Map())
+  const activeAccountId = ref('main')
+  
+  // Demo account templates the user can reset to
+  const accountTemplates = {
+    retail: { name: 'Retail $10k (1:30)', balance: 10000, leverage: 30 },
+    standard: { name: 'Standard $10k (1:100)', balance: 10000, leverage: 100 },
+    professional: { name: 'Professional $100k (1:200)', balance: 100000, leverage: 200 },
+    prop: { name: 'Prop $1M (1:100)', balance: 1000000, leverage: 100 }
+  }
+  
+  // What a new account may be opened with; brokerIds null allows any registered broker.
+  // Margin is still worked out at no more than the broker's own maxLeverage.
+  const accountOpeningLimits = ref({
+    balance: { min: 100, max: 10000000 },
+    leverage: { min: 1, max: 500 },
+    currencies: ['USD', 'EUR', 'GBP', 'JPY', 'CHF', 'AUD', 'CAD'],
+    brokerIds: null
+  })
   
   // Real-time update interval
   const updateInterval = ref(null)
@@ -39,9 +143,7 @@ export const useMarketStore = defineStore('market', () => {
     
     symbols.forEach(symbol => {
       if (!priceHistories.value[symbol]) {
-        priceHistories.value[symbol] = {
-          '1m': [], '5m': [], '15m': [], '1h': [], '4h': [], '1d': []
-        }
+        priceHistories.value[symbol] = createCandleSeries()
       }
       if (!lastCandleTimestamps.value[symbol]) {
         lastCandleTimestamps.value[symbol] = {}
@@ -82,42 +184,127 @@ export const useMarketStore = defineStore('market', () => {
     return price.ask - price.bid
   })
   
+  // The selected broker's quote for the selected symbol
+  const brokerQuote = computed(() => {
+    return getQuote(selectedSymbol.value, { brokerId: brokerStore.selectedBroker?.id || null })
+  })
+  
   const orderbook = computed(() => {
-    const orderBook = marketEngineStore.getOrderBook(selectedSymbol.value)
-    if (!orderBook) return { bids: [], asks: [] }
+    const snapshot = marketEngineStore.getMarketDataSnapshot(selectedSymbol.value, 10)
+    if (!snapshot) return { bids: [], asks: [] }
     
-    return {
-      bids: orderBook.getBids(10),
-      asks: orderBook.getAsks(10)
+    if (selectedPool.value === 'consolidated') {
+      return snapshot.depth
     }
+    return snapshot.pools[selectedPool.value] || { bids: [], asks: [] }
   })
   
   const marketStats = computed(() => {
     return marketEngineStore.marketStats
   })
   
+  // Per-currency strength indices, strongest first
+  const currencyStrength = computed(() => {
+    return marketEngineStore.currencyStrength.indices
+  })
+  
+  // Last published retail positioning for the selected symbol
+  const retailSentiment = computed(() => {
+    return marketEngineStore.retailSentiment.symbols.find(entry => entry.symbol === selectedSymbol.value) || null
+  })
+  
   const brokers = computed(() => {
     return brokerStore.brokerList
   })
   
+  const brokerDirectory = computed(() => {
+    return brokerStore.getBrokerDirectory()
+  })
+  
+  // Instrument metadata for every traded symbol, with the brokers offering it, so a
+  // client UI can configure its tickets and ladders without hardcoding instruments
+  const symbolMetadata = computed(() => {
+    return marketEngineStore.config.symbols.map(symbol => ({
+      ...getInstrumentMetadata(symbol),
+      brokers: brokerDirectory.value
+        .filter(broker => broker.availableSymbols.includes(symbol))
+        .map(broker => ({
+          id: broker.id,
+          name: broker.name,
+          brokerType: broker.brokerType,
+          maxLeverage: broker.maxLeverage,
+          minTradeSize: broker.minTradeSize,
+          maxTradeSize: broker.maxTradeSize
+        }))
+    }))
+  })
+  
+  const getSymbolMetadata = (symbol = null) => {
+    if (symbol === null) return symbolMetadata.value
+    return symbolMetadata.value.find(entry => entry.symbol === symbol) || null
+  }
+  
   const selectedBroker = computed(() => {
     return brokerStore.selectedBroker?.id || null
   })
   
+  const accountList = computed(() => {
+    return Array.from(accounts.value.values()).map(acc => {
+      if (acc.id !== activeAccountId.value) return acc
+      return { ...acc, account: account.value, positions: positions.value }
+    })
+  })
+  
+  // Totals across accounts, each converted from its own currency into USD
+  const consolidatedAccount = computed(() => {
+    const prices = marketEngineStore.getMidPrices()
+    const totals = {
+      currency: 'USD',
+      accounts: 0,
+      balance: 0,
+      equity: 0,
+      margin_used: 0,
+      free_margin: 0,
+      open_positions: 0,
+      unrealized_pnl: 0
+    }
+    
+    accountList.value.forEach(acc => {
+      const rate = getConversionRate(acc.currency || 'USD', 'USD', prices) ?? 1
+      totals.accounts += 1
+      totals.balance += acc.account.balance * rate
+      totals.equity += acc.account.equity * rate
+      totals.margin_used += acc.account.margin_used * rate
+      totals.free_margin += acc.account.free_margin * rate
+      totals.open_positions += acc.positions.length
+      totals.unrealized_pnl += acc.positions.reduce((sum, pos) => sum + pos.unrealized_pnl, 0) * rate
+    })
+    
+    return totals
+  })
+  
   const accountMarginLevel = computed(() => {
     if (account.value.margin_used === 0) return Infinity
     return (account.value.equity / account.value.margin_used) * 100
   })
   
   // Actions
+  // Top-of-book ladder with running totals for the order book panel
+  const orderbookLadder = computed(() => {
+    return marketEngineStore.getDepthLadder(selectedSymbol.value, { levels: 10, poolId: selectedPool.value }) ||
+      { bids: [], asks: [] }
+  })
+  
   const updateMarketData = () => {
     // Get current prices from market engine orderbooks
     marketEngineStore.config.symbols.forEach(symbol => {
       const orderBook = marketEngineStore.getOrderBook(symbol)
       if (orderBook) {
-        const bid = orderBook.getBestBid() || marketEngineStore.config.basePrices[symbol] || 1.0
-        const ask = orderBook.getBestAsk() || (bid + 0.0002)
-        const volume = orderBook.getTotalVolume()
+        // Brokers aggregate across liquidity pools, so quote the consolidated touch
+        const summary = marketEngineStore.getMarketDataSnapshot(symbol).summary
+        const bid = summary.bestBid || marketEngineStore.config.basePrices[symbol] || 1.0
+        const ask = summary.bestAsk || (bid + 0.0002)
+        const volume = summary.pools.reduce((sum, pool) => sum + pool.volume, 0)
         
         marketPrices.value.set(symbol, {
           bid: bid,
@@ -141,6 +328,7 @@ export const useMarketStore = defineStore('market', () => {
     
     updateInterval.value = setInterval(() => {
       updateMarketData()
+      advanceSettlementClock()
     }, 1000) // Update every second
   }
   
@@ -151,55 +339,112 @@ export const useMarketStore = defineStore('market', () => {
     }
   }
   
-  const placeTrade = async (tradeData) => {
+  // accountId:clientOrderId -> original placeTrade result (or in-flight promise)
+  const submittedOrders = new Map()
+  
+  // Resubmitting with the same clientOrderId returns the original result instead of trading again
+  const placeTrade = (tradeData) => {
+    if (!tradeData.clientOrderId) return submitTrade(tradeData)
+    
+    const key = `${tradeData.accountId || activeAccountId.value}:${tradeData.clientOrderId}`
+    if (submittedOrders.has(key)) {
+      return submittedOrders.get(key)
+    }
+    
+    const result = submitTrade(tradeData)
+    submittedOrders.set(key, result)
+    if (submittedOrders.size > 1000) {
+      submittedOrders.delete(submittedOrders.keys().next().value)
+    }
+    return result
+  }
+  
+  const submitTrade = async (tradeData) => {
     try {
+      if (tradeData.accountId && tradeData.accountId !== activeAccountId.value) {
+        if (!switchAccount(tradeData.accountId)) {
+          return { success: false, error: `Account ${tradeData.accountId} not found` }
+        }
+      }
+      // Fees are charged to this account even if another becomes active while the order is out
+      tradeData = { ...tradeData, accountId: activeAccountId.value }
+      
       // Get selected broker
       const broker = brokerStore.selectedBroker
       if (!broker) {
         return { success: false, error: 'No broker selected' }
       }
       
+      const unavailable = brokerStore.checkSymbolAvailability(broker.id, tradeData.symbol)
+      if (unavailable) {
+        return unavailable
+      }
+      
+      const outage = brokerStore.checkBrokerHealth(broker.id)
+      if (outage) {
+        return outage
+      }
+      
+      const riskCheck = checkRiskControls(tradeData)
+      if (!riskCheck.allowed) {
+        return { success: false, error: riskCheck.reason }
+      }
+      if (riskCheck.amount) {
+        tradeData = { ...tradeData, amount: riskCheck.amount, reducedFrom: riskCheck.reducedFrom }
+      }
+      
       // Calculate required margin
       const price = currentPrice.value
       const entryPrice = tradeData.side === 'Buy' ? price.ask : price.bid
       const marginRequired = broker.getMarginRequirement(tradeData.symbol, tradeData.amount, account.value.leverage)
       
+      // Under portfolio margin only the increase in netted margin has to be covered
+      const additionalMargin = marginMode.value === 'portfolio'
+        ? calculatePortfolioMargin([
+            ...positions.value,
+            { symbol: tradeData.symbol, side: tradeData.side, margin_required: marginRequired }
+          ]) - account.value.margin_used
+        : marginRequired
+      
       // Check if enough margin available
-      if (marginRequired > account.value.free_margin) {
+      if (additionalMargin > account.value.free_margin) {
         return { success: false, error: 'Insufficient margin' }
       }
       
-      // Place order in market engine
+      // A stressed market slows the broker down and makes it requote more
+      const stress = marketEngineStore.getMarketStress(tradeData.symbol).level
+      if (stress > 0) {
+        if (Math.random() < broker.getStressRequoteProbability(stress)) {
+          return { success: false, error: 'Requote: price moved during fast market', requote: true }
+        }
+        await new Promise(resolve => setTimeout(resolve, broker.getExecutionSpeedMs(stress)))
+      }
+      
+      const incidentLatency = brokerStore.getIncidentLatencyMs(broker.id)
+      if (incidentLatency > 0) {
+        await new Promise(resolve => setTimeout(resolve, incidentLatency))
+      }
+      
+      // Route order to the selected execution venue
       try {
-        const orderId = await marketEngineStore.placeOrder(
-          tradeData.symbol,
-          tradeData.side,
-          tradeData.amount,
-          'user_trader', // User participant ID
-          'Market',
-          entryPrice
-        )
-        
-        // Create position
-        const position = {
-          id: orderId,
+        const execution = await executionVenueStore.routeOrder({
           symbol: tradeData.symbol,
           side: tradeData.side,
-          volume: tradeData.amount,
-          entry_price: entryPrice,
-          current_price: entryPrice,
-          unrealized_pnl: 0,
-          margin_required: marginRequired,
-          timestamp: new Date().toISOString()
+          amount: tradeData.amount,
+          participantId: 'user_trader', // User participant ID
+          orderType: 'Market',
+          price: entryPrice,
+          clientOrderId: tradeData.clientOrderId
+        }, tradeData.venueId)
+        if (!(execution.filledVolume > 0)) {
+          return { success: false, error: 'Order was not filled' }
         }
         
-        // Add position and update account
-        positions.value.push(position)
-        account.value.margin_used += marginRequired
-        account.value.free_margin -= marginRequired
-        
+        const position = openPosition(tradeData, execution, marginRequired)
         updateAccountInfo()
-        return { success: true, data: position }
+        return tradeData.reducedFrom
+          ? { success: true, data: position, reducedFrom: tradeData.reducedFrom }
+          : { success: true, data: position }
       } catch (engineError) {
         console.error('Market engine order failed:', engineError)
         return { success: false, error: 'Order execution failed' }
@@ -210,14 +455,508 @@ export const useMarketStore = defineStore('market', () => {
     }
   }
   
+  // Books a filled execution as a position and records its ledger, settlement and fees
+  const openPosition = (tradeData, execution, marginRequired, extra = {}) => {
+    const position = {
+      id: execution.orderId,
+      client_order_id: execution.clientOrderId || null,
+      symbol: tradeData.symbol,
+      side: tradeData.side,
+      volume: execution.filledVolume,
+      entry_price: execution.fillPrice,
+      current_price: execution.fillPrice,
+      unrealized_pnl: 0,
+      margin_required: marginRequired,
+      venue_id: tradeData.venueId || executionVenueStore.selectedVenue.id,
+      stop_loss: tradeData.stopLoss || null,
+      strategy_tag: tradeData.strategyTag || null,
+      value_date: getSpotDate(tradeData.symbol, settlementClock.value.simDate),
+      timestamp: new Date().toISOString(),
+      ...extra
+    }
+    
+    // Orders through the engine were already counted by its pre-trade check
+    if (executionVenueStore.venues.get(position.venue_id)?.venueType !== VenueType.INTERNAL) {
+      riskControls.value.recentOrderTimes.push(Date.now())
+    }
+    recordAccountEvent(AccountEventType.POSITION_OPENED, { position: { ...position } })
+    recordCashFlows(position, position.side, position.entry_price, SettlementReason.TRADE)
+    chargeCommission(position, tradeData.accountId)
+    positions.value.push(position)
+    account.value.margin_used += marginRequired
+    account.value.free_margin -= marginRequired
+    return position
+  }
+  
+  // Multi-leg package: every leg executes or none is kept. Margin is taken on the package
+  // as a whole, netted through the correlation matrix, and split across the legs.
+  const placePackage = async ({ legs, accountId = null, venueId = null, strategyTag = null } = {}) => {
+    if (!Array.isArray(legs) || legs.length < 2) {
+      return { success: false, error: 'A package needs at least two legs' }
+    }
+    if (accountId && accountId !== activeAccountId.value && !switchAccount(accountId)) {
+      return { success: false, error: `Account ${accountId} not found` }
+    }
+    accountId = activeAccountId.value
+    
+    const broker = brokerStore.selectedBroker
+    if (!broker) {
+      return { success: false, error: 'No broker selected' }
+    }
+    
+    for (const [i, leg] of legs.entries()) {
+      if (!(leg.amount > 0) || !['Buy', 'Sell'].includes(leg.side)) {
+        return { success: false, error: `Leg ${i + 1} is invalid` }
+      }
+      const unavailable = brokerStore.checkSymbolAvailability(broker.id, leg.symbol)
+      if (unavailable) {
+        return { ...unavailable, error: `Leg ${i + 1}: ${unavailable.error}`, leg: i + 1 }
+      }
+      const riskCheck = checkRiskControls(leg, { allowReduce: false })
+      if (!riskCheck.allowed) {
+        return { success: false, error: `Leg ${i + 1}: ${riskCheck.reason}` }
+      }
+      
+      // Check the whole package can fill before sending anything
+      const depth = marketEngineStore.getConsolidatedDepth(leg.symbol, 50)
+      const available = (leg.side === 'Buy' ? depth.asks : depth.bids).reduce((sum, [, volume]) => sum + volume, 0)
+      if (available < leg.amount) {
+        return { success: false, error: `Leg ${i + 1}: not enough liquidity in ${leg.symbol}, package cancelled` }
+      }
+    }
+    
+    const legMargins = legs.map(leg => broker.getMarginRequirement(leg.symbol, leg.amount, account.value.leverage))
+    const legRisk = legs.map((leg, i) => ({ symbol: leg.symbol, side: leg.side, margin_required: legMargins[i] }))
+    const packageMargin = calculatePortfolioMargin(legRisk)
+    const additionalMargin = marginMode.value === 'portfolio'
+      ? calculatePortfolioMargin([...positions.value, ...legRisk]) - account.value.margin_used
+      : packageMargin
+    if (additionalMargin > account.value.free_margin) {
+      return { success: false, error: 'Insufficient margin for package' }
+    }
+    
+    const packageId = `pkg_${Date.now().toString(36)}${Math.random().toString(36).substr(2, 4)}`
+    const executions = []
+    for (const [i, leg] of legs.entries()) {
+      const quote = marketPrices.value.get(leg.symbol)
+      try {
+        const execution = await executionVenueStore.routeOrder({
+          symbol: leg.symbol,
+          side: leg.side,
+          amount: leg.amount,
+          participantId: 'user_trader',
+          orderType: 'Market',
+          price: quote ? (leg.side === 'Buy' ? quote.ask : quote.bid) : null
+        }, venueId)
+        if (!(execution.filledVolume > 0)) throw new Error('not filled')
+        executions.push(execution)
+      } catch (error) {
+        // Take the filled legs back out so the package leaves nothing behind
+        await unwindLegs(legs, executions, venueId)
+        return { success: false, error: `Leg ${i + 1} failed (${error.message}), package cancelled` }
+      }
+    }
+    
+    const totalLegMargin = legMargins.reduce((sum, margin) => sum + margin, 0)
+    const legPositions = legs.map((leg, i) => openPosition(
+      { ...leg, accountId, venueId, strategyTag: leg.strategyTag || strategyTag },
+      executions[i],
+      totalLegMargin > 0 ? packageMargin * legMargins[i] / totalLegMargin : 0,
+      { package_id: packageId }
+    ))
+    updateAccountInfo()
+    
+    return { success: true, data: { packageId, margin: packageMargin, positions: legPositions } }
+  }
+  
+  const unwindLegs = async (legs, executions, venueId) => {
+    for (const [i, execution] of executions.entries()) {
+      try {
+        await executionVenueStore.routeOrder({
+          symbol: legs[i].symbol,
+          side: legs[i].side === 'Buy' ? 'Sell' : 'Buy',
+          amount: execution.filledVolume,
+          participantId: 'user_trader',
+          orderType: 'Market',
+          price: execution.fillPrice
+        }, venueId)
+      } catch (error) {
+        console.error(`Failed to unwind package leg ${legs[i].symbol}:`, error)
+      }
+    }
+  }
+  
+  // Open packages with their legs and combined margin and P&L
+  const getPackages = () => {
+    const packages = new Map()
+    positions.value
+      .filter(position => position.package_id)
+      .forEach(position => {
+        const entry = packages.get(position.package_id) || {
+          packageId: position.package_id,
+          legs: [],
+          margin: 0,
+          unrealizedPnl: 0
+        }
+        entry.legs.push(position)
+        entry.margin += position.margin_required
+        entry.unrealizedPnl += position.unrealized_pnl
+        packages.set(position.package_id, entry)
+      })
+    return Array.from(packages.values())
+  }
+  
+  const closePackage = (packageId) => {
+    const legs = positions.value.filter(position => position.package_id === packageId)
+    legs.forEach(position => closePosition(position.id))
+    return legs.length > 0
+  }
+  
+  // Packages pass allowReduce false: shrinking one leg would unbalance the package
+  // Kill switch and order rate; the reason no order may be sent right now, or null
+  const checkTradingAllowed = () => {
+    const controls = riskControls.value
+    if (controls.killSwitchActive) {
+      return `Kill switch active: ${controls.killSwitchReason}`
+    }
+    
+    if (controls.maxOrdersPerMinute) {
+      const cutoff = Date.now() - 60000
+      controls.recentOrderTimes = controls.recentOrderTimes.filter(t => t > cutoff)
+      if (controls.recentOrderTimes.length >= controls.maxOrdersPerMinute) {
+        return `Order rate limit of ${controls.maxOrdersPerMinute}/min reached`
+      }
+    }
+    return null
+  }
+  
+  const checkRiskControls = (tradeData, { allowReduce = true } = {}) => {
+    const controls = riskControls.value
+    const blocked = checkTradingAllowed()
+    if (blocked) {
+      return { allowed: false, reason: blocked }
+    }
+    
+    const limit = getExposureLimit(tradeData.symbol, controls)
+    if (limit) {
+      // Net exposure in the symbol after this order, in units. Orders that bring an
+      // over-limit position back towards flat are always let through.
+      const net = getNetExposure(tradeData.symbol)
+      const direction = tradeData.side === 'Buy' ? 1 : -1
+      const after = net + direction * tradeData.amount
+      if (Math.abs(after) > limit.units && Math.abs(after) > Math.abs(net)) {
+        const room = limit.units - direction * net
+        if (allowReduce && controls.exposureLimitAction === ExposureLimitAction.REDUCE && room > 0) {
+          return { allowed: true, amount: room, reducedFrom: tradeData.amount }
+        }
+        return { allowed: false, reason: `${limit.source} exposure limit of ${limit.units} in ${tradeData.symbol} exceeded` }
+      }
+    }
+    
+    return { allowed: true }
+  }
+  
+  // The engine runs this on every user_trader order, so algo children, batches, mass quotes
+  // and triggered conditionals are held to the broker's symbol list and the same controls as
+  // placeTrade. Exposure limits refuse rather than reduce here; placeTrade has already cut its
+  // own orders down to size.
+  const checkPreTrade = (order) => {
+    const broker = brokerStore.selectedBroker
+    const unavailable = broker && brokerStore.checkSymbolAvailability(broker.id, order.symbol)
+    if (unavailable) return unavailable.error
+    const riskCheck = checkRiskControls(order, { allowReduce: false })
+    if (!riskCheck.allowed) return riskCheck.reason
+    riskControls.value.recentOrderTimes.push(Date.now())
+    return null
+  }
+  marketEngineStore.setPreTradeCheck('user_trader', checkPreTrade)
+  
+  const getNetExposure = (symbol, positionList = positions.value) => {
+    return positionList
+      .filter(p => p.symbol === symbol)
+      .reduce((sum, p) => sum + (p.side === 'Buy' ? p.volume : -p.volume), 0)
+  }
+  
+  // Tightest limit on the symbol's absolute net units and who set it, null when unlimited
+  const getExposureLimit = (symbol, controls = riskControls.value) => {
+    const candidates = [
+      { units: controls.maxPositionSize, source: 'Position size' },
+      { units: controls.exposureLimits?.[symbol], source: 'Account' },
+      { units: controls.adminExposureLimits?.[symbol], source: 'Admin' }
+    ].filter(limit => limit.units > 0)
+    if (candidates.length === 0) return null
+    return candidates.reduce((tightest, limit) => (limit.units < tightest.units ? limit : tightest))
+  }
+  
+  // Net exposure against the applicable limit for every symbol held or limited
+  const getExposureUtilization = (accountId = activeAccountId.value) => {
+    const isActive = accountId === activeAccountId.value
+    const acc = accounts.value.get(accountId)
+    if (!isActive && !acc) return null
+    
+    const controls = isActive ? riskControls.value : acc.riskControls
+    const positionList = isActive ? positions.value : acc.positions
+    const symbols = new Set([
+      ...positionList.map(p => p.symbol),
+      ...Object.keys(controls.exposureLimits || {}),
+      ...Object.keys(controls.adminExposureLimits || {})
+    ])
+    return Array.from(symbols).map(symbol => {
+      const netUnits = getNetExposure(symbol, positionList)
+      const limit = getExposureLimit(symbol, controls)
+      return {
+        symbol,
+        netUnits,
+        limit: limit?.units ?? null,
+        limitSource: limit?.source ?? null,
+        utilization: limit ? Math.abs(netUnits) / limit.units : null
+      }
+    })
+  }
+  
+  // limits: { [symbol]: units }; null or 0 removes a symbol's limit
+  const applyExposureLimits = (current, limits) => {
+    const next = { ...current }
+    for (const [symbol, value] of Object.entries(limits)) {
+      const units = value === null || value === '' ? 0 : Math.max(0, Number(value)) || 0
+      if (units > 0) next[symbol] = units
+      else delete next[symbol]
+    }
+    return next
+  }
+  
+  const setExposureLimits = (limits, { action = null } = {}) => {
+    const controls = riskControls.value
+    controls.exposureLimits = applyExposureLimits(controls.exposureLimits, limits)
+    if (action) {
+      if (!Object.values(ExposureLimitAction).includes(action)) {
+        return { success: false, error: `Unknown exposure limit action ${action}` }
+      }
+      controls.exposureLimitAction = action
+    }
+    return { success: true, data: { exposureLimits: { ...controls.exposureLimits }, action: controls.exposureLimitAction } }
+  }
+  
+  // Admin-imposed limits, for any account
+  const setAdminExposureLimits = (accountId, limits) => {
+    const isActive = accountId === activeAccountId.value
+    const acc = accounts.value.get(accountId)
+    if (!isActive && !acc) return { success: false, error: `Account ${accountId} not found` }
+    
+    const controls = isActive ? riskControls.value : acc.riskControls
+    controls.adminExposureLimits = applyExposureLimits(controls.adminExposureLimits, limits)
+    return { success: true, data: { ...controls.adminExposureLimits } }
+  }
+  
+  const evaluateDailyLoss = () => {
+    const controls = riskControls.value
+    const today = new Date().toISOString().slice(0, 10)
+    if (controls.tradingDay !== today || controls.dayStartEquity === null) {
+      controls.tradingDay = today
+      controls.dayStartEquity = account.value.equity
+    }
+    
+    if (!controls.maxDailyLoss || controls.killSwitchActive) return
+    
+    const dailyLoss = controls.dayStartEquity - account.value.equity
+    if (dailyLoss >= controls.maxDailyLoss) {
+      triggerKillSwitch(`Daily loss ${dailyLoss.toFixed(2)} reached limit ${controls.maxDailyLoss}`)
+    }
+  }
+  
+  const setRiskLimits = (limits) => {
+    const controls = riskControls.value
+    for (const key of ['maxOrdersPerMinute', 'maxPositionSize', 'maxDailyLoss']) {
+      if (key in limits) {
+        const value = limits[key]
+        controls[key] = value === null || value === '' ? null : Math.max(0, Number(value)) || null
+      }
+    }
+    return { ...controls }
+  }
+  
+  const triggerKillSwitch = (reason = 'Manually activated') => {
+    riskControls.value.killSwitchActive = true
+    riskControls.value.killSwitchReason = reason
+    console.warn(`Kill switch activated for account ${activeAccountId.value}: ${reason}`)
+  }
+  
+  // Risk-off: pulls the user's resting orders, optionally for one symbol and/or side
+  const cancelAllOrders = async ({ symbol = null, side = null } = {}) => {
+    const result = await marketEngineStore.cancelAllOrders('user_trader', { symbol, side })
+    const keep = (order) => (symbol && order.symbol !== symbol) || (side && order.side !== side)
+    for (const order of pendingOrders.value.filter(order => !keep(order) && order.conditionalId)) {
+      await marketEngineStore.cancelConditionalOrder(order.conditionalId)
+    }
+    pendingOrders.value = pendingOrders.value.filter(keep)
+    return { success: true, data: result }
+  }
+  
+  // Panic button: pulls every working order and closes every position at the touch in one
+  // synchronous pass, so no tick lands between the cancels and the closes
+  const flattenAccount = async (reason = 'Flatten') => {
+    const cancelResult = marketEngineStore.cancelAllOrders('user_trader')
+    const conditionals = pendingOrders.value.filter(order => order.conditionalId)
+    const conditionalResults = conditionals.map(order => marketEngineStore.cancelConditionalOrder(order.conditionalId))
+    const cancelledPending = pendingOrders.value.length
+    pendingOrders.value = []
+    
+    const closed = positions.value.slice().map(position => {
+      const price = marketPrices.value.get(position.symbol)
+      const closePrice = price ? (position.side === 'Buy' ? price.bid : price.ask) : position.current_price
+      const realizedPnl = calculatePnl(position, closePrice)
+      closePosition(position.id, { closePrice, reason })
+      return { positionId: position.id, symbol: position.symbol, side: position.side, volume: position.volume, closePrice, realizedPnl }
+    })
+    
+    const cancelled = await cancelResult
+    await Promise.all(conditionalResults)
+    return {
+      success: true,
+      data: {
+        cancelledOrders: cancelled.count,
+        cancelledPending,
+        closedPositions: closed,
+        realizedPnl: closed.reduce((sum, entry) => sum + entry.realizedPnl, 0),
+        balance: account.value.balance
+      }
+    }
+  }
+  
+  // "Buy EURUSD if USDJPY trades above 152.00": the engine's trigger engine watches the
+  // condition and the order goes through placeTrade when it fires
+  let unsubscribeConditionals = null
+  
+  const placeConditionalOrder = async ({ symbol, side, amount, condition, expiresAt = null, strategyTag = null }) => {
+    // Refuse up front rather than when the condition fires
+    const unavailable = brokerStore.selectedBroker && brokerStore.checkSymbolAvailability(brokerStore.selectedBroker.id, symbol)
+    if (unavailable) {
+      return unavailable
+    }
+    
+    if (!unsubscribeConditionals) {
+      unsubscribeConditionals = marketEngineStore.subscribe(handleConditionalTriggered)
+    }
+    
+    try {
+      const conditionalId = await marketEngineStore.placeConditionalOrder(
+        'user_trader',
+        { symbol, side, amount, orderType: 'Market' },
+        condition,
+        { expiresAt, notifyOnly: true }
+      )
+      
+      // A condition that already holds fires during placement, before we track it
+      const placed = marketEngineStore.getConditionalOrders('user_trader').find(c => c.id === conditionalId)
+      if (placed?.status === ConditionalStatus.TRIGGERED) {
+        const result = await placeTrade({ symbol, side, amount, strategyTag })
+        return { ...result, triggered: true }
+      }
+      
+      const order = {
+        id: conditionalId,
+        conditionalId,
+        accountId: activeAccountId.value,
+        type: 'Conditional',
+        symbol,
+        side,
+        amount,
+        condition: { ...condition },
+        strategyTag,
+        timestamp: new Date().toISOString()
+      }
+      pendingOrders.value.push(order)
+      return { success: true, data: order }
+    } catch (error) {
+      return { success: false, error: error.message }
+    }
+  }
+  
+  const cancelConditionalOrder = async (conditionalId) => {
+    const cancelled = await marketEngineStore.cancelConditionalOrder(conditionalId)
+    pendingOrders.value = pendingOrders.value.filter(order => order.conditionalId !== conditionalId)
+    return cancelled
+  }
+  
+  const findConditionalOwner = (conditionalId) => {
+    if (pendingOrders.value.some(order => order.conditionalId === conditionalId)) {
+      return activeAccountId.value
+    }
+    for (const acc of accounts.value.values()) {
+      if (acc.pendingOrders.some(order => order.conditionalId === conditionalId)) return acc.id
+    }
+    return null
+  }
+  
+  const handleConditionalTriggered = (event) => {
+    if (event.type !== EngineEvent.CONDITIONAL_TRIGGERED) return
+    const accountId = findConditionalOwner(event.conditional.id)
+    if (!accountId) return
+    
+    const owned = accountId === activeAccountId.value ? pendingOrders.value : accounts.value.get(accountId).pendingOrders
+    const strategyTag = owned.find(order => order.conditionalId === event.conditional.id)?.strategyTag || null
+    const removeFrom = (list) => list.filter(order => order.conditionalId !== event.conditional.id)
+    if (accountId === activeAccountId.value) {
+      pendingOrders.value = removeFrom(pendingOrders.value)
+    } else {
+      const acc = accounts.value.get(accountId)
+      acc.pendingOrders = removeFrom(acc.pendingOrders)
+    }
+    
+    const { symbol, side, amount } = event.conditional.order
+    placeTrade({ symbol, side, amount, accountId, strategyTag }).then(result => {
+      if (!result.success) {
+        console.warn(`Conditional order ${event.conditional.id} could not be filled: ${result.error}`)
+      }
+    })
+  }
+  
+  const resetKillSwitch = () => {
+    riskControls.value.killSwitchActive = false
+    riskControls.value.killSwitchReason = null
+    riskControls.value.dayStartEquity = account.value.equity
+  }
+  
+  const calculatePortfolioMargin = (positionList) => {
+    // Treat each position's margin as a signed risk unit and combine them
+    // through the correlation matrix, like a portfolio variance
+    let variance = 0
+    positionList.forEach(a => {
+      const signA = a.side === 'Buy' ? 1 : -1
+      positionList.forEach(b => {
+        const signB = b.side === 'Buy' ? 1 : -1
+        const correlation = marketEngineStore.getCorrelation(a.symbol, b.symbol)
+        variance += signA * signB * a.margin_required * b.margin_required * correlation
+      })
+    })
+    return Math.sqrt(Math.max(variance, 0))
+  }
+  
+  const calculateMarginUsed = (positionList) => {
+    if (marginMode.value === 'portfolio') {
+      return calculatePortfolioMargin(positionList)
+    }
+    return positionList.reduce((sum, pos) => sum + pos.margin_required, 0)
+  }
+  
+  const setMarginMode = (mode) => {
+    if (mode !== 'standard' && mode !== 'portfolio') return false
+    marginMode.value = mode
+    updateAccountInfo()
+    return true
+  }
+  
   const updateAccountInfo = () => {
     const totalPnL = positions.value.reduce((sum, pos) => sum + pos.unrealized_pnl, 0)
-    const totalMargin = positions.value.reduce((sum, pos) => sum + pos.margin_required, 0)
+    const totalMargin = calculateMarginUsed(positions.value)
     
     account.value.equity = account.value.balance + totalPnL
     account.value.margin_used = totalMargin
     account.value.free_margin = account.value.equity - account.value.margin_used
     
+    evaluateDailyLoss()
+    updateChallengeStatus()
+    
     // Keep user participant in sync
     const userParticipant = marketEngineStore.getUserParticipant()
     if (userParticipant) {
@@ -246,8 +985,457 @@ export const useMarketStore = defineStore('market', () => {
     chartType.value = type
   }
   
+  // Appends to the active account's ledger, or to a stored account's when accountId is another
+  const recordAccountEvent = (type, data = {}, accountId = activeAccountId.value) => {
+    const target = accountId === activeAccountId.value
+      ? { events: accountEvents.value, account: account.value }
+      : accounts.value.get(accountId)
+    const event = createAccountEvent(target.events, type, data)
+    target.events.push(event)
+    storeLedgerEvent(accountId, event)
+    
+    // Balance and leverage only ever change by applying ledger events
+    const state = applyAccountEvent({
+      balance: target.account.balance,
+      leverage: target.account.leverage,
+      positions: []
+    }, event)
+    target.account.balance = state.balance
+    target.account.leverage = state.leverage
+    
+    return event
+  }
+  
+  const getTradeNotional = (symbol, volume, currency = null) => {
+    currency = currency || accounts.value.get(activeAccountId.value)?.currency || 'USD'
+    return convertVolume(symbol, volume, VolumeUnit.NOTIONAL, {
+      prices: marketEngineStore.getMidPrices(),
+      accountCurrency: currency
+    })
+  }
+  
+  // Market quote for a symbol, or that broker's quote around it when a broker id is given
+  const getQuote = (symbol, { brokerId = null } = {}) => {
+    const price = marketPrices.value.get(symbol)
+    if (!price) return null
+    if (!brokerId) return { symbol, ...price, spread: price.ask - price.bid }
+    const quote = brokerStore.getBrokerQuote(brokerId, symbol, price, marketEngineStore.getMarketStress(symbol).level)
+    return quote ? { ...quote, timestamp: price.timestamp, volume: price.volume } : null
+  }
+  
+  // Stop/take-profit heatmap including the stops on every account's positions
+  const getLiquidityMap = (symbol = selectedSymbol.value, options = {}) => {
+    const levels = accountList.value.flatMap(acc => acc.positions
+      .filter(position => position.symbol === symbol && position.stop_loss)
+      .map(position => ({
+        price: position.stop_loss,
+        volume: position.volume,
+        side: position.side === 'Buy' ? 'Sell' : 'Buy',
+        kind: 'stop'
+      })))
+    return marketEngineStore.getLiquidityMap(symbol, { ...options, levels })
+  }
+  
+  // Commission and costs for a prospective trade on the selected broker
+  const getTradingCosts = (symbol, side, volume, leverage = account.value.leverage) => {
+    const broker = brokerStore.selectedBroker
+    if (!broker) return null
+    const price = marketPrices.value.get(symbol)
+    return brokerStore.calculateTradingCosts(broker.id, symbol, side, volume, leverage, {
+      notional: getTradeNotional(symbol, volume),
+      monthlyVolume: getMonthlyVolume(broker.id),
+      stress: marketEngineStore.getMarketStress(symbol).level,
+      underlyingSpread: price ? price.ask - price.bid : null
+    })
+  }
+  
+  // Notional an account has traded with a broker in the current sim month, for tiered
+  // commission. Derived from the ledger's commission fees, so it survives a reload.
+  const getMonthlyVolume = (brokerId, accountId = activeAccountId.value) => {
+    const events = accountId === activeAccountId.value ? accountEvents.value : accounts.value.get(accountId)?.events || []
+    return getMonthlyTradedNotional(events, brokerId, settlementClock.value.simDate.slice(0, 7))
+  }
+  
+  // Charges one side's commission to the trade's account. The fee records the notional and
+  // sim date it was charged on, which is what the monthly volume tier counts.
+  const chargeCommission = (position, accountId = activeAccountId.value) => {
+    const broker = brokerStore.selectedBroker
+    if (!broker) return 0
+    
+    const notional = getTradeNotional(position.symbol, position.volume, accounts.value.get(accountId)?.currency)
+    const commission = brokerStore.calculateCommission(broker.id, position.volume, {
+      notional,
+      monthlyVolume: getMonthlyVolume(broker.id, accountId)
+    })
+    if (commission > 0) {
+      recordAccountEvent(AccountEventType.FEE, {
+        amount: commission,
+        reason: 'Commission',
+        scheme: broker.commission.scheme,
+        brokerId: broker.id,
+        notional,
+        simDate: settlementClock.value.simDate,
+        positionId: position.id,
+        symbol: position.symbol,
+        strategyTag: position.strategy_tag || null
+      }, accountId)
+    }
+    return commission
+  }
+  
+  const recordCashFlows = (position, side, price, reason, valueDate = null) => {
+    const simDate = settlementClock.value.simDate
+    settlementLedger.value.push(...createCashFlows({
+      symbol: position.symbol,
+      side,
+      volume: position.volume,
+      price,
+      tradeDate: simDate,
+      valueDate: valueDate || getSpotDate(position.symbol, simDate),
+      reason,
+      positionId: position.id
+    }))
+  }
+  
+  const advanceSettlementClock = (now = Date.now()) => {
+    const clock = settlementClock.value
+    while (now - clock.dayStartedAt >= clock.dayLengthMs) {
+      clock.dayStartedAt += clock.dayLengthMs
+      rollSettlementDay()
+    }
+    
+    const warningAt = clock.dayStartedAt + clock.dayLengthMs * riskReportSettings.warningRatio
+    if (now >= warningAt && clock.reportedFor !== clock.simDate) {
+      clock.reportedFor = clock.simDate
+      publishRiskReports()
+    }
+  }
+  
+  // Private channel: pass an accountId to only receive that account's notices
+  const subscribe = (handler, accountId = null) => {
+    accountSubscribers.set(handler, accountId)
+    return () => accountSubscribers.delete(handler)
+  }
+  
+  const notifyAccount = (accountId, type, payload) => {
+    const event = { type, accountId, timestamp: Date.now(), ...payload }
+    for (const [handler, filter] of accountSubscribers) {
+      if (filter && filter !== accountId) continue
+      try {
+        handler(event)
+      } catch (error) {
+        console.warn('Account notice handler failed:', error)
+      }
+    }
+  }
+  
+  // Overnight unless the next business day is more than one night away
+  const getCloseType = () => {
+    const simDate = settlementClock.value.simDate
+    return nightsBetween(simDate, addBusinessDays(simDate, 1)) > 1
+      ? RiskReportType.WEEKEND
+      : RiskReportType.OVERNIGHT
+  }
+  
+  const getRiskReport = (accountId = activeAccountId.value, type = getCloseType()) => {
+    const isActive = accountId === activeAccountId.value
+    const acc = accounts.value.get(accountId)
+    if (!isActive && !acc) return null
+    
+    const report = buildRiskReport({
+      accountId,
+      account: isActive ? account.value : acc.account,
+      positions: isActive ? positions.value : acc.positions,
+      type,
+      simDate: settlementClock.value.simDate,
+      calculatePnl,
+      getNotional: (symbol, units) => getTradeNotional(symbol, units, acc?.currency)
+    })
+    return { ...report, exposureLimits: getExposureUtilization(accountId) }
+  }
+  
+  const publishRiskReports = () => {
+    const type = getCloseType()
+    const accountIds = new Set([activeAccountId.value, ...accounts.value.keys()])
+    accountIds.forEach(accountId => {
+      const report = getRiskReport(accountId, type)
+      if (!report) return
+      riskReports.value.set(accountId, report)
+      notifyAccount(accountId, RISK_REPORT_NOTICE, { report })
+    })
+  }
+  
+  // The day's statement covers ledger events booked since the account's last one; a reset
+  // ledger no longer holds that event, so all of its events count
+  const publishDailyStatements = (simDate) => {
+    const accountIds = new Set([activeAccountId.value, ...accounts.value.keys()])
+    accountIds.forEach(accountId => {
+      const isActive = accountId === activeAccountId.value
+      const acc = accounts.value.get(accountId)
+      if (!isActive && !acc) return
+      
+      const events = isActive ? accountEvents.value : acc.events
+      const history = dailyStatements.value.get(accountId) || []
+      const statement = buildDailyStatement({
+        accountId,
+        currency: acc?.currency || 'USD',
+        simDate,
+        account: isActive ? account.value : acc.account,
+        positions: isActive ? positions.value : acc.positions,
+        events: events.slice(events.lastIndexOf(statementCursors.get(accountId)) + 1),
+        previous: history[history.length - 1]
+      })
+      statementCursors.set(accountId, events[events.length - 1])
+      
+      history.push(statement)
+      if (history.length > dailyStatementSettings.maxStatements) history.shift()
+      dailyStatements.value.set(accountId, history)
+      notifyAccount(accountId, DAILY_STATEMENT_NOTICE, { statement })
+    })
+  }
+  
+  // Newest first, optionally limited to sim dates from..to (YYYY-MM-DD, inclusive)
+  const getDailyStatements = (accountId = activeAccountId.value, { from = null, to = null } = {}) => {
+    return (dailyStatements.value.get(accountId) || [])
+      .filter(statement => (!from || statement.simDate >= from) && (!to || statement.simDate <= to))
+      .reverse()
+  }
+  
+  // Moves sim time to the next business day: positions whose value date is now tom are
+  // rolled out to the new spot date (charging swap per night rolled), then due flows settle
+  // and the closed day's statements go out
+  const rollSettlementDay = () => {
+    const clock = settlementClock.value
+    const closedDate = clock.simDate
+    clock.simDate = addBusinessDays(clock.simDate, 1)
+    const tom = addBusinessDays(clock.simDate, 1)
+    const broker = brokerStore.selectedBroker
+    
+    positions.value.forEach(position => {
+      if (!position.value_date || position.value_date > tom) return
+      
+      const spot = getSpotDate(position.symbol, clock.simDate)
+      const nights = nightsBetween(position.value_date, spot)
+      const reverse = position.side === 'Buy' ? 'Sell' : 'Buy'
+      recordCashFlows(position, reverse, position.current_price, SettlementReason.ROLL_OUT, position.value_date)
+      recordCashFlows(position, position.side, position.current_price, SettlementReason.ROLL_IN, spot)
+      
+      const swap = broker ? broker.calculateSwap(position.symbol, position.side, position.volume) * nights : 0
+      if (swap !== 0) {
+        recordAccountEvent(AccountEventType.SWAP, {
+          amount: swap,
+          positionId: position.id,
+          symbol: position.symbol,
+          fromValueDate: position.value_date,
+          toValueDate: spot,
+          nights,
+          strategyTag: position.strategy_tag || null
+        })
+      }
+      position.value_date = spot
+    })
+    
+    settleDueFlows(settlementLedger.value, clock.simDate)
+    updateAccountInfo()
+    publishDailyStatements(closedDate)
+    return clock.simDate
+  }
+  
+  const getUnsettledCashFlows = () => {
+    return {
+      simDate: settlementClock.value.simDate,
+      currencies: summarizeUnsettled(settlementLedger.value)
+    }
+  }
+  
+  const getSettlementLedger = ({ positionId = null } = {}) => {
+    return positionId
+      ? settlementLedger.value.filter(flow => flow.positionId === positionId)
+      : settlementLedger.value.slice()
+  }
+  
+  // Open positions with volumes in the requested unit and prices at instrument precision
+  const getPositionsReport = ({ volumeUnit = VolumeUnit.UNITS, accountCurrency = null } = {}) => {
+    const currency = accountCurrency || accounts.value.get(activeAccountId.value)?.currency || 'USD'
+    const prices = marketEngineStore.getMidPrices()
+
+    return positions.value.map(position => ({
+      ...position,
+      volume: convertVolume(position.symbol, position.volume, volumeUnit, { prices, accountCurrency: currency }),
+      volume_unit: volumeUnit,
+      entry_price: roundPrice(position.symbol, position.entry_price),
+      current_price: roundPrice(position.symbol, position.current_price),
+      currency
+    }))
+  }
+  
+  // P&L, costs and trade statistics per strategy tag for an account
+  const getStrategyReport = (accountId = activeAccountId.value) => {
+    const isActive = accountId === activeAccountId.value
+    const acc = accounts.value.get(accountId)
+    if (!isActive && !acc) return []
+    return buildStrategyAttribution(
+      eventsSinceReset(isActive ? accountEvents.value : acc.events),
+      isActive ? positions.value : acc.positions
+    )
+  }
+  
+  // Fills of an account's own trades, in the shape the execution analysis takes
+  const getJournalFills = (events) => {
+    const opened = new Map()
+    const fills = []
+    events.forEach(event => {
+      if (event.type === AccountEventType.POSITION_OPENED) {
+        const position = event.position
+        opened.set(position.id, position)
+        fills.push({
+          id: position.id,
+          timestamp: position.timestamp,
+          symbol: position.symbol,
+          side: position.side,
+          volume: position.volume,
+          price: position.entry_price,
+          strategyTag: position.strategy_tag || null
+        })
+      } else if (event.type === AccountEventType.POSITION_CLOSED || event.type === AccountEventType.LIQUIDATION) {
+        const position = opened.get(event.positionId)
+        if (!position) return
+        fills.push({
+          id: `${position.id}_close`,
+          timestamp: event.timestamp,
+          symbol: position.symbol,
+          side: position.side === 'Buy' ? 'Sell' : 'Buy',
+          volume: position.volume,
+          price: event.closePrice,
+          strategyTag: position.strategy_tag || null
+        })
+      }
+    })
+    return fills
+  }
+  
+  const getCandles = (symbol) => priceHistories.value[symbol]?.['1m'] || []
+  
+  // Execution quality and per-strategy attribution for an account's trades
+  const getJournalReport = (accountId = activeAccountId.value) => {
+    const isActive = accountId === activeAccountId.value
+    const acc = accounts.value.get(accountId)
+    if (!isActive && !acc) return null
+    const events = eventsSinceReset(isActive ? accountEvents.value : acc.events)
+    return {
+      accountId,
+      attribution: getStrategyReport(accountId),
+      execution: buildExecutionAnalysis(getJournalFills(events), getCandles)
+    }
+  }
+  
+  // Prices a CSV of external trades against the simulator's candles and reports them
+  // the same way as native trades. Lines that fail to parse are returned, not fatal.
+  const importJournal = (csv, { name = null } = {}) => {
+    const { trades, errors } = parseTradeCsv(csv, { symbols: marketEngineStore.config.symbols })
+    if (trades.length === 0) {
+      return { success: false, error: 'No valid trades to import', errors }
+    }
+    
+    const { events, openPositions } = tradesToLedgerEvents(trades, {
+      calculatePnl,
+      getMark: (symbol) => {
+        const quote = marketPrices.value.get(symbol)
+        return quote ? (quote.bid + quote.ask) / 2 : null
+      }
+    })
+    const report = {
+      id: `journal_${Date.now()}_${journalImports.value.length + 1}`,
+      name,
+      importedAt: new Date().toISOString(),
+      trades,
+      errors,
+      attribution: buildStrategyAttribution(events, openPositions),
+      execution: buildExecutionAnalysis(trades, getCandles)
+    }
+    journalImports.value.push(report)
+    getStorage().set('journalImports', journalImports.value)
+    return { success: true, data: report }
+  }
+  
+  const getJournalImport = (importId) => journalImports.value.find(entry => entry.id === importId) || null
+  
+  const getAccountLedger = (accountId = activeAccountId.value) => {
+    if (accountId === activeAccountId.value) return accountEvents.value.slice()
+    const acc = accounts.value.get(accountId)
+    return acc ? acc.events.slice() : []
+  }
+  
+  // Runs a listing through the shared query conventions (see listQuery.js)
+  const runListQuery = (entries, query, defaults = {}, options = {}) => {
+    try {
+      return { success: true, data: queryList(entries, { ...defaults, ...query }, options) }
+    } catch (error) {
+      return { success: false, error: error.message }
+    }
+  }
+  
+  // Positions by status from the account ledger, so closed ones stay queryable after they
+  // leave the live book. status is 'open', 'closed' or 'all'; closed positions list most
+  // recently closed first, the rest most recently opened first.
+  const getPositionHistory = ({ status = 'closed', accountId = activeAccountId.value, ...query } = {}) => {
+    const wanted = { open: ['Open'], closed: ['Closed'], all: ['Open', 'Closed'] }[status]
+    if (!wanted) return { success: false, error: `Unknown position status ${status}` }
+    const entries = buildPositionHistory(getAccountLedger(accountId)).filter(position => wanted.includes(position.status))
+    return runListQuery(entries, query, { sort: status === 'closed' ? '-closed_at' : '-timestamp' })
+  }
+  
+  // Opening and closing fills of the account's positions, newest first
+  const getFilledOrders = ({ accountId = activeAccountId.value, ...query } = {}) => {
+    return runListQuery(buildFillHistory(getAccountLedger(accountId)), query, { sort: '-timestamp' })
+  }
+  
+  // Candles of one timeframe, oldest first, keyed by their timestamp
+  const listCandles = (symbol, candleTimeframe = '1m', query = {}) => {
+    if (!CANDLE_TIMEFRAMES[candleTimeframe]) return { success: false, error: `Unknown timeframe ${candleTimeframe}` }
+    const candles = priceHistories.value[symbol]?.[candleTimeframe] || []
+    return runListQuery(candles, query, { sort: 'timestamp' }, { idField: 'timestamp' })
+  }
+  
+  // Engine events for a market data client, opened by a SubscriptionSnapshot whose books also
+  // carry the last `candles` candles of `timeframe` (see buildSubscriptionSnapshot). Later
+  // candles build from the trades that follow. Returns the unsubscribe function.
+  const subscribeMarketData = (handler, {
+    clientId = null,
+    symbols = null,
+    timeframe: candleTimeframe = '1m',
+    candles = marketEngineStore.config.subscriptionSnapshots.candles
+  } = {}) => {
+    if (!CANDLE_TIMEFRAMES[candleTimeframe]) throw new Error(`Unknown timeframe ${candleTimeframe}`)
+    return marketEngineStore.subscribe(handler, {
+      clientId,
+      snapshot: {
+        symbols,
+        extend: (symbol) => ({
+          timeframe: candleTimeframe,
+          candles: (priceHistories.value[symbol]?.[candleTimeframe] || []).slice(-candles)
+        })
+      }
+    })
+  }
+  
+  const rebuildAccountFromEvents = () => {
+    const state = replayAccountEvents(accountEvents.value, {
+      balance: 0,
+      leverage: account.value.leverage
+    })
+    
+    account.value.balance = state.balance
+    account.value.leverage = state.leverage
+    positions.value = state.positions
+    
+    marketEngineStore.updateUserBalance(state.balance)
+    updatePositionPrices()
+    return { balance: state.balance, positions: state.positions.length }
+  }
+  
   const setAccountBalance = (balance) => {
-    account.value.balance = balance
+    recordAccountEvent(AccountEventType.BALANCE_SET, { balance })
     account.value.equity = balance + positions.value.reduce((sum, pos) => sum + pos.unrealized_pnl, 0)
     account.value.free_margin = account.value.equity - account.value.margin_used
     
@@ -255,9 +1443,322 @@ export const useMarketStore = defineStore('market', () => {
     marketEngineStore.updateUserBalance(balance)
   }
   
+  // Storage per config.storage (see storage.js), created on first use. Ledgers are stored
+  // one event per key under ledger/<accountId>/<seq>, so recording an event is a single
+  // write; the accounts key indexes them and remembers the active one.
+  const getStorage = () => {
+    if (!storage) storage = createStorage(marketEngineStore.config.storage)
+    return storage
+  }
+  
+  const getStorageInfo = () => {
+    const { mode, requestedMode, namespace, durable } = getStorage()
+    return { mode, requestedMode, namespace, durable }
+  }
+  
+  const storeLedgerEvent = (accountId, event) => {
+    const store = getStorage()
+    // A ledger starts at seq 1; anything stored under the id before is from an older account
+    if (event.seq === 1) store.keys(`ledger/${accountId}/`).forEach(key => store.remove(key))
+    store.set(`ledger/${accountId}/${event.seq}`, event)
+  }
+  
+  const loadLedger = (accountId) => {
+    const store = getStorage()
+    return store.keys(`ledger/${accountId}/`)
+      .map(key => store.get(key))
+      .filter(Boolean)
+      .sort((a, b) => a.seq - b.seq)
+  }
+  
+  const storeAccountIndex = () => {
+    getStorage().set('accounts', {
+      activeAccountId: activeAccountId.value,
+      accounts: Array.from(accounts.value.values()).map(({ id, name, brokerId, currency }) => ({ id, name, brokerId, currency }))
+    })
+  }
+  
+  // Accounts left in storage by an earlier session, rebuilt by replaying their ledgers.
+  // Only durable storage ever has any. Pending orders, risk controls and challenges are
+  // not stored and start fresh.
+  const restoreAccounts = () => {
+    const store = getStorage()
+    const index = store.get('accounts')
+    journalImports.value = store.get('journalImports', journalImports.value)
+    if (!index) return false
+    
+    index.accounts.forEach(({ id, name, brokerId, currency }) => {
+      const events = loadLedger(id)
+      if (events.length === 0) return
+      const state = replayAccountEvents(events)
+      accounts.value.set(id, {
+        id,
+        name,
+        brokerId,
+        currency,
+        account: {
+          balance: state.balance,
+          equity: state.balance,
+          margin_used: 0,
+          free_margin: state.balance,
+          leverage: state.leverage
+        },
+        positions: state.positions,
+        pendingOrders: [],
+        events,
+        settlementLedger: [],
+        riskControls: createRiskControls(),
+        challenge: null
+      })
+    })
+    
+    const active = accounts.value.get(index.activeAccountId) || accounts.value.values().next().value
+    if (!active) return false
+    activeAccountId.value = active.id
+    account.value = { ...active.account }
+    positions.value = active.positions
+    pendingOrders.value = active.pendingOrders
+    accountEvents.value = active.events
+    settlementLedger.value = active.settlementLedger
+    riskControls.value = active.riskControls
+    challenge.value = null
+    // Engine state doesn't outlive the page, so the user participant starts from the ledger
+    // balance; the other restored accounts do the same when first switched to
+    marketEngineStore.resetUserParticipant(account.value.balance, account.value.leverage)
+    return true
+  }
+  
+  const saveActiveAccount = () => {
+    const active = accounts.value.get(activeAccountId.value)
+    if (active) {
+      active.account = { ...account.value }
+      active.positions = positions.value
+      active.pendingOrders = pendingOrders.value
+      active.events = accountEvents.value
+      active.settlementLedger = settlementLedger.value
+      active.riskControls = riskControls.value
+      active.challenge = challenge.value
+      // The engine's user participant is shared by all accounts; keep this account's part
+      active.engineState = marketEngineStore.saveUserParticipant()
+    }
+  }
+  
+  const setAccountOpeningLimits = (limits) => {
+    accountOpeningLimits.value = { ...accountOpeningLimits.value, ...limits }
+    return { ...accountOpeningLimits.value }
+  }
+  
+  // Every problem with the requested opening profile, empty when it can be opened
+  const validateAccountOpening = ({ balance, leverage, currency, brokerId }) => {
+    const limits = accountOpeningLimits.value
+    const errors = []
+    if (!Number.isFinite(balance) || balance < limits.balance.min || balance > limits.balance.max) {
+      errors.push(`Balance must be between ${limits.balance.min} and ${limits.balance.max}`)
+    }
+    if (!Number.isFinite(leverage) || leverage < limits.leverage.min || leverage > limits.leverage.max) {
+      errors.push(`Leverage must be between ${limits.leverage.min} and ${limits.leverage.max}`)
+    }
+    if (!limits.currencies.includes(currency)) {
+      errors.push(`Account currency ${currency} is not offered`)
+    }
+    if (brokerId) {
+      const broker = brokerStore.getBroker(brokerId)
+      if (!broker || (limits.brokerIds && !limits.brokerIds.includes(brokerId))) {
+        errors.push(`Broker ${brokerId} is not available`)
+      }
+    }
+    return errors
+  }
+  
+  const createAccount = (accountData = {}) => {
+    const template = accountTemplates[accountData.template] || accountTemplates.standard
+    const balance = accountData.balance ?? template.balance
+    const leverage = accountData.leverage ?? template.leverage
+    const currency = accountData.currency || 'USD'
+    const brokerId = accountData.brokerId || brokerStore.selectedBroker?.id || null
+    const id = accountData.id || `acc_${Date.now().toString(36)}${Math.random().toString(36).substr(2, 4)}`
+    
+    if (accounts.value.has(id)) {
+      return { success: false, error: `Account ${id} already exists` }
+    }
+    
+    const errors = validateAccountOpening({ balance, leverage, currency, brokerId })
+    if (errors.length > 0) {
+      return { success: false, error: errors.join('; '), errors }
+    }
+    
+    const newAccount = {
+      id,
+      name: accountData.name || `Account ${accounts.value.size + 1}`,
+      brokerId,
+      currency,
+      account: {
+        balance,
+        equity: balance,
+        margin_used: 0,
+        free_margin: balance,
+        leverage
+      },
+      positions: [],
+      pendingOrders: [],
+      events: [],
+      settlementLedger: [],
+      riskControls: createRiskControls(),
+      challenge: null
+    }
+    newAccount.events.push(createAccountEvent(newAccount.events, AccountEventType.RESET, {
+      balance,
+      leverage: newAccount.account.leverage
+    }))
+    accounts.value.set(id, newAccount)
+    storeLedgerEvent(id, newAccount.events[0])
+    storeAccountIndex()
+    
+    return { success: true, data: newAccount }
+  }
+  
+  const switchAccount = (accountId) => {
+    const target = accounts.value.get(accountId)
+    if (!target) return false
+    if (accountId === activeAccountId.value) return true
+    
+    saveActiveAccount()
+    
+    activeAccountId.value = accountId
+    account.value = { ...target.account }
+    positions.value = target.positions
+    pendingOrders.value = target.pendingOrders
+    accountEvents.value = target.events
+    settlementLedger.value = target.settlementLedger || []
+    riskControls.value = target.riskControls
+    challenge.value = target.challenge
+    
+    if (target.brokerId) {
+      brokerStore.selectBroker(target.brokerId)
+    }
+    // An account that has been active before gets its engine state back; a new one starts
+    // from its opening balance
+    if (target.engineState) {
+      marketEngineStore.restoreUserParticipant(target.engineState)
+      target.engineState = null
+    } else {
+      marketEngineStore.resetUserParticipant(account.value.balance, account.value.leverage)
+    }
+    updatePositionPrices()
+    storeAccountIndex()
+    
+    return true
+  }
+  
+  const deleteAccount = (accountId) => {
+    if (accountId === activeAccountId.value || accounts.value.size <= 1) return false
+    if (!accounts.value.delete(accountId)) return false
+    const store = getStorage()
+    store.keys(`ledger/${accountId}/`).forEach(key => store.remove(key))
+    storeAccountIndex()
+    return true
+  }
+  
+  const resetAccount = (templateId = 'standard') => {
+    const template = accountTemplates[templateId]
+    if (!template) {
+      return { success: false, error: `Unknown account template ${templateId}` }
+    }
+    
+    challenge.value = null
+    return resetAccountTo(template, templateId)
+  }
+  
+  const startChallenge = (presetId = 'standard') => {
+    const rules = challengePresets[presetId]
+    if (!rules) {
+      return { success: false, error: `Unknown challenge preset ${presetId}` }
+    }
+    
+    const result = resetAccountTo(rules, `challenge:${presetId}`)
+    challenge.value = {
+      presetId,
+      rules: { ...rules },
+      status: ChallengeStatus.ACTIVE,
+      failureReason: null,
+      startedAt: new Date().toISOString(),
+      endedAt: null
+    }
+    return result
+  }
+  
+  const getTradingDays = () => {
+    const days = new Set(
+      eventsSinceReset(accountEvents.value)
+        .filter(e => e.type === AccountEventType.POSITION_OPENED)
+        .map(e => e.timestamp.slice(0, 10))
+    )
+    return days.size
+  }
+  
+  const getChallengeCompliance = () => {
+    if (!challenge.value) return null
+    
+    const evaluation = evaluateChallenge(challenge.value, {
+      equity: account.value.equity,
+      dayStartEquity: riskControls.value.dayStartEquity,
+      tradingDays: getTradingDays()
+    })
+    return { ...challenge.value, ...evaluation }
+  }
+  
+  const updateChallengeStatus = () => {
+    if (!challenge.value || challenge.value.status !== ChallengeStatus.ACTIVE) return
+    
+    const compliance = getChallengeCompliance()
+    if (compliance.status === ChallengeStatus.ACTIVE) return
+    
+    challenge.value.status = compliance.status
+    challenge.value.failureReason = compliance.failureReason
+    challenge.value.endedAt = new Date().toISOString()
+    if (compliance.status === ChallengeStatus.FAILED) {
+      triggerKillSwitch(`Challenge failed: ${compliance.failureReason}`)
+    }
+  }
+  
+  const resetAccountTo = (template, templateId) => {
+    // Close everything without realizing P&L. The ledger keeps its history; the RESET
+    // recorded below starts the account over from the template.
+    positions.value = []
+    pendingOrders.value = []
+    settlementLedger.value = []
+    riskControls.value = {
+      ...createRiskControls(),
+      maxOrdersPerMinute: riskControls.value.maxOrdersPerMinute,
+      maxPositionSize: riskControls.value.maxPositionSize,
+      maxDailyLoss: riskControls.value.maxDailyLoss,
+      exposureLimits: riskControls.value.exposureLimits,
+      adminExposureLimits: riskControls.value.adminExposureLimits,
+      exposureLimitAction: riskControls.value.exposureLimitAction
+    }
+    
+    account.value = {
+      balance: template.balance,
+      equity: template.balance,
+      margin_used: 0,
+      free_margin: template.balance,
+      leverage: template.leverage
+    }
+    recordAccountEvent(AccountEventType.RESET, {
+      balance: template.balance,
+      leverage: template.leverage,
+      template: templateId
+    })
+    
+    marketEngineStore.resetUserParticipant(template.balance, template.leverage)
+    updateAccountInfo()
+    
+    return { success: true, data: { template: templateId, ...account.value } }
+  }
+  
   const addPosition = (position) => {
     const marginRequired = (position.amount * position.price) / account.value.leverage
-    positions.value.push({
+    const newPosition = {
       id: Date.now() + Math.random(),
       symbol: position.symbol,
       side: position.side,
@@ -266,20 +1767,41 @@ export const useMarketStore = defineStore('market', () => {
       current_price: position.price,
       unrealized_pnl: 0,
       margin_required: marginRequired,
+      value_date: getSpotDate(position.symbol, settlementClock.value.simDate),
       timestamp: new Date().toISOString()
-    })
+    }
+    recordAccountEvent(AccountEventType.POSITION_OPENED, { position: { ...newPosition } })
+    recordCashFlows(newPosition, newPosition.side, newPosition.entry_price, SettlementReason.TRADE)
+    positions.value.push(newPosition)
     account.value.margin_used += marginRequired
     account.value.free_margin -= marginRequired
     updateAccountInfo()
   }
   
-  const closePosition = (positionId) => {
+  // closePrice defaults to the current mark; stops and stop-outs pass their slipped fill
+  // price, stop-outs are booked as Liquidation
+  const closePosition = (positionId, { closePrice = null, eventType = AccountEventType.POSITION_CLOSED, ...details } = {}) => {
     const index = positions.value.findIndex(p => p.id === positionId)
     if (index !== -1) {
       const position = positions.value[index]
+      const price = closePrice ?? position.current_price
       
       // Realize the P&L
-      account.value.balance += position.unrealized_pnl
+      recordAccountEvent(eventType, {
+        positionId: position.id,
+        symbol: position.symbol,
+        closePrice: price,
+        realizedPnl: calculatePnl(position, price),
+        strategyTag: position.strategy_tag || null,
+        ...details
+      })
+      recordCashFlows(
+        position,
+        position.side === 'Buy' ? 'Sell' : 'Buy',
+        price,
+        SettlementReason.CLOSE
+      )
+      chargeCommission(position)
       
       // Free up the margin
       account.value.margin_used -= position.margin_required
@@ -292,134 +1814,163 @@ export const useMarketStore = defineStore('market', () => {
     }
   }
   
+  const updatePositionPrice = (position) => {
+    const symbolPrice = marketPrices.value.get(position.symbol)
+    if (symbolPrice) {
+      const currentPrice = (symbolPrice.bid + symbolPrice.ask) / 2
+      position.current_price = currentPrice
+      position.unrealized_pnl = calculatePnl(position, currentPrice)
+    }
+  }
+  
+  const calculatePnl = (position, price) => {
+    const priceDiff = position.side === 'Buy' 
+      ? price - position.entry_price 
+      : position.entry_price - price
+    
+    // For USD/JPY, pip value is different
+    const pipValue = position.symbol === 'USDJPY' ? 100 : 10000
+    return priceDiff * position.volume * pipValue
+  }
+  
+  // Closes positions whose stop has been touched. The fill comes from the broker's stop
+  // model, so gaps and fast markets slip through the stop level instead of filling at it.
+  const checkStopLosses = () => {
+    const broker = brokerStore.selectedBroker
+    const triggered = positions.value.filter(position => {
+      const price = marketPrices.value.get(position.symbol)
+      if (!position.stop_loss || !price) return false
+      return position.side === 'Buy' ? price.bid <= position.stop_loss : price.ask >= position.stop_loss
+    })
+    
+    triggered.forEach(position => {
+      const price = marketPrices.value.get(position.symbol)
+      const closeSide = position.side === 'Buy' ? 'Sell' : 'Buy'
+      const marketPrice = closeSide === 'Sell' ? price.bid : price.ask
+      const volatility = marketEngineStore.getConditionFactors(position.symbol).volatility
+      const fastMarket = broker ? volatility >= broker.slippageModel.fastMarketVolatility : false
+      const fillPrice = broker
+        ? broker.fillStop(position.stop_loss, closeSide, marketPrice, { fastMarket })
+        : marketPrice
+      
+      closePosition(position.id, {
+        closePrice: fillPrice,
+        reason: 'StopLoss',
+        stopPrice: position.stop_loss,
+        stopSlippage: closeSide === 'Sell' ? position.stop_loss - fillPrice : fillPrice - position.stop_loss,
+        fastMarket
+      })
+    })
+  }
+  
+  // Broker stop-out: while the margin level is below riskReportSettings.stopOutLevel the
+  // worst-losing position is closed at market. Each close keeps the context it was made
+  // in (the equity path before it, the quote, the order sent and the fill) for review.
+  const stopOutSettings = {
+    enabled: true,
+    equitySamples: 60 // margin level samples kept for the audit's equity path
+  }
+  const equityPath = [] // { accountId, time, equity, marginUsed, marginLevel }, oldest first
+  const liquidations = ref([]) // stop-out audits, oldest first
+  
+  const marginLevelOf = (acc) => (acc.margin_used > 0 ? acc.equity / acc.margin_used * 100 : Infinity)
+  
+  const checkStopOut = () => {
+    equityPath.push({
+      accountId: activeAccountId.value,
+      time: Date.now(),
+      equity: account.value.equity,
+      marginUsed: account.value.margin_used,
+      marginLevel: marginLevelOf(account.value)
+    })
+    if (equityPath.length > stopOutSettings.equitySamples) equityPath.shift()
+    if (!stopOutSettings.enabled) return
+    
+    const broker = brokerStore.selectedBroker
+    while (positions.value.length > 0 && marginLevelOf(account.value) < riskReportSettings.stopOutLevel) {
+      const position = positions.value.reduce((worst, p) => (p.unrealized_pnl < worst.unrealized_pnl ? p : worst))
+      const price = marketPrices.value.get(position.symbol)
+      const closeSide = position.side === 'Buy' ? 'Sell' : 'Buy'
+      const marketPrice = price ? (closeSide === 'Sell' ? price.bid : price.ask) : position.current_price
+      const volatility = marketEngineStore.getConditionFactors(position.symbol).volatility
+      const fastMarket = broker ? volatility >= broker.slippageModel.fastMarketVolatility : false
+      const fillPrice = broker ? broker.applySlippage(marketPrice, closeSide, { fastMarket }) : marketPrice
+      
+      const before = { ...account.value, marginLevel: marginLevelOf(account.value) }
+      closePosition(position.id, {
+        closePrice: fillPrice,
+        eventType: AccountEventType.LIQUIDATION,
+        reason: 'StopOut',
+        marginLevel: before.marginLevel
+      })
+      liquidations.value.push({
+        id: `liq_${Date.now().toString(36)}${Math.random().toString(36).substr(2, 4)}`,
+        accountId: activeAccountId.value,
+        brokerId: broker?.id || null,
+        timestamp: new Date().toISOString(),
+        stopOutLevel: riskReportSettings.stopOutLevel,
+        equityPath: equityPath.filter(sample => sample.accountId === activeAccountId.value),
+        accountBefore: before,
+        accountAfter: { ...account.value, marginLevel: marginLevelOf(account.value) },
+        prices: price ? { bid: price.bid, ask: price.ask, timestamp: price.timestamp } : null,
+        position: { ...position },
+        order: { symbol: position.symbol, side: closeSide, volume: position.volume, orderType: 'Market', expectedPrice: marketPrice },
+        fill: {
+          price: fillPrice,
+          slippage: closeSide === 'Sell' ? marketPrice - fillPrice : fillPrice - marketPrice,
+          fastMarket,
+          realizedPnl: calculatePnl(position, fillPrice)
+        }
+      })
+    }
+  }
+  
+  // Newest first
+  const getLiquidations = (accountId = activeAccountId.value) => {
+    return liquidations.value.filter(entry => entry.accountId === accountId).reverse()
+  }
+  
   const updatePositionPrices = () => {
-    positions.value.forEach(position => {
-      const symbolPrice = marketPrices.value.get(position.symbol)
-      if (symbolPrice) {
-        const currentPrice = (symbolPrice.bid + symbolPrice.ask) / 2
-        position.current_price = currentPrice
-        
-        // Calculate P&L
-        const priceDiff = position.side === 'Buy' 
-          ? currentPrice - position.entry_price 
-          : position.entry_price - currentPrice
-        
-        // For USD/JPY, pip value is different
-        const pipValue = position.symbol === 'USDJPY' ? 100 : 10000
-        position.unrealized_pnl = priceDiff * position.volume * pipValue
-      }
+    positions.value.forEach(updatePositionPrice)
+    checkStopLosses()
+    
+    // Keep inactive accounts marked to market for consolidated reporting
+    accounts.value.forEach(acc => {
+      if (acc.id === activeAccountId.value) return
+      acc.positions.forEach(updatePositionPrice)
+      const pnl = acc.positions.reduce((sum, pos) => sum + pos.unrealized_pnl, 0)
+      acc.account.equity = acc.account.balance + pnl
+      acc.account.free_margin = acc.account.equity - acc.account.margin_used
     })
+    
     updateAccountInfo()
+    checkStopOut()
   }
   
   const updateCandleData = (symbol, bid, ask, volume, timestamp) => {
-    const midPrice = (bid + ask) / 2
-    const timeframes = {
-      '1m': 60,
-      '5m': 300,
-      '15m': 900,
-      '1h': 3600,
-      '4h': 14400,
-      '1d': 86400
-    }
-    
     // Ensure price history exists for this symbol
     if (!priceHistories.value[symbol]) {
-      priceHistories.value[symbol] = {
-        '1m': [], '5m': [], '15m': [], '1h': [], '4h': [], '1d': []
-      }
+      priceHistories.value[symbol] = createCandleSeries()
     }
     if (!lastCandleTimestamps.value[symbol]) {
       lastCandleTimestamps.value[symbol] = {}
     }
     
-    Object.entries(timeframes).forEach(([tf, seconds]) => {
-      // Round timestamp to the timeframe interval
-      const roundedTimestamp = Math.floor(timestamp / seconds) * seconds
-      
-      // Get the last candle for this timeframe
-      const tfData = priceHistories.value[symbol][tf]
-      let lastCandle = tfData[tfData.length - 1]
-      
-      if (!lastCandle || lastCandle.timestamp !== roundedTimestamp) {
-        // Create new candle
-        const newCandle = {
-          timestamp: roundedTimestamp,
-          open: midPrice,
-          high: midPrice,
-          low: midPrice,
-          close: midPrice,
-          volume: volume || 0
-        }
-        tfData.push(newCandle)
-        
-        // Keep only last 1000 candles for 1m, 500 for others
-        const maxCandles = tf === '1m' ? 1000 : 500
-        if (tfData.length > maxCandles) {
-          tfData.shift()
-        }
-        
-        lastCandleTimestamps.value[symbol][tf] = roundedTimestamp
-      } else {
-        // Update existing candle
-        lastCandle.high = Math.max(lastCandle.high, midPrice)
-        lastCandle.low = Math.min(lastCandle.low, midPrice)
-        lastCandle.close = midPrice
-        lastCandle.volume += volume || 0
-      }
+    const series = priceHistories.value[symbol]
+    updateCandleSeries(series, { price: (bid + ask) / 2, volume: volume || 0, timestamp })
+    Object.keys(CANDLE_TIMEFRAMES).forEach(tf => {
+      lastCandleTimestamps.value[symbol][tf] = series[tf][series[tf].length - 1]?.timestamp ?? null
     })
   }
   
-  const aggregateTimeframes = (symbol) => {
-    // This function is now replaced by updateCandleData for real-time updates
-    // Keeping for backward compatibility but functionality moved to updateCandleData
-  }
-  
+  // Rebuilds the stored higher timeframes from 1m history, e.g. after a backfill
   const generateHigherTimeframes = (symbol) => {
     const oneMinData = priceHistories.value[symbol]?.['1m'] || []
     if (oneMinData.length === 0) return
     
-    const timeframes = {
-      '5m': 300,
-      '15m': 900,
-      '1h': 3600,
-      '4h': 14400,
-      '1d': 86400
-    }
-    
-    Object.entries(timeframes).forEach(([tf, seconds]) => {
-      const aggregated = []
-      
-      // Group candles by time boundaries
-      const groups = {}
-      oneMinData.forEach(candle => {
-        const boundary = Math.floor(candle.timestamp / seconds) * seconds
-        if (!groups[boundary]) {
-          groups[boundary] = []
-        }
-        groups[boundary].push(candle)
-      })
-      
-      // Create aggregated candles
-      Object.entries(groups).forEach(([timestamp, candles]) => {
-        if (candles.length > 0) {
-          const aggregatedCandle = {
-            timestamp: parseInt(timestamp),
-            open: candles[0].open,
-            high: Math.max(...candles.map(c => c.high)),
-            low: Math.min(...candles.map(c => c.low)),
-            close: candles[candles.length - 1].close,
-            volume: candles.reduce((sum, c) => sum + (c.volume || 0), 0)
-          }
-          aggregated.push(aggregatedCandle)
-        }
-      })
-      
-      // Sort by timestamp and store
-      if (!priceHistories.value[symbol]) {
-        priceHistories.value[symbol] = { '1m': [], '5m': [], '15m': [], '1h': [], '4h': [], '1d': [] }
-      }
-      priceHistories.value[symbol][tf] = aggregated.sort((a, b) => a.timestamp - b.timestamp)
+    Object.entries(CANDLE_TIMEFRAMES).forEach(([tf, seconds]) => {
+      if (tf === '1m') return
+      priceHistories.value[symbol][tf] = aggregateCandles(oneMinData, seconds).slice(-CANDLE_LIMITS[tf])
     })
   }
   
@@ -435,6 +1986,9 @@ export const useMarketStore = defineStore('market', () => {
       marketEngineStore.initializeMarket()
     }
     
+    // Pick up accounts kept by an earlier session, if storage is durable
+    restoreAccounts()
+    
     // Sync user account with market engine user participant
     const userParticipant = marketEngineStore.getUserParticipant()
     if (userParticipant) {
@@ -444,6 +1998,31 @@ export const useMarketStore = defineStore('market', () => {
       updateAccountInfo()
     }
     
+    // Open the ledger with the starting balance
+    if (accountEvents.value.length === 0) {
+      recordAccountEvent(AccountEventType.RESET, {
+        balance: account.value.balance,
+        leverage: account.value.leverage
+      })
+    }
+    
+    // Register the default account so it can be switched away from
+    if (!accounts.value.has(activeAccountId.value)) {
+      accounts.value.set(activeAccountId.value, {
+        id: activeAccountId.value,
+        name: 'Main Account',
+        brokerId: brokerStore.selectedBroker?.id || null,
+        currency: 'USD',
+        account: { ...account.value },
+        positions: positions.value,
+        pendingOrders: pendingOrders.value,
+        events: accountEvents.value,
+        riskControls: riskControls.value,
+        challenge: challenge.value
+      })
+    }
+    storeAccountIndex()
+    
     // Initialize price data structures
     initializePriceData()
     
@@ -454,48 +2033,43 @@ export const useMarketStore = defineStore('market', () => {
     startRealTimeUpdates()
   }
   
-  const generateInitialHistory = () => {
-    marketEngineStore.config.symbols.forEach(symbol => {
-      const basePrice = marketEngineStore.config.basePrices[symbol] || 1.0
-      const history = []
-      let currentPrice = basePrice
-      
-      // Generate 100 historical 1-minute candles
-      const now = Date.now()
-      const minuteMs = 60 * 1000
-      
-      for (let i = 0; i < 100; i++) {
-        const candleTime = now - (100 - i) * minuteMs
-        const alignedTime = Math.floor(candleTime / minuteMs) * minuteMs
-        const timestamp = Math.floor(alignedTime / 1000)
-        
-        // Create realistic price movement
-        const variation = (Math.random() - 0.5) * 0.0010
-        const open = currentPrice
-        const volatility = symbol === 'USDJPY' ? 0.05 : 0.0005
-        
-        const high = open + Math.random() * volatility
-        const low = open - Math.random() * volatility
-        const close = open + variation
-        
-        currentPrice = close
-        
-        const candle = {
-          timestamp: timestamp,
-          open: Number(open.toFixed(symbol === 'USDJPY' ? 3 : 5)),
-          high: Number(Math.max(open, high, close).toFixed(symbol === 'USDJPY' ? 3 : 5)),
-          low: Number(Math.min(open, low, close).toFixed(symbol === 'USDJPY' ? 3 : 5)),
-          close: Number(close.toFixed(symbol === 'USDJPY' ? 3 : 5)),
-          volume: Math.floor(Math.random() * 1000000) + 50000
-        }
-        
-        history.push(candle)
-      }
-      
+  // Bootstrap history comes from the symbol's configured generator (historyGenerators.js),
+  // anchored so the last candle closes at the current mid
+  const generateInitialHistory = (symbols = marketEngineStore.config.symbols) => {
+    symbols.forEach(symbol => {
+      const price = marketPrices.value.get(symbol)
+      const endPrice = price ? (price.bid + price.ask) / 2 : marketEngineStore.config.basePrices[symbol] || 1.0
+      const history = buildHistory(symbol, { endPrice, count: Math.min(historyBootstrapSettings.candles, CANDLE_LIMITS['1m']) })
+        .map(candle => ({
+          ...candle,
+          open: roundPrice(symbol, candle.open),
+          high: roundPrice(symbol, candle.high),
+          low: roundPrice(symbol, candle.low),
+          close: roundPrice(symbol, candle.close)
+        }))
+
       priceHistories.value[symbol]['1m'] = history
       generateHigherTimeframes(symbol)
     })
   }
+
+  // Sets a symbol's history generator (null restores the default) and regenerates its
+  // bootstrap history, e.g. { generator: 'Fractal', hurst: 0.7 } or an imported file as
+  // { generator: 'Imported', candles: [...] }
+  const setHistoryGenerator = (symbol, settings) => {
+    if (!priceHistories.value[symbol]) return { success: false, error: `Unknown symbol ${symbol}` }
+    const previous = historyBootstrapSettings.symbols[symbol]
+    if (settings) historyBootstrapSettings.symbols[symbol] = settings
+    else delete historyBootstrapSettings.symbols[symbol]
+    try {
+      generateInitialHistory([symbol])
+      return { success: true }
+    } catch (error) {
+      if (previous) historyBootstrapSettings.symbols[symbol] = previous
+      else delete historyBootstrapSettings.symbols[symbol]
+      return { success: false, error: error.message }
+    }
+  }
   
   // Watch for market engine changes
   watch(() => marketEngineStore.isRunning, (isRunning) => {
@@ -506,9 +2080,34 @@ export const useMarketStore = defineStore('market', () => {
     }
   })
   
+  // Last engine heartbeat, for stall detection and clock alignment
+  const lastHeartbeat = ref(null)
+  const unsubscribeHeartbeat = marketEngineStore.subscribe(event => {
+    if (event.type !== EngineEvent.HEARTBEAT) return
+    lastHeartbeat.value = { ...event, receivedAt: Date.now() }
+  })
+  
+  // Stalled once three heartbeat intervals pass in silence while the engine should be running
+  const getFeedHealth = () => {
+    const time = marketEngineStore.getServerTime()
+    const ageMs = lastHeartbeat.value ? Date.now() - lastHeartbeat.value.receivedAt : null
+    return {
+      lastHeartbeat: lastHeartbeat.value,
+      ageMs,
+      stalled: marketEngineStore.isRunning && (ageMs === null || ageMs > 3 * time.heartbeatIntervalMs),
+      simTime: time.simTime,
+      clockOffsetMs: time.simTime - Date.now()
+    }
+  }
+  
   // Cleanup on component unmount
   const cleanup = () => {
     stopRealTimeUpdates()
+    unsubscribeHeartbeat()
+    if (unsubscribeConditionals) {
+      unsubscribeConditionals()
+      unsubscribeConditionals = null
+    }
   }
   
   return {
@@ -519,33 +2118,108 @@ export const useMarketStore = defineStore('market', () => {
     account,
     positions,
     pendingOrders,
+    accountEvents,
+    riskControls,
+    challenge,
+    challengePresets,
+    marginMode,
+    accounts,
+    activeAccountId,
+    accountTemplates,
+    ExposureLimitAction,
+    accountOpeningLimits,
     priceHistories,
     lastCandleTimestamps,
+    selectedPool,
+    settlementClock,
+    riskReports,
+    dailyStatements,
+    journalImports,
+    lastHeartbeat,
     
     // Computed
     currentPrice,
     priceHistory,
     currentSpread,
+    brokerQuote,
     orderbook,
+    orderbookLadder,
     marketStats,
+    currencyStrength,
+    retailSentiment,
     brokers,
+    brokerDirectory,
+    symbolMetadata,
+    getSymbolMetadata,
     selectedBroker,
     accountMarginLevel,
+    accountList,
+    consolidatedAccount,
     
     // Actions
     updateMarketData,
     updateCandleData,
     placeTrade,
     updateAccountInfo,
+    recordAccountEvent,
+    getAccountLedger,
+    getPositionHistory,
+    getFilledOrders,
+    listCandles,
+    getPositionsReport,
+    getUnsettledCashFlows,
+    getTradingCosts,
+    getMonthlyVolume,
+    getQuote,
+    getLiquidityMap,
+    getStrategyReport,
+    getJournalReport,
+    getFeedHealth,
+    importJournal,
+    getJournalImport,
+    placePackage,
+    placeConditionalOrder,
+    cancelConditionalOrder,
+    getPackages,
+    closePackage,
+    getRiskReport,
+    getDailyStatements,
+    getLiquidations,
+    publishRiskReports,
+    subscribe,
+    getSettlementLedger,
+    rollSettlementDay,
+    rebuildAccountFromEvents,
+    setRiskLimits,
+    setExposureLimits,
+    setAdminExposureLimits,
+    getExposureUtilization,
+    triggerKillSwitch,
+    cancelAllOrders,
+    flattenAccount,
+    resetKillSwitch,
+    calculatePortfolioMargin,
+    setMarginMode,
     setTimeframe,
     setSelectedBroker,
     setSelectedSymbol,
     setChartType,
     setAccountBalance,
+    resetAccount,
+    startChallenge,
+    getChallengeCompliance,
+    createAccount,
+    setAccountOpeningLimits,
+    validateAccountOpening,
+    switchAccount,
+    deleteAccount,
+    getStorageInfo,
+    subscribeMarketData,
     addPosition,
     closePosition,
     updatePositionPrices,
     generateHigherTimeframes,
+    setHistoryGenerator,
     initializeMarketData,
     startRealTimeUpdates,
     stopRealTimeUpdates,
@@ -553,6 +2227,7 @@ export const useMarketStore = defineStore('market', () => {
     
     // Access to stores
     marketEngineStore,
-    brokerStore
+    brokerStore,
+    executionVenueStore
   }
 })
\ No newline at end of file
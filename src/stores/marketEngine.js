@@ -1,5 +1,20 @@
 import { ref, reactive, computed } from 'vue'
 import { defineStore } from 'pinia'
+import { getPipSize, getPriceDecimals, toReportUnits } from './instruments.js'
+import { MarketDataTier, isMarketDataTier, getClientDataTier, applyDataTier as filterForDataTier } from './dataTiers.js'
+import { createClientNetwork, createRedundantFeeds } from './networkSimulation.js'
+import { conflateTapeEntries, createTapeConflator } from './tape.js'
+import {
+  PriceProcessModel,
+  createPriceProcess,
+  stepPriceProcess,
+  setProcessVolatility,
+  annualizeVolatility
+} from './priceProcess.js'
+import { createLobsterRecorder } from './lobsterExport.js'
+import { AuctionType, computeUncross, allocateUncross } from './auction.js'
+import { queryList } from './listQuery.js'
+import { StorageMode } from './storage.js'
 
 // Order Side Enum
 export const OrderSide = {
@@ -12,7 +27,11 @@ export const OrderType = {
   MARKET: 'Market',
   LIMIT: 'Limit',
   STOP: 'Stop',
-  STOP_LIMIT: 'StopLimit'
+  STOP_LIMIT: 'StopLimit',
+  // Held until the symbol's next fix: MOC joins the fix auction as a market order, TAS
+  // trades against opposite TAS orders at the fixing price without affecting it
+  MOC: 'MarketOnClose',
+  TAS: 'TradeAtSettlement'
 }
 
 // Participant Types
@@ -37,6 +56,14 @@ export const TradingStrategy = {
   MARKET_MAKING: 'MarketMaking'
 }
 
+// Retail Personas (behavioural biases of individually simulated retail agents)
+export const RetailPersona = {
+  BREAKOUT_CHASER: 'BreakoutChaser', // buys new highs, sells new lows
+  BAG_HOLDER: 'BagHolder', // averages down losers instead of stopping out
+  PROFIT_TAKER: 'ProfitTaker', // banks small winners early, lets losers run
+  BALANCED: 'Balanced' // symmetric take profit and stop, no entry bias
+}
+
 // Broker Types
 export const BrokerType = {
   DIRECT_ACCESS: 'DirectAccess',
@@ -54,8 +81,277 @@ export const ExecutionModel = {
   EXCHANGE_EXECUTION: 'ExchangeExecution'
 }
 
+// Engine Commands (processed strictly in dispatch order)
+export const EngineCommand = {
+  PLACE_ORDER: 'PlaceOrder',
+  PLACE_BATCH: 'PlaceBatch',
+  CANCEL_ORDER: 'CancelOrder',
+  CANCEL_ALL: 'CancelAll',
+  MASS_QUOTE: 'MassQuote',
+  PLACE_CONDITIONAL: 'PlaceConditional',
+  CANCEL_CONDITIONAL: 'CancelConditional',
+  TICK: 'Tick',
+  SNAPSHOT: 'Snapshot',
+  START_AUCTION: 'StartAuction',
+  UNCROSS_AUCTION: 'UncrossAuction'
+}
+
+// Engine Events (broadcast to all subscribers)
+export const EngineEvent = {
+  ORDER_ACCEPTED: 'OrderAccepted',
+  ORDER_CANCELLED: 'OrderCancelled',
+  TRADE_EXECUTED: 'TradeExecuted',
+  TICKED: 'Ticked',
+  SNAPSHOT_TAKEN: 'SnapshotTaken',
+  QUEUE_POSITION_CHANGED: 'QueuePositionChanged',
+  CONDITIONAL_TRIGGERED: 'ConditionalTriggered',
+  CURRENCY_STRENGTH: 'CurrencyStrength',
+  HEARTBEAT: 'Heartbeat',
+  RETAIL_SENTIMENT: 'RetailSentiment',
+  EXECUTION_REPORT: 'ExecutionReport',
+  AUCTION_STARTED: 'AuctionStarted',
+  AUCTION_INDICATIVE: 'AuctionIndicative',
+  AUCTION_UNCROSSED: 'AuctionUncrossed',
+  BOOK_TICKER: 'BookTicker',
+  TAPE_PRINT: 'TapePrint',
+  SUBSCRIPTION_SNAPSHOT: 'SubscriptionSnapshot'
+}
+
+// Still exported from here for code that imports them alongside the engine
+export { MarketDataTier, conflateTapeEntries }
+
+// Conditional order trigger: `price source` of `symbol` crosses `operator` `price`
+export const ConditionOperator = {
+  ABOVE: 'Above',
+  BELOW: 'Below'
+}
+
+export const PriceSource = {
+  LAST: 'Last',
+  BID: 'Bid',
+  ASK: 'Ask',
+  MID: 'Mid'
+}
+
+// Conditional Order Status
+export const ConditionalStatus = {
+  PENDING: 'Pending',
+  TRIGGERED: 'Triggered',
+  CANCELLED: 'Cancelled',
+  EXPIRED: 'Expired'
+}
+
+// Lot Accounting Methods
+export const LotMethod = {
+  FIFO: 'FIFO',
+  LIFO: 'LIFO'
+}
+
+// Liquidity pool every symbol has; its book is the one in `symbols`
+export const PRIMARY_POOL = 'primary'
+
+// Trade Tape Privacy Levels
+export const TapePrivacy = {
+  FULL: 'Full', // raw participant ids
+  TYPE_TAGS: 'TypeTags', // aggressor side plus participant type tags
+  ANONYMOUS: 'Anonymous' // aggressor side only
+}
+
+// Market Regimes (hidden states of the price process)
+export const MarketRegime = {
+  TREND_UP: 'TrendUp',
+  TREND_DOWN: 'TrendDown',
+  RANGE: 'Range',
+  VOLATILE: 'Volatile'
+}
+
+// Observable volatility state, from realized ATR against its long-run baseline
+export const VolatilityLevel = {
+  QUIET: 'Quiet',
+  NORMAL: 'Normal',
+  ELEVATED: 'Elevated',
+  EXTREME: 'Extreme'
+}
+
+// Balance Distributions
+export const BalanceDistribution = {
+  UNIFORM: 'uniform',
+  LOG_UNIFORM: 'logUniform',
+  LOGNORMAL: 'lognormal'
+}
+
+// Standard normal sample (Box-Muller)
+const sampleNormal = () => {
+  const u = 1 - Math.random()
+  const v = Math.random()
+  return Math.sqrt(-2 * Math.log(u)) * Math.cos(2 * Math.PI * v)
+}
+
+const sampleBalance = (range) => {
+  switch (range.distribution) {
+    case BalanceDistribution.LOG_UNIFORM: {
+      const logMin = Math.log(range.min)
+      const logMax = Math.log(range.max)
+      return Math.exp(logMin + Math.random() * (logMax - logMin))
+    }
+    case BalanceDistribution.LOGNORMAL: {
+      // Centered on the geometric midpoint, clamped to the configured range
+      const median = range.median || Math.sqrt(range.min * range.max)
+      const sigma = range.sigma ?? 1
+      const value = median * Math.exp(sigma * sampleNormal())
+      return Math.min(range.max, Math.max(range.min, value))
+    }
+    default:
+      return range.min + Math.random() * (range.max - range.min)
+  }
+}
+
+// Order Size Distributions
+export const OrderSizeDistribution = {
+  UNIFORM: 'uniform',
+  LOGNORMAL: 'lognormal',
+  POWER_LAW: 'powerLaw' // Pareto tail from min with exponent alpha, truncated at max
+}
+
+// spec: { distribution, min, max, median, sigma, alpha, roundTo }. Sizes are rounded to
+// roundTo units (never below one increment), the way real tickets cluster on round amounts.
+const sampleOrderSize = (spec) => {
+  let size
+  switch (spec.distribution) {
+    case OrderSizeDistribution.LOGNORMAL: {
+      const median = spec.median || Math.sqrt(spec.min * spec.max)
+      size = median * Math.exp((spec.sigma ?? 1) * sampleNormal())
+      break
+    }
+    case OrderSizeDistribution.POWER_LAW: {
+      // Inverse CDF of a Pareto truncated to [min, max]
+      const alpha = spec.alpha ?? 1.5
+      const tail = Math.pow(spec.min / spec.max, alpha)
+      size = spec.min * Math.pow(1 - Math.random() * (1 - tail), -1 / alpha)
+      break
+    }
+    default:
+      size = spec.min + Math.random() * (spec.max - spec.min)
+  }
+  size = Math.min(spec.max, Math.max(spec.min, size))
+  return spec.roundTo > 0 ? Math.max(spec.roundTo, Math.round(size / spec.roundTo) * spec.roundTo) : size
+}
+
+const pickWeighted = (weights) => {
+  const entries = Object.entries(weights || {}).filter(([, weight]) => weight > 0)
+  const total = entries.reduce((sum, [, weight]) => sum + weight, 0)
+  if (total <= 0) return null
+
+  let roll = Math.random() * total
+  for (const [key, weight] of entries) {
+    roll -= weight
+    if (roll <= 0) return key
+  }
+  return entries[entries.length - 1][0]
+}
+
+// Level-by-level differences between two OrderBook snapshots
+export const diffSnapshots = (before, after) => {
+  const diffSide = (a, b) => {
+    const prev = new Map(a.map(([price, volume]) => [price, volume]))
+    const next = new Map(b.map(([price, volume]) => [price, volume]))
+    const changes = []
+    for (const [price, volume] of next) {
+      const old = prev.get(price) ?? 0
+      if (old !== volume) changes.push({ price, before: old, after: volume })
+    }
+    for (const [price, volume] of prev) {
+      if (!next.has(price)) changes.push({ price, before: volume, after: 0 })
+    }
+    return changes.sort((x, y) => x.price - y.price)
+  }
+
+  return {
+    bids: diffSide(before.bids, after.bids),
+    asks: diffSide(before.asks, after.asks)
+  }
+}
+
+// Poisson sample, with a normal approximation for large rates
+const samplePoisson = (lambda) => {
+  if (lambda <= 0) return 0
+  if (lambda > 30) {
+    return Math.max(0, Math.round(lambda + Math.sqrt(lambda) * sampleNormal()))
+  }
+
+  const limit = Math.exp(-lambda)
+  let k = 0
+  let p = 1
+  do {
+    k += 1
+    p *= Math.random()
+  } while (p > limit)
+  return k - 1
+}
+
+export const validatePopulationConfig = (config) => {
+  const errors = []
+  const strategies = Object.values(TradingStrategy)
+  const distributions = Object.values(BalanceDistribution)
+  const sizeDistributions = Object.values(OrderSizeDistribution)
+
+  Object.values(ParticipantType).forEach(type => {
+    const count = config.participantCounts?.[type]
+    if (!Number.isInteger(count) || count < 0) {
+      errors.push(`${type}: count must be a non-negative integer`)
+    }
+
+    const range = config.balanceRanges?.[type]
+    if (!range || !(range.min > 0) || !(range.max >= range.min)) {
+      errors.push(`${type}: balance range must satisfy 0 < min <= max`)
+    } else if (range.distribution && !distributions.includes(range.distribution)) {
+      errors.push(`${type}: unknown balance distribution ${range.distribution}`)
+    }
+
+    const sizes = config.orderSizes?.[type]
+    if (sizes) {
+      if (!(sizes.min > 0) || !(sizes.max >= sizes.min)) {
+        errors.push(`${type}: order size range must satisfy 0 < min <= max`)
+      }
+      if (!sizeDistributions.includes(sizes.distribution)) {
+        errors.push(`${type}: unknown order size distribution ${sizes.distribution}`)
+      }
+      if (sizes.distribution === OrderSizeDistribution.POWER_LAW && !(sizes.alpha > 0)) {
+        errors.push(`${type}: power law alpha must be positive`)
+      }
+    }
+
+    const mix = config.strategyMix?.[type]
+    if (mix) {
+      Object.entries(mix).forEach(([strategy, weight]) => {
+        if (!strategies.includes(strategy)) errors.push(`${type}: unknown strategy ${strategy}`)
+        if (!(weight >= 0)) errors.push(`${type}: strategy weight for ${strategy} must be >= 0`)
+      })
+    }
+
+    const rate = config.activityRates?.[type]
+    if (rate !== undefined && !(rate >= 0 && rate <= 10)) {
+      errors.push(`${type}: activity rate must be between 0 and 10`)
+    }
+  })
+
+  Object.entries(config.retailPersonas?.mix || {}).forEach(([persona, weight]) => {
+    if (!config.retailPersonas.profiles?.[persona]) errors.push(`Retail persona ${persona} has no profile`)
+    if (!(weight >= 0)) errors.push(`Retail persona weight for ${persona} must be >= 0`)
+  })
+
+  if (config.storage && !Object.values(StorageMode).includes(config.storage.mode)) {
+    errors.push(`Unknown storage mode ${config.storage.mode}`)
+  }
+  if (config.tapeConflation && !(config.tapeConflation.windowMs > 0)) {
+    errors.push('Tape conflation window must be positive')
+  }
+
+  return errors
+}
+
 // Order Class
-class Order {
+export class Order {
   constructor(data) {
     this.id = data.id || this.generateId()
     this.symbol = data.symbol
@@ -65,6 +361,8 @@ class Order {
     this.timestamp = data.timestamp || Date.now()
     this.participantId = data.participantId
     this.orderType = data.orderType || OrderType.MARKET
+    this.clientOrderId = data.clientOrderId || null
+    this.poolId = data.poolId || null
   }
 
   generateId() {
@@ -79,10 +377,13 @@ class Trade {
     this.symbol = data.symbol
     this.buyerId = data.buyerId
     this.sellerId = data.sellerId
+    this.buyOrderId = data.buyOrderId || null
+    this.sellOrderId = data.sellOrderId || null
     this.price = data.price
     this.volume = data.volume
     this.timestamp = Date.now()
     this.tradeType = data.tradeType || 'Market'
+    this.aggressorSide = data.aggressorSide || null
   }
 
   generateId() {
@@ -90,165 +391,240 @@ class Trade {
   }
 }
 
+// How an incoming order is shared among the resting orders at a price level
+export const MatchingAlgorithm = {
+  FIFO: 'FIFO', // time priority
+  PRO_RATA: 'ProRata', // in proportion to resting size, remainder by time priority
+  SIZE_PRIORITY: 'SizePriority' // largest resting order first, ties by time priority
+}
+
+// Why the book turned an order away, for onOrderRejected listeners
+export const BookRejectReason = {
+  INVALID_AMOUNT: 'InvalidAmount',
+  INVALID_PRICE: 'InvalidPrice',
+  NO_LIQUIDITY: 'NoLiquidity', // market order (or its remainder) found nothing to match
+  STOP_NOT_TRIGGERED: 'StopNotTriggered', // stops are not held; untriggered ones are dropped
+  WOULD_CROSS: 'WouldCross', // limit remainder that couldn't trade but would cross the book
+  ENGINE: 'Engine' // refused by the engine before reaching the book
+}
+
 // OrderBook Class
-class OrderBook {
+export class OrderBook {
   constructor(symbol) {
     this.symbol = symbol
     this.bids = new Map() // price -> orders array
     this.asks = new Map() // price -> orders array
     this.lastTradePrice = 1.0
     this.totalVolume = 0
+    // Optional hooks: matchLimit(incoming, resting, price) caps the volume two orders may
+    // trade (0 skips the resting order); onMatch(trade) runs as each fill is created
+    this.matchLimit = null
+    this.onMatch = null
+    // Embedder listeners, see addListener. Engine internals use the hooks above.
+    this.listeners = []
+    // Bumped on every change, so derived market data can be reused until the book moves
+    this.version = 0
+    // Resting orders by id (the same objects the levels hold), so cancels and queue
+    // lookups go straight to the order's level instead of scanning the book
+    this.orderIndex = new Map()
+    // How a price level is shared among its resting orders, see MatchingAlgorithm
+    this.matchingAlgorithm = MatchingAlgorithm.FIFO
+    this.allocationLot = 1 // pro-rata shares are rounded down to this size
+  }
+
+  // Registers an object with any of onTrade(trade, book), onLevelChange(change, book),
+  // onOrderRejected(order, reason, book), onOrderRested(order, book) and
+  // onOrderCancelled(order, book). A level change is { side, price, before, after } in
+  // resting volume; after is 0 when the level empties. onOrderRested reports the part of an
+  // order left on the book after it matched. Returns a function that removes it.
+  addListener(listener) {
+    this.listeners.push(listener)
+    return () => {
+      this.listeners = this.listeners.filter(entry => entry !== listener)
+    }
+  }
+
+  notifyListeners(method, ...args) {
+    for (const listener of this.listeners) {
+      if (typeof listener[method] !== 'function') continue
+      try {
+        listener[method](...args, this)
+      } catch (error) {
+        console.warn(`Order book listener ${method} failed:`, error)
+      }
+    }
+  }
+
+  rejectOrder(order, reason) {
+    if (this.listeners.length > 0) this.notifyListeners('onOrderRejected', order, reason)
+  }
+
+  // Level volumes are diffed around each change only while someone is listening
+  notifyChanges(before, trades) {
+    if (!before) return
+    ;(trades || []).forEach(trade => this.notifyListeners('onTrade', trade))
+    const diff = diffSnapshots(before, this.snapshot())
+    diff.bids.forEach(change => this.notifyListeners('onLevelChange', { side: OrderSide.BUY, ...change }))
+    diff.asks.forEach(change => this.notifyListeners('onLevelChange', { side: OrderSide.SELL, ...change }))
   }
 
   addOrder(order) {
-    const trades = []
-    
+    if (!(order.amount > 0) || !Number.isFinite(order.amount)) {
+      this.rejectOrder(order, BookRejectReason.INVALID_AMOUNT)
+      return null
+    }
+    if (order.orderType !== OrderType.MARKET && !(order.price > 0)) {
+      this.rejectOrder(order, BookRejectReason.INVALID_PRICE)
+      return null
+    }
+
+    const before = this.listeners.length > 0 ? this.snapshot() : null
+    let trades
     switch (order.orderType) {
       case OrderType.MARKET:
-        return this.processMarketOrder(order)
+        trades = this.processMarketOrder(order)
+        break
       case OrderType.LIMIT:
-        return this.processLimitOrder(order)
+        trades = this.processLimitOrder(order)
+        break
       case OrderType.STOP:
-        return this.processStopOrder(order)
+        trades = this.processStopOrder(order)
+        break
       case OrderType.STOP_LIMIT:
-        return this.processStopLimitOrder(order)
+        trades = this.processStopLimitOrder(order)
+        break
       default:
-        return this.processMarketOrder(order)
+        trades = this.processMarketOrder(order)
+    }
+
+    this.version += 1
+    if (before) {
+      this.notifyChanges(before, trades)
+      const rested = this.orderIndex.get(order.id)
+      if (rested) this.notifyListeners('onOrderRested', rested)
+      const filled = (trades || []).reduce((sum, trade) => sum + trade.volume, 0)
+      const triggered = order.side === OrderSide.BUY ? this.lastTradePrice >= order.price : this.lastTradePrice <= order.price
+      if ((order.orderType === OrderType.STOP || order.orderType === OrderType.STOP_LIMIT) && !trades && !triggered) {
+        this.rejectOrder(order, BookRejectReason.STOP_NOT_TRIGGERED)
+      } else if (order.orderType !== OrderType.LIMIT && order.orderType !== OrderType.STOP_LIMIT && filled < order.amount) {
+        this.rejectOrder({ ...order, amount: order.amount - filled }, BookRejectReason.NO_LIQUIDITY)
+      }
     }
+    return trades
   }
 
   processMarketOrder(order) {
+    return this.matchOrder(order)
+  }
+
+  // Takes liquidity for `order` level by level. With a limitPrice it stops at the first
+  // level beyond the limit, so it never trades through it.
+  matchOrder(order, limitPrice = null) {
     const trades = []
     let remainingAmount = order.amount
+    const isBuy = order.side === OrderSide.BUY
 
-    if (order.side === OrderSide.BUY) {
-      // Match against asks (sell orders)
-      const sortedAsks = Array.from(this.asks.entries()).sort((a, b) => a[0] - b[0])
-      
-      for (const [price, orders] of sortedAsks) {
+    // Buys match against asks from the lowest price up, sells against bids from the highest
+    const levels = isBuy
+      ? Array.from(this.asks.entries()).sort((a, b) => a[0] - b[0])
+      : Array.from(this.bids.entries()).sort((a, b) => b[0] - a[0])
+
+    for (const [price, orders] of levels) {
+      if (remainingAmount <= 0) break
+      if (limitPrice !== null && (isBuy ? price > limitPrice : price < limitPrice)) break
+
+      for (const [resting, allotted] of this.allocateLevel(orders, remainingAmount)) {
         if (remainingAmount <= 0) break
-        
-        for (let i = orders.length - 1; i >= 0; i--) {
-          if (remainingAmount <= 0) break
-          
-          const askOrder = orders[i]
-          const tradeAmount = Math.min(remainingAmount, askOrder.amount)
-          
-          const trade = new Trade({
-            symbol: this.symbol,
-            buyerId: order.participantId,
-            sellerId: askOrder.participantId,
-            price: price,
-            volume: tradeAmount,
-            tradeType: 'Market'
-          })
-          
-          trades.push(trade)
-          remainingAmount -= tradeAmount
-          askOrder.amount -= tradeAmount
-          this.lastTradePrice = price
-          this.totalVolume += tradeAmount
-          
-          if (askOrder.amount <= 0) {
-            orders.splice(i, 1)
-          }
-        }
-        
-        if (orders.length === 0) {
-          this.asks.delete(price)
+        if (resting.amount <= 0) continue
+
+        const allowed = this.matchLimit ? this.matchLimit(order, resting, price) : Infinity
+        if (!(allowed > 0)) continue
+        const tradeAmount = Math.min(remainingAmount, resting.amount, allowed, allotted)
+        if (!(tradeAmount > 0)) continue
+
+        const trade = new Trade({
+          symbol: this.symbol,
+          buyerId: isBuy ? order.participantId : resting.participantId,
+          sellerId: isBuy ? resting.participantId : order.participantId,
+          buyOrderId: isBuy ? order.id : resting.id,
+          sellOrderId: isBuy ? resting.id : order.id,
+          price: price,
+          volume: tradeAmount,
+          tradeType: 'Market',
+          aggressorSide: order.side
+        })
+
+        trades.push(trade)
+        if (this.onMatch) this.onMatch(trade)
+        remainingAmount -= tradeAmount
+        resting.amount -= tradeAmount
+        this.lastTradePrice = price
+        this.totalVolume += tradeAmount
+
+        if (resting.amount <= 0) {
+          orders.splice(orders.indexOf(resting), 1)
+          this.orderIndex.delete(resting.id)
         }
       }
-    } else {
-      // Match against bids (buy orders)
-      const sortedBids = Array.from(this.bids.entries()).sort((a, b) => b[0] - a[0])
-      
-      for (const [price, orders] of sortedBids) {
-        if (remainingAmount <= 0) break
-        
-        for (let i = orders.length - 1; i >= 0; i--) {
-          if (remainingAmount <= 0) break
-          
-          const bidOrder = orders[i]
-          const tradeAmount = Math.min(remainingAmount, bidOrder.amount)
-          
-          const trade = new Trade({
-            symbol: this.symbol,
-            buyerId: bidOrder.participantId,
-            sellerId: order.participantId,
-            price: price,
-            volume: tradeAmount,
-            tradeType: 'Market'
-          })
-          
-          trades.push(trade)
-          remainingAmount -= tradeAmount
-          bidOrder.amount -= tradeAmount
-          this.lastTradePrice = price
-          this.totalVolume += tradeAmount
-          
-          if (bidOrder.amount <= 0) {
-            orders.splice(i, 1)
-          }
-        }
-        
-        if (orders.length === 0) {
-          this.bids.delete(price)
-        }
+
+      if (orders.length === 0) {
+        (isBuy ? this.asks : this.bids).delete(price)
       }
     }
 
     return trades.length > 0 ? trades : null
   }
 
+  // [resting order, most it may fill] pairs in the sequence a level is offered to an
+  // incoming order for `amount`. Pro-rata gives each order its share of the amount,
+  // rounded down to allocationLot, then offers whatever is left in time priority.
+  allocateLevel(orders, amount) {
+    const queue = this.getLevelPriority(orders)
+    const inFull = queue.map(order => [order, Infinity])
+    if (this.matchingAlgorithm !== MatchingAlgorithm.PRO_RATA) return inFull
+
+    const total = queue.reduce((sum, order) => sum + order.amount, 0)
+    if (amount >= total) return inFull
+    const shares = queue
+      .map(order => [order, Math.floor(amount * order.amount / total / this.allocationLot) * this.allocationLot])
+      .filter(([, share]) => share > 0)
+    return [...shares, ...inFull]
+  }
+
   processLimitOrder(order) {
-    const trades = []
-    let remainingOrder = { ...order }
-
-    // First try to match immediately
-    if (order.side === OrderSide.BUY) {
-      const bestAsk = this.getBestAsk()
-      if (bestAsk && order.price >= bestAsk) {
-        const marketOrder = new Order({
-          ...remainingOrder,
-          orderType: OrderType.MARKET
-        })
-        const marketTrades = this.processMarketOrder(marketOrder)
-        if (marketTrades) {
-          trades.push(...marketTrades)
-          const tradedVolume = marketTrades.reduce((sum, t) => sum + t.volume, 0)
-          remainingOrder.amount -= tradedVolume
-        }
-      }
-    } else {
-      const bestBid = this.getBestBid()
-      if (bestBid && order.price <= bestBid) {
-        const marketOrder = new Order({
-          ...remainingOrder,
-          orderType: OrderType.MARKET
-        })
-        const marketTrades = this.processMarketOrder(marketOrder)
-        if (marketTrades) {
-          trades.push(...marketTrades)
-          const tradedVolume = marketTrades.reduce((sum, t) => sum + t.volume, 0)
-          remainingOrder.amount -= tradedVolume
-        }
-      }
-    }
+    const remainingOrder = { ...order }
+    const trades = this.matchOrder(order, order.price) || []
+    remainingOrder.amount -= trades.reduce((sum, t) => sum + t.volume, 0)
 
-    // Add remaining amount to orderbook
+    // Rest the remainder unless it would still cross, which happens when the engine's
+    // matchLimit (credit) kept it from trading with the opposite touch
     if (remainingOrder.amount > 0) {
-      const bookSide = order.side === OrderSide.BUY ? this.bids : this.asks
-      const price = order.price
-      
-      if (!bookSide.has(price)) {
-        bookSide.set(price, [])
+      const touch = order.side === OrderSide.BUY ? this.getBestAsk() : this.getBestBid()
+      const crosses = touch !== null && (order.side === OrderSide.BUY ? order.price >= touch : order.price <= touch)
+      if (crosses) {
+        this.rejectOrder(remainingOrder, BookRejectReason.WOULD_CROSS)
+      } else {
+        const bookSide = order.side === OrderSide.BUY ? this.bids : this.asks
+        if (!bookSide.has(order.price)) {
+          bookSide.set(order.price, [])
+        }
+        bookSide.get(order.price).push(remainingOrder)
+        this.orderIndex.set(remainingOrder.id, remainingOrder)
       }
-      bookSide.get(price).push(remainingOrder)
     }
 
+    this.assertUncrossed(order)
     return trades.length > 0 ? trades : null
   }
 
+  assertUncrossed(order) {
+    const bestBid = this.getBestBid()
+    const bestAsk = this.getBestAsk()
+    if (bestBid !== null && bestAsk !== null && bestBid >= bestAsk) {
+      throw new Error(`${this.symbol} book crossed after order ${order.id}: bid ${bestBid} >= ask ${bestAsk}`)
+    }
+  }
+
   processStopOrder(order) {
     const shouldTrigger = order.side === OrderSide.BUY 
       ? this.lastTradePrice >= order.price
@@ -279,6 +655,77 @@ class OrderBook {
     return null
   }
 
+  // Where a resting order sits: { side, price, orders, index } or null
+  locateOrder(orderId) {
+    const resting = this.orderIndex.get(orderId)
+    if (resting) {
+      const side = resting.side === OrderSide.BUY ? this.bids : this.asks
+      const orders = side.get(resting.price)
+      const index = orders ? orders.indexOf(resting) : -1
+      if (index !== -1) return { side, price: resting.price, orders, index }
+    }
+    // Not indexed (e.g. levels edited directly): fall back to a scan
+    for (const side of [this.bids, this.asks]) {
+      for (const [price, orders] of side) {
+        const index = orders.findIndex(order => order.id === orderId)
+        if (index !== -1) return { side, price, orders, index }
+      }
+    }
+    return null
+  }
+
+  cancelOrder(orderId) {
+    const location = this.locateOrder(orderId)
+    if (!location) return null
+
+    const { side, price, orders, index } = location
+    const before = this.listeners.length > 0 ? this.snapshot() : null
+    const [cancelled] = orders.splice(index, 1)
+    this.orderIndex.delete(orderId)
+    if (orders.length === 0) {
+      side.delete(price)
+    }
+    this.version += 1
+    if (before) {
+      this.notifyChanges(before, null)
+      this.notifyListeners('onOrderCancelled', cancelled)
+    }
+    return cancelled
+  }
+
+  // Resting orders at one level in the order they will be matched. Levels hold orders in
+  // arrival order, oldest first. Pro-rata levels report time priority, which is how any
+  // remainder after the pro-rata shares is filled.
+  getLevelPriority(orders) {
+    const queue = orders.slice()
+    if (this.matchingAlgorithm === MatchingAlgorithm.SIZE_PRIORITY) {
+      // Stable, so equal sizes keep time priority
+      queue.sort((a, b) => b.amount - a.amount)
+    }
+    return queue
+  }
+
+  getQueuePosition(orderId) {
+    const location = this.locateOrder(orderId)
+    if (!location) return null
+
+    const { price, orders } = location
+    const queue = this.getLevelPriority(orders)
+    const index = queue.findIndex(order => order.id === orderId)
+    const ahead = queue.slice(0, index)
+    return {
+      orderId,
+      symbol: this.symbol,
+      side: location.side === this.bids ? OrderSide.BUY : OrderSide.SELL,
+      price,
+      position: index + 1,
+      ordersAhead: index,
+      volumeAhead: ahead.reduce((sum, order) => sum + order.amount, 0),
+      levelVolume: orders.reduce((sum, order) => sum + order.amount, 0),
+      remaining: queue[index].amount
+    }
+  }
+
   getBestBid() {
     if (this.bids.size === 0) return null
     return Math.max(...this.bids.keys())
@@ -295,6 +742,12 @@ class OrderBook {
     return (bid && ask) ? ask - bid : null
   }
 
+  getMidPrice() {
+    const bid = this.getBestBid()
+    const ask = this.getBestAsk()
+    return (bid && ask) ? (bid + ask) / 2 : (bid || ask)
+  }
+
   getBids(depth = 10) {
     const result = []
     const sortedBids = Array.from(this.bids.entries()).sort((a, b) => b[0] - a[0])
@@ -323,10 +776,129 @@ class OrderBook {
     return this.totalVolume
   }
 
+  snapshot() {
+    const levels = (side) => Array.from(side.entries())
+      .map(([price, orders]) => [price, orders.reduce((sum, order) => sum + order.amount, 0), orders.length])
+    return {
+      symbol: this.symbol,
+      bids: levels(this.bids).sort((a, b) => b[0] - a[0]),
+      asks: levels(this.asks).sort((a, b) => a[0] - b[0]),
+      lastTradePrice: this.lastTradePrice
+    }
+  }
+
+  // Full copy of resting orders, suitable for restoring an identical book
+  exportState() {
+    const copySide = (side) => Array.from(side.entries())
+      .map(([price, orders]) => [price, orders.map(order => ({ ...order }))])
+    return {
+      symbol: this.symbol,
+      bids: copySide(this.bids),
+      asks: copySide(this.asks),
+      lastTradePrice: this.lastTradePrice,
+      totalVolume: this.totalVolume,
+      matchingAlgorithm: this.matchingAlgorithm,
+      allocationLot: this.allocationLot
+    }
+  }
+
+  static fromState(state) {
+    const book = new OrderBook(state.symbol)
+    state.bids.forEach(([price, orders]) => book.bids.set(price, orders.map(order => ({ ...order }))))
+    state.asks.forEach(([price, orders]) => book.asks.set(price, orders.map(order => ({ ...order }))))
+    for (const side of [book.bids, book.asks]) {
+      for (const orders of side.values()) orders.forEach(order => book.orderIndex.set(order.id, order))
+    }
+    book.lastTradePrice = state.lastTradePrice
+    book.totalVolume = state.totalVolume
+    book.matchingAlgorithm = state.matchingAlgorithm ?? MatchingAlgorithm.FIFO
+    book.allocationLot = state.allocationLot ?? 1
+    return book
+  }
+
+  checkIntegrity() {
+    const violations = []
+    const bestBid = this.getBestBid()
+    const bestAsk = this.getBestAsk()
+
+    if (bestBid !== null && bestAsk !== null && bestBid >= bestAsk) {
+      violations.push(`crossed book: best bid ${bestBid} >= best ask ${bestAsk}`)
+    }
+
+    const checkSide = (side, name, expectedSide) => {
+      for (const [price, orders] of side) {
+        if (!Number.isFinite(price) || price <= 0) {
+          violations.push(`${name} level has invalid price ${price}`)
+        }
+        if (orders.length === 0) {
+          violations.push(`${name} level ${price} is empty but still indexed`)
+        }
+        for (const order of orders) {
+          if (!(order.amount > 0)) {
+            violations.push(`${name} order ${order.id} at ${price} has non-positive amount ${order.amount}`)
+          }
+          if (order.price !== price) {
+            violations.push(`${name} order ${order.id} price ${order.price} filed under level ${price}`)
+          }
+          if (order.side !== expectedSide) {
+            violations.push(`${name} order ${order.id} has side ${order.side}`)
+          }
+          if (order.symbol !== this.symbol) {
+            violations.push(`${name} order ${order.id} belongs to ${order.symbol}`)
+          }
+        }
+      }
+    }
+    checkSide(this.bids, 'bid', OrderSide.BUY)
+    checkSide(this.asks, 'ask', OrderSide.SELL)
+
+    for (const [id, order] of this.orderIndex) {
+      const level = (order.side === OrderSide.BUY ? this.bids : this.asks).get(order.price)
+      if (!level || !level.includes(order)) {
+        violations.push(`order index holds ${id}, which is not resting at ${order.price}`)
+      }
+    }
+
+    return violations
+  }
+
+  // Walks the opposite side of the book for a hypothetical order without matching it
+  simulateFill(side, amount) {
+    const levels = side === OrderSide.BUY
+      ? Array.from(this.asks.entries()).sort((a, b) => a[0] - b[0])
+      : Array.from(this.bids.entries()).sort((a, b) => b[0] - a[0])
+
+    let remaining = amount
+    let notional = 0
+    let levelsConsumed = 0
+    let worstPrice = null
+
+    for (const [price, orders] of levels) {
+      if (remaining <= 0) break
+      const levelVolume = orders.reduce((sum, order) => sum + order.amount, 0)
+      const take = Math.min(remaining, levelVolume)
+      notional += take * price
+      remaining -= take
+      levelsConsumed += 1
+      worstPrice = price
+    }
+
+    const filled = amount - remaining
+    return {
+      filled,
+      unfilled: remaining,
+      vwap: filled > 0 ? notional / filled : null,
+      worstPrice,
+      levelsConsumed
+    }
+  }
+
   clear() {
     this.bids.clear()
     this.asks.clear()
+    this.orderIndex.clear()
     this.totalVolume = 0
+    this.version += 1
   }
 }
 
@@ -360,7 +932,9 @@ class Position {
 class Participant {
   constructor(data) {
     this.id = data.id
-    this.name = data.name
+    this.no = data.no ?? null // compact numeric id, assigned by the engine
+    this.displayName = data.name ?? null
+    this.nameIndex = data.nameIndex ?? 0
     this.participantType = data.participantType
     this.balance = data.balance
     this.equity = data.balance
@@ -369,8 +943,23 @@ class Participant {
     this.positions = new Map()
     this.tradingStrategy = data.tradingStrategy || this.getDefaultStrategy()
     this.riskTolerance = data.riskTolerance || this.getDefaultRiskTolerance()
+    this.activityRate = data.activityRate ?? 1 // multiplier on trading probability
+    this.persona = data.persona || null // RetailPersona, retail agents only
     this.active = true
     this.lastTradeTime = 0
+    this.lots = new Map() // symbol -> open lots
+    this.realizedPnl = 0
+    this.ledger = [] // realized P&L per closed lot
+  }
+
+  // Agents are created by the thousand, so their display name is only formatted when read
+  get name() {
+    if (this.displayName === null) this.displayName = `${this.participantType} ${this.nameIndex + 1}`
+    return this.displayName
+  }
+
+  set name(value) {
+    this.displayName = value
   }
 
   getDefaultLeverage() {
@@ -425,6 +1014,96 @@ class Participant {
     return null
   }
 
+  applyFill(trade, side, lotMethod = LotMethod.FIFO) {
+    const lots = this.lots.get(trade.symbol) || []
+    let remaining = trade.volume
+    const realized = []
+
+    // Close opposite-side lots first, oldest (FIFO) or newest (LIFO) first
+    while (remaining > 0 && lots.length > 0 && lots[0].side !== side) {
+      const index = lotMethod === LotMethod.LIFO ? lots.length - 1 : 0
+      const lot = lots[index]
+      const closedVolume = Math.min(remaining, lot.volume)
+      const pnl = lot.side === OrderSide.BUY
+        ? (trade.price - lot.price) * closedVolume
+        : (lot.price - trade.price) * closedVolume
+
+      realized.push({
+        lotId: lot.id,
+        symbol: trade.symbol,
+        side: lot.side,
+        volume: closedVolume,
+        openPrice: lot.price,
+        closePrice: trade.price,
+        openTime: lot.timestamp,
+        closeTime: trade.timestamp,
+        tradeId: trade.id,
+        pnl
+      })
+
+      lot.volume -= closedVolume
+      remaining -= closedVolume
+      if (lot.volume <= 0) {
+        lots.splice(index, 1)
+      }
+    }
+
+    // Whatever is left opens a new lot
+    if (remaining > 0) {
+      lots.push({
+        id: trade.id,
+        side,
+        volume: remaining,
+        price: trade.price,
+        timestamp: trade.timestamp
+      })
+    }
+
+    if (lots.length > 0) {
+      this.lots.set(trade.symbol, lots)
+    } else {
+      this.lots.delete(trade.symbol)
+    }
+
+    for (const entry of realized) {
+      this.realizedPnl += entry.pnl
+      this.balance += entry.pnl
+      this.ledger.push(entry)
+    }
+    if (this.ledger.length > 1000) {
+      this.ledger = this.ledger.slice(-500)
+    }
+
+    this.syncPositionFromLots(trade.symbol, trade.price)
+    return realized
+  }
+
+  syncPositionFromLots(symbol, price) {
+    const lots = this.lots.get(symbol)
+    if (!lots || lots.length === 0) {
+      this.positions.delete(symbol)
+      this.updateEquity()
+      return
+    }
+
+    const volume = lots.reduce((sum, lot) => sum + lot.volume, 0)
+    const entryPrice = lots.reduce((sum, lot) => sum + lot.price * lot.volume, 0) / volume
+    const position = new Position({
+      symbol,
+      side: lots[0].side,
+      volume,
+      entryPrice,
+      currentPrice: price
+    })
+    position.updatePrice(price)
+    this.addPosition(position)
+  }
+
+  getUnrealizedPnl() {
+    return Array.from(this.positions.values())
+      .reduce((sum, pos) => sum + pos.unrealizedPnl, 0)
+  }
+
   updatePositionPrice(symbol, newPrice) {
     const position = this.positions.get(symbol)
     if (position) {
@@ -453,7 +1132,7 @@ class Participant {
     return positionSize * this.leverage
   }
 
-  shouldTrade() {
+  shouldTrade(activityScale = 1) {
     if (!this.active) return false
     
     // Prevent too frequent trading
@@ -461,7 +1140,7 @@ class Participant {
     const minInterval = this.getMinTradingInterval()
     if (timeSinceLastTrade < minInterval) return false
 
-    const probability = this.getTradingProbability()
+    const probability = this.getTradingProbability() * this.activityRate * activityScale
     return Math.random() < probability
   }
 
@@ -506,7 +1185,11 @@ class Participant {
     }
   }
 
-  getTypicalTradeSize() {
+  // With a size spec (see config.orderSizes) the size is drawn from it; otherwise from the
+  // type's fixed uniform range
+  getTypicalTradeSize(sizeSpec = null) {
+    if (sizeSpec) return sampleOrderSize(sizeSpec)
+
     const multiplier = 1 + (Math.random() - 0.5) * 0.5 // ±25% variation
     
     switch (this.participantType) {
@@ -535,18 +1218,51 @@ export const useMarketEngineStore = defineStore('marketEngine', () => {
   const participants = ref(new Map())
   const activeOrders = ref(new Map())
   const tradeHistory = ref([])
+  // Per-symbol copies of the history, trimmed the same way, so recent-trade queries
+  // don't filter the global list
+  const tradesBySymbol = new Map()
+  const venueStats = new Map() // symbol -> running microstructure counters, see getVenueStats
   const marketStats = ref({
     totalVolume: 0,
     totalTrades: 0,
     activeParticipants: 0,
     liquidityIndex: 0,
-    volatility: 0
+    volatility: 0,
+    aggregateOrders: 0
   })
 
+  const anchorPrices = ref(new Map()) // symbol -> external reference mid
+  const integrityViolations = ref([])
+  const depthHistory = ref(new Map()) // symbol -> recent { timestamp, bidDepth, askDepth } samples
+  const poolBooks = ref(new Map()) // symbol -> Map(poolId -> OrderBook) for non-primary pools
+  const marketConditions = ref(new Map()) // id -> temporary overlay on agent behaviour
+  const regimeStates = ref(new Map()) // symbol -> { regime, since, referencePrice, history }
+  const volatilityStates = ref(new Map()) // symbol -> { bars, atr, baselineAtr }
+  const conditionalOrders = ref(new Map()) // id -> conditional order, see handlePlaceConditional
+  const lastTradePrices = new Map() // symbol -> last trade price across pools
+  const priceProcessStates = ref(new Map()) // symbol -> { model, settings, state, steppedAt }
+  const priceTargets = ref(new Map()) // symbol -> drift/volatility target, see setPriceModel
+  const currencyStrength = ref({ baseline: new Map(), history: [], indices: [], updatedAt: null })
+  const population = ref({ counts: null, ramp: null }) // active agents per type and any ramp in progress
+  const retailSentiment = ref({ symbols: [], updatedAt: null })
+  // Sim time runs at simulationSpeed x wall time while ticks are processed
+  const simClock = ref({ simTime: null, wallTime: null, heartbeats: 0, lastHeartbeatAt: null })
   const isRunning = ref(false)
   const simulationSpeed = ref(1) // 1x speed
   const updateInterval = ref(null)
 
+  // Simulation loop timing and throttling metrics
+  const loopMetrics = ref({
+    ticks: 0,
+    lastDurationMs: 0,
+    averageDurationMs: 0,
+    overruns: 0,
+    totalOverrunMs: 0,
+    skippedCohorts: 0,
+    activityScale: 1,
+    lastTickAt: null
+  })
+
   // Market Configuration
   const config = reactive({
     participantCounts: {
@@ -558,12 +1274,74 @@ export const useMarketEngineStore = defineStore('marketEngine', () => {
       [ParticipantType.RETAIL_TRADER]: 10000
     },
     balanceRanges: {
-      [ParticipantType.BANK]: { min: 10000000, max: 1000000000 },
-      [ParticipantType.TRADER]: { min: 100000, max: 10000000 },
-      [ParticipantType.HEDGE_FUND]: { min: 50000000, max: 500000000 },
-      [ParticipantType.CORPORATION]: { min: 1000000, max: 100000000 },
-      [ParticipantType.GOVERNMENT]: { min: 100000000, max: 1000000000 },
-      [ParticipantType.RETAIL_TRADER]: { min: 1000, max: 100000 }
+      [ParticipantType.BANK]: { min: 10000000, max: 1000000000, distribution: BalanceDistribution.LOG_UNIFORM },
+      [ParticipantType.TRADER]: { min: 100000, max: 10000000, distribution: BalanceDistribution.LOGNORMAL },
+      [ParticipantType.HEDGE_FUND]: { min: 50000000, max: 500000000, distribution: BalanceDistribution.LOG_UNIFORM },
+      [ParticipantType.CORPORATION]: { min: 1000000, max: 100000000, distribution: BalanceDistribution.LOGNORMAL },
+      [ParticipantType.GOVERNMENT]: { min: 100000000, max: 1000000000, distribution: BalanceDistribution.UNIFORM },
+      [ParticipantType.RETAIL_TRADER]: { min: 1000, max: 100000, distribution: BalanceDistribution.LOGNORMAL }
+    },
+    // Quote management for market-making and HFT agents: each keeps a two-sided quote in
+    // one symbol and cancel-replaces it (mass quote) once it is maxQuoteAgeMs old or the
+    // mid has moved requotePips, as long as its orders stay within orderToTradeRatio
+    // times its fills. offsetPips is how far behind the touch it quotes. maxQuoters bounds
+    // the agents doing this per step.
+    quoteManagement: {
+      enabled: true,
+      maxQuoters: 40,
+      strategies: {
+        [TradingStrategy.MARKET_MAKING]: { orderToTradeRatio: 20, maxQuoteAgeMs: 2000, requotePips: 0.5, offsetPips: 0.5 },
+        [TradingStrategy.HIGH_FREQUENCY]: { orderToTradeRatio: 50, maxQuoteAgeMs: 300, requotePips: 0.2, offsetPips: 0 }
+      }
+    },
+    // Orders kept per participant for inspectParticipant
+    participantInspection: {
+      recentOrders: 20
+    },
+    // Order size per participant type (see OrderSizeDistribution). FX ticket sizes are
+    // heavy-tailed: interbank and official flow follows a power law above the market's
+    // standard clip, client flow is roughly lognormal, and both cluster on round amounts.
+    orderSizes: {
+      [ParticipantType.BANK]: { distribution: OrderSizeDistribution.POWER_LAW, min: 1000000, max: 50000000, alpha: 1.5, roundTo: 100000 },
+      [ParticipantType.TRADER]: { distribution: OrderSizeDistribution.LOGNORMAL, min: 1000, max: 1000000, median: 30000, sigma: 1, roundTo: 1000 },
+      [ParticipantType.HEDGE_FUND]: { distribution: OrderSizeDistribution.LOGNORMAL, min: 50000, max: 20000000, median: 500000, sigma: 1, roundTo: 10000 },
+      [ParticipantType.CORPORATION]: { distribution: OrderSizeDistribution.LOGNORMAL, min: 10000, max: 20000000, median: 200000, sigma: 1.2, roundTo: 10000 },
+      [ParticipantType.GOVERNMENT]: { distribution: OrderSizeDistribution.POWER_LAW, min: 1000000, max: 100000000, alpha: 1.6, roundTo: 1000000 },
+      [ParticipantType.RETAIL_TRADER]: { distribution: OrderSizeDistribution.LOGNORMAL, min: 1000, max: 500000, median: 5000, sigma: 1.1, roundTo: 1000 }
+    },
+    // Relative weights of strategies assigned within each participant type
+    strategyMix: {
+      [ParticipantType.BANK]: {
+        [TradingStrategy.MARKET_MAKING]: 0.7,
+        [TradingStrategy.ARBITRAGE]: 0.2,
+        [TradingStrategy.CONSERVATIVE]: 0.1
+      },
+      [ParticipantType.TRADER]: {
+        [TradingStrategy.HIGH_FREQUENCY]: 0.4,
+        [TradingStrategy.TREND_FOLLOWING]: 0.3,
+        [TradingStrategy.MEAN_REVERSION]: 0.3
+      },
+      [ParticipantType.HEDGE_FUND]: {
+        [TradingStrategy.AGGRESSIVE]: 0.4,
+        [TradingStrategy.TREND_FOLLOWING]: 0.3,
+        [TradingStrategy.ARBITRAGE]: 0.3
+      },
+      [ParticipantType.CORPORATION]: { [TradingStrategy.CONSERVATIVE]: 1 },
+      [ParticipantType.GOVERNMENT]: { [TradingStrategy.CONSERVATIVE]: 1 },
+      [ParticipantType.RETAIL_TRADER]: {
+        [TradingStrategy.MODERATE]: 0.5,
+        [TradingStrategy.TREND_FOLLOWING]: 0.3,
+        [TradingStrategy.AGGRESSIVE]: 0.2
+      }
+    },
+    // Multipliers on each type's base trading probability
+    activityRates: {
+      [ParticipantType.BANK]: 1,
+      [ParticipantType.TRADER]: 1,
+      [ParticipantType.HEDGE_FUND]: 1,
+      [ParticipantType.CORPORATION]: 0.5,
+      [ParticipantType.GOVERNMENT]: 0.2,
+      [ParticipantType.RETAIL_TRADER]: 1
     },
     symbols: ['EURUSD', 'GBPUSD', 'USDJPY', 'USDCHF', 'AUDUSD', 'USDCAD'],
     basePrices: {
@@ -574,152 +1352,2901 @@ export const useMarketEngineStore = defineStore('marketEngine', () => {
       'AUDUSD': 0.6450,
       'USDCAD': 1.3650
     },
+    // Pairwise return correlations between symbols
+    correlations: {
+      'EURUSD/GBPUSD': 0.85,
+      'EURUSD/USDJPY': -0.3,
+      'EURUSD/USDCHF': -0.9,
+      'EURUSD/AUDUSD': 0.7,
+      'EURUSD/USDCAD': -0.6,
+      'GBPUSD/USDJPY': -0.25,
+      'GBPUSD/USDCHF': -0.8,
+      'GBPUSD/AUDUSD': 0.65,
+      'GBPUSD/USDCAD': -0.55,
+      'USDJPY/USDCHF': 0.5,
+      'USDJPY/AUDUSD': -0.2,
+      'USDJPY/USDCAD': 0.3,
+      'USDCHF/AUDUSD': -0.65,
+      'USDCHF/USDCAD': 0.55,
+      'AUDUSD/USDCAD': -0.6
+    },
     updateFrequency: 100, // milliseconds
-    maxTradesPerUpdate: 1000
-  })
-
-  // Computed
-  const totalParticipants = computed(() => {
-    return Object.values(config.participantCounts).reduce((sum, count) => sum + count, 0)
-  })
-
-  const activeParticipantCount = computed(() => {
+    maxTradesPerUpdate: 1000,
+    simulatedBankLimit: 50, // banks simulated individually each tick
+    simulatedTraderLimit: 200, // traders simulated individually each tick
+    // Retail agents simulated individually with a persona, on top of simulatedTraderLimit.
+    // breakoutChase: chance of trading with a break of the recent range; averageDown:
+    // chance of adding to a loser; takeProfitPips/stopLossPips close the position (null
+    // stop: never cut). lookbackTrades sets the range.
+    retailPersonas: {
+      enabled: true,
+      simulatedLimit: 100,
+      lookbackTrades: 50,
+      mix: {
+        [RetailPersona.BREAKOUT_CHASER]: 0.3,
+        [RetailPersona.BAG_HOLDER]: 0.25,
+        [RetailPersona.PROFIT_TAKER]: 0.3,
+        [RetailPersona.BALANCED]: 0.15
+      },
+      profiles: {
+        [RetailPersona.BREAKOUT_CHASER]: { breakoutChase: 0.8, averageDown: 0.05, takeProfitPips: 30, stopLossPips: 15 },
+        [RetailPersona.BAG_HOLDER]: { breakoutChase: 0.2, averageDown: 0.6, takeProfitPips: 10, stopLossPips: null },
+        [RetailPersona.PROFIT_TAKER]: { breakoutChase: 0.3, averageDown: 0.2, takeProfitPips: 4, stopLossPips: 40 },
+        [RetailPersona.BALANCED]: { breakoutChase: 0, averageDown: 0, takeProfitPips: 20, stopLossPips: 20 }
+      }
+    },
+    // Statistical order flow for the population that isn't simulated individually
+    aggregateFlow: {
+      enabled: true,
+      flowScale: 0.01, // fraction of per-agent trading probability realized per tick
+      maxOrdersPerTick: 50
+    },
+    // Runtime population changes, see scalePopulation
+    populationScaling: {
+      rampSeconds: 30,
+      maxChangePerTick: 500 // agents (de)activated per tick, bounds the work of a large step
+    },
+    // Hour-of-day (UTC) multipliers; per-symbol curves in `symbols` override the defaults
+    seasonality: {
+      enabled: true,
+      volume: [
+        0.6, 0.7, 0.7, 0.6, 0.5, 0.45, 0.6, 1.0, 1.3, 1.2, 1.1, 1.0,
+        1.3, 1.5, 1.4, 1.3, 1.1, 0.9, 0.7, 0.6, 0.5, 0.45, 0.5, 0.55
+      ],
+      volatility: [
+        0.8, 0.85, 0.85, 0.8, 0.7, 0.65, 0.75, 1.1, 1.25, 1.15, 1.05, 1.0,
+        1.2, 1.35, 1.3, 1.2, 1.05, 0.95, 0.85, 0.8, 0.75, 0.7, 0.75, 0.8
+      ],
+      symbols: {}
+    },
+    selfCheck: false, // verify order book invariants after every order
+    adaptiveThrottling: true,
+    minActivityScale: 0.1, // lowest fraction of normal agent activity when throttled
+    lotMethod: LotMethod.FIFO,
+    maxBatchSize: 100, // orders accepted in one batch submission
+    // Independent ECN pools per symbol. Participants are spread across pools by id, banks
+    // quote in `bankPools` of them, and unrouted market orders go to the pool with the best touch.
+    liquidityPools: {
+      enabled: false,
+      pools: [
+        { id: PRIMARY_POOL, name: 'Primary ECN' },
+        { id: 'ecn_b', name: 'ECN B' },
+        { id: 'ecn_c', name: 'ECN C' }
+      ],
+      bankPools: 2
+    },
+    // Bilateral credit: two participants can trade up to the smaller of their types' credit
+    // lines (notional), unless an explicit pair limit is set
+    credit: {
+      enabled: false,
+      creditLines: {
+        [ParticipantType.BANK]: 500000000,
+        [ParticipantType.HEDGE_FUND]: 100000000,
+        [ParticipantType.CORPORATION]: 50000000,
+        [ParticipantType.GOVERNMENT]: 1000000000,
+        [ParticipantType.TRADER]: 20000000,
+        [ParticipantType.RETAIL_TRADER]: 2000000
+      }
+    },
+    tapePrivacy: TapePrivacy.TYPE_TAGS, // what the public trade tape reveals about counterparties
+    // Layered market-maker depth placed around the reference price on startup and restart
+    bookSeeding: {
+      levels: 10,
+      halfSpreadPips: 0.5,
+      levelSpacingPips: 0.5,
+      baseSize: 1000000,
+      sizeGrowth: 1.25, // each level further out is this much larger
+      makers: 5, // banks the seed levels are spread across
+      minLevels: 3 // re-seed a side that has fewer levels than this on restart
+    },
+    // Hidden Markov regimes: each symbol stays in a regime for an exponentially distributed
+    // time, then moves to another according to the transition weights
+    regimes: {
+      enabled: true,
+      meanDurationMs: {
+        [MarketRegime.TREND_UP]: 240000,
+        [MarketRegime.TREND_DOWN]: 240000,
+        [MarketRegime.RANGE]: 480000,
+        [MarketRegime.VOLATILE]: 90000
+      },
+      transitions: {
+        [MarketRegime.TREND_UP]: { [MarketRegime.RANGE]: 0.6, [MarketRegime.VOLATILE]: 0.3, [MarketRegime.TREND_DOWN]: 0.1 },
+        [MarketRegime.TREND_DOWN]: { [MarketRegime.RANGE]: 0.6, [MarketRegime.VOLATILE]: 0.3, [MarketRegime.TREND_UP]: 0.1 },
+        [MarketRegime.RANGE]: { [MarketRegime.TREND_UP]: 0.35, [MarketRegime.TREND_DOWN]: 0.35, [MarketRegime.VOLATILE]: 0.3 },
+        [MarketRegime.VOLATILE]: { [MarketRegime.RANGE]: 0.5, [MarketRegime.TREND_UP]: 0.25, [MarketRegime.TREND_DOWN]: 0.25 }
+      },
+      // Same fields as a market condition overlay
+      params: {
+        [MarketRegime.TREND_UP]: { buyBias: 0.08, volatility: 1, liquidity: 1, activity: 1.1 },
+        [MarketRegime.TREND_DOWN]: { buyBias: -0.08, volatility: 1, liquidity: 1, activity: 1.1 },
+        [MarketRegime.RANGE]: { buyBias: 0, volatility: 0.7, liquidity: 1.2, activity: 0.9, meanReversion: 0.5 },
+        [MarketRegime.VOLATILE]: { buyBias: 0, volatility: 2.5, liquidity: 0.6, activity: 1.5 }
+      },
+      // Activity multipliers per strategy, so agents react to the regime they are trading in
+      strategyActivity: {
+        [MarketRegime.TREND_UP]: { [TradingStrategy.TREND_FOLLOWING]: 2, [TradingStrategy.MEAN_REVERSION]: 0.5 },
+        [MarketRegime.TREND_DOWN]: { [TradingStrategy.TREND_FOLLOWING]: 2, [TradingStrategy.MEAN_REVERSION]: 0.5 },
+        [MarketRegime.RANGE]: { [TradingStrategy.TREND_FOLLOWING]: 0.5, [TradingStrategy.MEAN_REVERSION]: 2 },
+        [MarketRegime.VOLATILE]: { [TradingStrategy.HIGH_FREQUENCY]: 1.5, [TradingStrategy.CONSERVATIVE]: 0.3 }
+      }
+    },
+    // Realized volatility from mid-price bars. Agent order sizes scale with ATR relative to
+    // its baseline, raised to sizeElasticity, so flow thins out in quiet markets.
+    volatilityModel: {
+      enabled: true,
+      barMs: 10000,
+      atrPeriod: 14,
+      baselinePeriod: 200,
+      sizeElasticity: 0.5,
+      minSizeScale: 0.25,
+      maxSizeScale: 3,
+      levels: { quiet: 0.7, elevated: 1.4, extreme: 2.5 } // ATR / baseline thresholds
+    },
+    // Market stress from loop throttling or news-level volatility. At full stress banks
+    // quote spreadWideningPips further out and brokers degrade per their stress profile.
+    stress: {
+      enabled: true,
+      newsVolatility: 2.5, // condition volatility factor that counts as full stress
+      spreadWideningPips: 2
+    },
+    // Fair-value model per symbol (see priceProcess.js); symbols without an entry use
+    // `default`. Banks quote around the process price the way they do around an anchor.
+    priceProcesses: {
+      default: { model: PriceProcessModel.RANDOM_WALK }
+    },
+    // Level allocation per symbol (see MatchingAlgorithm); symbols without an entry use
+    // `default`. Pro-rata shares are rounded down to proRataLot units.
+    matchingAlgorithms: {
+      default: MatchingAlgorithm.FIFO
+    },
+    proRataLot: 1000,
+    // Currency news shocks (simulateNewsEvent). Every pair quoting the currency gets the
+    // condition below with the sign of its side of the pair, scaled by its correlation with
+    // the currency's reference pair; pairs with no configured correlation use
+    // uncorrelatedWeight and no pair reacts less than minWeight.
+    newsShock: {
+      buyBias: 0.4,
+      volatility: 4,
+      liquidity: 0.5,
+      activity: 3,
+      durationMs: 20000,
+      uncorrelatedWeight: 0.5,
+      minWeight: 0.2
+    },
+    // Per-currency strength from the cross rates: the average move of every pair quoting
+    // the currency, signed by its side of the pair, since the baseline (change) and over
+    // the last momentumTicks ticks (momentum). Published each tick as CurrencyStrength.
+    currencyStrength: {
+      enabled: true,
+      momentumTicks: 50
+    },
+    // Heartbeats go out from the tick at most every intervalMs with the sim time, so a
+    // consumer that stops hearing them knows the loop has stalled
+    heartbeat: {
+      enabled: true,
+      intervalMs: 1000
+    },
+    // Best bid/ask per symbol, published as BookTicker after any command that changes the
+    // top of book (price or size) rather than on a timer. timestampUs is monotonic.
+    bookTicker: {
+      enabled: true
+    },
+    // Broker-style positioning of retail accounts (agents and the user) per symbol,
+    // published from the tick at most every intervalMs as RetailSentiment
+    retailSentiment: {
+      enabled: true,
+      intervalMs: 1000
+    },
+    // Resting stop/take-profit heatmap, see getLiquidityMap. Buckets holding fewer than
+    // minOrders orders are left out so no single account's levels can be read off it.
+    liquidityMap: {
+      bucketPips: 5,
+      rangePips: 200,
+      minOrders: 2
+    },
+    // Runtime drift/volatility targets set by setPriceModel. Realized volatility of the book
+    // mid is measured over barMs bars and the process volatility corrected towards the
+    // target by (target / realized) ^ gain each bar.
+    priceTargeting: {
+      barMs: 10000,
+      window: 30, // bars of realized volatility
+      minBars: 5,
+      gain: 0.5,
+      minCorrection: 0.2,
+      maxCorrection: 5
+    },
+    // Private events (accepts, cancels, fills, queue moves, conditional triggers) are
+    // numbered per participant for these participants and kept for replay, see
+    // getExecutionReports. maxReports bounds each participant's retained history.
+    executionReports: {
+      participants: ['user_trader'],
+      maxReports: 5000
+    },
+    // Market data tier per subscriber clientId (see subscribe and MarketDataTier). Below
+    // L3 other participants' order events are withheld and trades arrive as tape entries
+    // (see toTapeEntry); L1 also cuts snapshots down to the best level. A client always
+    // sees its own activity in full.
+    dataTiers: {
+      default: MarketDataTier.L3,
+      clients: {}
+    },
+    // Where account ledgers and journal imports are kept (see storage.js): memory for
+    // nothing beyond the page, local for localStorage so they survive a reload. Read when
+    // the market data initializes; namespace separates deployments sharing an origin.
+    storage: {
+      mode: StorageMode.MEMORY,
+      namespace: 'fxmarketsim'
+    },
+    // Public tape conflation (see conflateTapeEntries): fills at the same price and aggressor
+    // side within windowMs are netted into single prints for getTradeTape and, as TapePrint
+    // events, for subscribers below L3, who then no longer get other participants' fills.
+    // Raw fills are kept as they are and still reach L3 subscribers.
+    tapeConflation: {
+      enabled: false,
+      windowMs: 100
+    },
+    // Default size of the snapshot a subscriber can ask for on subscribe: book levels,
+    // recent trades and (market store) candles per symbol
+    subscriptionSnapshots: {
+      depth: 20,
+      trades: 50,
+      candles: 100
+    },
+    // Redundant A/B market data feeds for practicing feed arbitration, see subscribeFeed.
+    // Both feeds carry the same messages with the same seq; each loses dropRate of them
+    // on its own and, with gapRate chance per message, goes silent for gapMs. Delivery
+    // takes latencyMs plus up to jitterMs, in order per feed. The last retransmitBuffer
+    // messages can be fetched again with getFeedRetransmission.
+    redundantFeeds: {
+      enabled: false,
+      events: [EngineEvent.BOOK_TICKER, EngineEvent.TRADE_EXECUTED],
+      retransmitBuffer: 10000,
+      feeds: {
+        A: { dropRate: 0.01, gapRate: 0.001, gapMs: 2000, latencyMs: 1, jitterMs: 2 },
+        B: { dropRate: 0.01, gapRate: 0.001, gapMs: 2000, latencyMs: 3, jitterMs: 2 }
+      }
+    },
+    // Adversarial agent for bot robustness testing, off by default. It watches the target
+    // participants' orders per symbol and side; once the last minRepeats arrive on a regular
+    // time grid (interval coefficient of variation within intervalTolerance) it buys or sells
+    // the same way leadMs before the next one is due, sizeMultiple times the target's size,
+    // and unwinds holdMs later. See getAdversaryReport.
+    adversary: {
+      enabled: false,
+      targets: ['user_trader'],
+      minRepeats: 4,
+      intervalTolerance: 0.1,
+      sizeTolerance: 0.05, // relative spread of sizes still counted as a fixed size
+      leadMs: 300,
+      sizeMultiple: 1,
+      holdMs: 2000,
+      history: 20
+    },
+    // While the simulation runs, commands from these participants (placed by UI or API
+    // handlers at arbitrary moments) are held and applied at the start of the next step,
+    // in submission order and before any agent flow, so an event log replays to the same
+    // fills. Each held command is stamped with the tick it was applied in.
+    commandOrdering: {
+      enabled: true,
+      heldParticipants: ['user_trader']
+    },
+    // Call auctions (see auction.js). Each schedule opens an auction per symbol windowMs
+    // before `time` (UTC, sim time) on its weekdays and uncrosses it at `time`. A halting
+    // auction collects every order for the symbol; otherwise only orders placed with the
+    // auction option take part and continuous trading carries on. The indicative price is
+    // published every indicativeIntervalMs while the window is open.
+    auctions: {
+      enabled: false,
+      indicativeIntervalMs: 1000,
+      maxResults: 500,
+      schedules: [
+        { id: 'weekly_open', type: AuctionType.OPEN, weekdays: [0], time: '22:00', windowMs: 300000, halt: true },
+        { id: 'london_fix', type: AuctionType.FIX, weekdays: [1, 2, 3, 4, 5], time: '16:00', windowMs: 300000, halt: false }
+      ]
+    }
+  })
+
+  // Computed
+  const totalParticipants = computed(() => {
+    return Object.values(config.participantCounts).reduce((sum, count) => sum + count, 0)
+  })
+
+  const activeParticipantCount = computed(() => {
     return Array.from(participants.value.values()).filter(p => p.active).length
   })
 
-  // Actions
-  const addSymbol = (symbol) => {
-    if (!symbols.value.has(symbol)) {
-      symbols.value.set(symbol, new OrderBook(symbol))
+  // Actions
+  const addSymbol = (symbol) => {
+    if (!symbols.value.has(symbol)) {
+      const orderbook = new OrderBook(symbol)
+      attachCreditHooks(orderbook)
+      attachBookListeners(orderbook)
+      applyMatchingAlgorithm(orderbook)
+      symbols.value.set(symbol, orderbook)
+
+      const pools = new Map()
+      for (const pool of config.liquidityPools?.pools || []) {
+        if (pool.id === PRIMARY_POOL) continue
+        const poolBook = new OrderBook(symbol)
+        attachCreditHooks(poolBook)
+        attachBookListeners(poolBook)
+        applyMatchingAlgorithm(poolBook)
+        pools.set(pool.id, poolBook)
+      }
+      poolBooks.value.set(symbol, pools)
+      initPriceProcess(symbol)
+      // Symbols added after startup need depth before anyone trades them
+      if (participants.value.size > 0) {
+        seedOrderBook(symbol)
+      }
+    }
+  }
+
+  const getMatchingAlgorithm = (symbol) => {
+    return config.matchingAlgorithms?.[symbol] || config.matchingAlgorithms?.default || MatchingAlgorithm.FIFO
+  }
+
+  const applyMatchingAlgorithm = (book) => {
+    book.matchingAlgorithm = getMatchingAlgorithm(book.symbol)
+    book.allocationLot = config.proRataLot || 1
+  }
+
+  // Switches how a symbol's price levels are allocated, in every pool; resting orders stay
+  const setMatchingAlgorithm = (symbol, algorithm) => {
+    if (!symbols.value.has(symbol)) return { success: false, error: `Unknown symbol ${symbol}` }
+    if (!Object.values(MatchingAlgorithm).includes(algorithm)) {
+      return { success: false, error: `Unknown matching algorithm ${algorithm}` }
+    }
+    config.matchingAlgorithms = { ...config.matchingAlgorithms, [symbol]: algorithm }
+    applyMatchingAlgorithm(symbols.value.get(symbol))
+    for (const book of poolBooks.value.get(symbol)?.values() || []) applyMatchingAlgorithm(book)
+    return { success: true }
+  }
+
+  // Embedder listeners (see OrderBook.addListener) registered through the engine. They are
+  // attached to every matching book, including pool books and symbols added later.
+  const bookListeners = []
+
+  const getAllBooks = () => {
+    const books = []
+    for (const [symbol, book] of symbols.value) {
+      books.push(book)
+      for (const poolBook of poolBooks.value.get(symbol)?.values() || []) books.push(poolBook)
+    }
+    return books
+  }
+
+  const attachBookListeners = (orderbook) => {
+    bookListeners
+      .filter(entry => !entry.symbol || entry.symbol === orderbook.symbol)
+      .forEach(entry => entry.removers.push(orderbook.addListener(entry.listener)))
+  }
+
+  // listener: { onTrade, onLevelChange, onOrderRejected }, each optional. Pass a symbol to
+  // hear only that symbol's books. Returns a function that unregisters it everywhere.
+  const addBookListener = (listener, { symbol = null } = {}) => {
+    const entry = { listener, symbol, removers: [] }
+    bookListeners.push(entry)
+    getAllBooks()
+      .filter(book => !symbol || book.symbol === symbol)
+      .forEach(book => entry.removers.push(book.addListener(listener)))
+    return () => {
+      entry.removers.forEach(remove => remove())
+      bookListeners.splice(bookListeners.indexOf(entry), 1)
+    }
+  }
+
+  // LOBSTER research export: one recorder per symbol's primary book for the session
+  const lobsterRecorders = new Map()
+
+  const startLobsterSession = (sessionSymbols = Array.from(symbols.value.keys()), { levels = 10 } = {}) => {
+    if (lobsterRecorders.size > 0) return { success: false, error: 'A LOBSTER session is already recording' }
+    for (const symbol of sessionSymbols) {
+      const book = symbols.value.get(symbol)
+      if (book) lobsterRecorders.set(symbol, createLobsterRecorder(book, { levels }))
+    }
+    return { success: true, symbols: Array.from(lobsterRecorders.keys()) }
+  }
+
+  // Ends the session; per symbol, the message and orderbook files as { name, content }
+  const stopLobsterSession = () => {
+    const files = Array.from(lobsterRecorders.values()).map(recorder => {
+      recorder.stop()
+      return { symbol: recorder.symbol, messages: recorder.messages.length, ...recorder.getFiles() }
+    })
+    lobsterRecorders.clear()
+    return files
+  }
+
+  const getPriceProcessSettings = (symbol) => {
+    return config.priceProcesses?.[symbol] || config.priceProcesses?.default || { model: PriceProcessModel.RANDOM_WALK }
+  }
+
+  const initPriceProcess = (symbol, settings = getPriceProcessSettings(symbol)) => {
+    const startPrice = symbols.value.get(symbol)?.getMidPrice() || config.basePrices[symbol]
+    priceProcessStates.value.set(symbol, { ...createPriceProcess(settings, startPrice), steppedAt: Date.now() })
+  }
+
+  // Switches a symbol's model at runtime; the new process starts from the current mid
+  const setPriceProcess = (symbol, settings) => {
+    if (!symbols.value.has(symbol)) return { success: false, error: `Unknown symbol ${symbol}` }
+    try {
+      config.priceProcesses = { ...config.priceProcesses, [symbol]: settings }
+      initPriceProcess(symbol, settings)
+      return { success: true }
+    } catch (error) {
+      return { success: false, error: error.message }
+    }
+  }
+
+  const advancePriceProcesses = (now = Date.now()) => {
+    for (const process of priceProcessStates.value.values()) {
+      const dt = (now - process.steppedAt) / 1000
+      if (dt <= 0) continue
+      stepPriceProcess(process, dt)
+      process.steppedAt = now
+    }
+  }
+
+  // Forces a drift and/or volatility on a symbol at runtime, e.g. a trending afternoon
+  // (driftPerHour 0.002 = +0.2%/h) or a dead-quiet range (targetVolatility 0.02). The
+  // symbol runs a diffusion process until clearPriceModel restores its configured model.
+  const setPriceModel = (symbol, { driftPerHour = 0, targetVolatility = null } = {}) => {
+    if (!symbols.value.has(symbol)) return { success: false, error: `Unknown symbol ${symbol}` }
+    if (!Number.isFinite(driftPerHour)) return { success: false, error: 'driftPerHour must be a number' }
+    if (targetVolatility !== null && !(targetVolatility > 0)) {
+      return { success: false, error: 'targetVolatility must be positive' }
+    }
+
+    const existing = priceTargets.value.get(symbol)
+    const previous = existing ? existing.previous : (config.priceProcesses?.[symbol] ?? null)
+    const volatility = targetVolatility ?? 0.08
+    const result = setPriceProcess(symbol, {
+      model: PriceProcessModel.JUMP_DIFFUSION,
+      drift: driftPerHour * 24 * 365,
+      volatility,
+      jumpsPerHour: 0
+    })
+    if (!result.success) return result
+
+    priceTargets.value.set(symbol, {
+      symbol,
+      driftPerHour,
+      targetVolatility,
+      correction: 1,
+      realizedVolatility: null,
+      samples: [],
+      setAt: Date.now(),
+      previous
+    })
+    return { success: true, data: getPriceModel(symbol) }
+  }
+
+  const clearPriceModel = (symbol) => {
+    const target = priceTargets.value.get(symbol)
+    if (!target) return false
+
+    priceTargets.value.delete(symbol)
+    const processes = { ...config.priceProcesses }
+    if (target.previous) processes[symbol] = target.previous
+    else delete processes[symbol]
+    config.priceProcesses = processes
+    initPriceProcess(symbol)
+    return true
+  }
+
+  const adjustPriceTargets = (now = Date.now()) => {
+    const targeting = config.priceTargeting
+    for (const target of priceTargets.value.values()) {
+      const last = target.samples[target.samples.length - 1]
+      if (last && now - last.at < targeting.barMs) continue
+
+      const mid = symbols.value.get(target.symbol)?.getMidPrice()
+      if (!mid) continue
+      target.samples.push({ at: now, mid })
+      if (target.samples.length > targeting.window + 1) target.samples.shift()
+      if (target.samples.length <= targeting.minBars) continue
+
+      const returns = target.samples.slice(1).map((sample, i) => Math.log(sample.mid / target.samples[i].mid))
+      const mean = returns.reduce((sum, r) => sum + r, 0) / returns.length
+      const variance = returns.reduce((sum, r) => sum + Math.pow(r - mean, 2), 0) / returns.length
+      target.realizedVolatility = annualizeVolatility(Math.sqrt(variance), targeting.barMs / 1000)
+
+      // Drift-only targets leave the volatility where it was set
+      if (target.targetVolatility === null || target.realizedVolatility <= 0) continue
+      const step = Math.pow(target.targetVolatility / target.realizedVolatility, targeting.gain)
+      target.correction = Math.min(targeting.maxCorrection, Math.max(targeting.minCorrection, target.correction * step))
+      const process = priceProcessStates.value.get(target.symbol)
+      if (process) setProcessVolatility(process, target.targetVolatility * target.correction)
+    }
+  }
+
+  const advanceSimClock = (now = Date.now()) => {
+    const clock = simClock.value
+    clock.simTime = clock.simTime === null ? now : clock.simTime + (now - clock.wallTime) * simulationSpeed.value
+    clock.wallTime = now
+
+    const heartbeat = config.heartbeat
+    if (!heartbeat?.enabled) return
+    if (clock.lastHeartbeatAt !== null && now - clock.lastHeartbeatAt < heartbeat.intervalMs) return
+    clock.lastHeartbeatAt = now
+    clock.heartbeats += 1
+    emit(EngineEvent.HEARTBEAT, {
+      heartbeat: clock.heartbeats,
+      simTime: clock.simTime,
+      wallTime: now,
+      speed: simulationSpeed.value,
+      running: isRunning.value
+    })
+  }
+
+  // Current sim time, extrapolated from the last tick, for clients aligning their clocks
+  const getServerTime = () => {
+    const clock = simClock.value
+    const now = Date.now()
+    return {
+      simTime: clock.simTime === null ? now : clock.simTime + (now - clock.wallTime) * simulationSpeed.value,
+      wallTime: now,
+      speed: simulationSpeed.value,
+      sequence: commandSequence,
+      heartbeats: clock.heartbeats,
+      lastHeartbeatAt: clock.lastHeartbeatAt,
+      heartbeatIntervalMs: config.heartbeat?.intervalMs ?? null
+    }
+  }
+
+  const getCurrencies = () => {
+    const currencies = new Set()
+    config.symbols.forEach(symbol => {
+      currencies.add(symbol.slice(0, 3))
+      currencies.add(symbol.slice(3, 6))
+    })
+    return Array.from(currencies)
+  }
+
+  const updateCurrencyStrength = (now = Date.now()) => {
+    const settings = config.currencyStrength
+    if (!settings?.enabled) return
+
+    const mids = new Map()
+    for (const symbol of config.symbols) {
+      const mid = symbols.value.get(symbol)?.getMidPrice()
+      if (mid) mids.set(symbol, mid)
+    }
+    if (mids.size === 0) return
+
+    const strength = currencyStrength.value
+    mids.forEach((mid, symbol) => {
+      if (!strength.baseline.has(symbol)) strength.baseline.set(symbol, mid)
+    })
+    strength.history.push(mids)
+    if (strength.history.length > settings.momentumTicks + 1) strength.history.shift()
+    const past = strength.history[0]
+
+    // Mean signed log move of the currency's pairs, in basis points
+    const averageMove = (currency, reference) => {
+      const moves = []
+      mids.forEach((mid, symbol) => {
+        const sign = symbol.slice(0, 3) === currency ? 1 : symbol.slice(3, 6) === currency ? -1 : 0
+        const from = reference.get(symbol)
+        if (sign !== 0 && from) moves.push(sign * Math.log(mid / from))
+      })
+      return moves.length > 0 ? moves.reduce((sum, move) => sum + move, 0) / moves.length * 10000 : null
+    }
+
+    const indices = getCurrencies()
+      .map(currency => {
+        const changeBps = averageMove(currency, strength.baseline)
+        return {
+          currency,
+          index: changeBps === null ? null : 100 * Math.exp(changeBps / 10000),
+          changeBps,
+          momentumBps: averageMove(currency, past),
+          pairs: config.symbols.filter(symbol => symbol.includes(currency)).length
+        }
+      })
+      .filter(entry => entry.changeBps !== null)
+      .sort((a, b) => b.changeBps - a.changeBps)
+      .map((entry, i) => ({ ...entry, rank: i + 1 }))
+
+    strength.indices = indices
+    strength.updatedAt = now
+    emit(EngineEvent.CURRENCY_STRENGTH, { indices })
+  }
+
+  // Share of retail accounts long and short per symbol, with volume-weighted average
+  // entry prices. An account holding the symbol counts once, on the side of its position.
+  const getRetailSentiment = (symbol = null) => {
+    const entries = new Map(config.symbols.map(name => [name, {
+      symbol: name,
+      accounts: 0,
+      longAccounts: 0,
+      shortAccounts: 0,
+      longVolume: 0,
+      shortVolume: 0,
+      longEntryValue: 0,
+      shortEntryValue: 0
+    }]))
+
+    for (const participant of participants.value.values()) {
+      if (participant.participantType !== ParticipantType.RETAIL_TRADER) continue
+      for (const position of participant.positions.values()) {
+        const entry = entries.get(position.symbol)
+        if (!entry) continue
+        entry.accounts += 1
+        if (position.side === OrderSide.BUY) {
+          entry.longAccounts += 1
+          entry.longVolume += position.volume
+          entry.longEntryValue += position.entryPrice * position.volume
+        } else {
+          entry.shortAccounts += 1
+          entry.shortVolume += position.volume
+          entry.shortEntryValue += position.entryPrice * position.volume
+        }
+      }
+    }
+
+    const result = Array.from(entries.values()).map(({ longEntryValue, shortEntryValue, ...entry }) => ({
+      ...entry,
+      longPercent: entry.accounts > 0 ? entry.longAccounts / entry.accounts * 100 : null,
+      shortPercent: entry.accounts > 0 ? entry.shortAccounts / entry.accounts * 100 : null,
+      averageLongEntry: entry.longVolume > 0 ? longEntryValue / entry.longVolume : null,
+      averageShortEntry: entry.shortVolume > 0 ? shortEntryValue / entry.shortVolume : null
+    }))
+    return symbol ? result.find(entry => entry.symbol === symbol) || null : result
+  }
+
+  // Stop and take-profit levels waiting around the market, bucketed by price: the implied
+  // exits of persona agents' positions, pending conditional orders, and any `levels`
+  // ({ price, volume, side, kind }) the caller knows about, e.g. the user's stops.
+  // side is the side of the order that would fire; kind is 'stop' or 'takeProfit'.
+  const getLiquidityMap = (symbol, { bucketPips = null, rangePips = null, minOrders = null, levels = [] } = {}) => {
+    const orderbook = symbols.value.get(symbol)
+    if (!orderbook) return null
+    const settings = config.liquidityMap
+    const pip = getPipSize(symbol)
+    const bucketSize = (bucketPips ?? settings.bucketPips) * pip
+    const range = (rangePips ?? settings.rangePips) * pip
+    const threshold = minOrders ?? settings.minOrders
+    const mid = orderbook.getMidPrice() || getReferencePrice(symbol)
+
+    const collected = [...levels]
+    const personas = config.retailPersonas?.profiles || {}
+    for (const participant of participants.value.values()) {
+      const profile = participant.persona && personas[participant.persona]
+      const position = profile && participant.positions.get(symbol)
+      if (!position) continue
+      const direction = position.side === OrderSide.BUY ? 1 : -1
+      const exitSide = position.side === OrderSide.BUY ? OrderSide.SELL : OrderSide.BUY
+      collected.push({ price: position.entryPrice + direction * profile.takeProfitPips * pip, volume: position.volume, side: exitSide, kind: 'takeProfit' })
+      if (profile.stopLossPips !== null) {
+        collected.push({ price: position.entryPrice - direction * profile.stopLossPips * pip, volume: position.volume, side: exitSide, kind: 'stop' })
+      }
+    }
+    for (const conditional of conditionalOrders.value.values()) {
+      if (conditional.status !== ConditionalStatus.PENDING) continue
+      if (conditional.condition.symbol !== symbol || conditional.order.symbol !== symbol) continue
+      const { side } = conditional.order
+      // Buying a rise or selling a fall is a stop; the opposite locks in a move
+      const momentum = (side === OrderSide.BUY) === (conditional.condition.operator === ConditionOperator.ABOVE)
+      collected.push({ price: conditional.condition.price, volume: conditional.order.amount, side, kind: momentum ? 'stop' : 'takeProfit' })
+    }
+
+    const buckets = new Map()
+    collected.forEach(level => {
+      if (!(level.price > 0) || !(level.volume > 0)) return
+      if (mid && Math.abs(level.price - mid) > range) return
+      const index = Math.floor(level.price / bucketSize)
+      if (!buckets.has(index)) {
+        const edge = (i) => Number((i * bucketSize).toFixed(getPriceDecimals(symbol)))
+        buckets.set(index, { priceLow: edge(index), priceHigh: edge(index + 1), orders: 0, buyStops: 0, sellStops: 0, buyTakeProfits: 0, sellTakeProfits: 0, totalVolume: 0 })
+      }
+      const bucket = buckets.get(index)
+      const key = `${level.side === OrderSide.BUY ? 'buy' : 'sell'}${level.kind === 'stop' ? 'Stops' : 'TakeProfits'}`
+      bucket[key] += level.volume
+      bucket.totalVolume += level.volume
+      bucket.orders += 1
+    })
+
+    const visible = Array.from(buckets.values())
+      .filter(bucket => bucket.orders >= threshold)
+      .sort((a, b) => b.priceLow - a.priceLow)
+    return {
+      symbol,
+      mid,
+      bucketSize,
+      timestamp: Date.now(),
+      buckets: visible.map(({ orders, ...bucket }) => bucket),
+      hiddenBuckets: buckets.size - visible.length
+    }
+  }
+
+  const publishRetailSentiment = (now = Date.now()) => {
+    const settings = config.retailSentiment
+    if (!settings?.enabled) return
+    const last = retailSentiment.value.updatedAt
+    if (last !== null && now - last < settings.intervalMs) return
+
+    const sentiment = getRetailSentiment()
+    retailSentiment.value = { symbols: sentiment, updatedAt: now }
+    emit(EngineEvent.RETAIL_SENTIMENT, { sentiment, timestamp: now })
+  }
+
+  // Strongest first. With `currency`, just that entry.
+  const getCurrencyStrength = (currency = null) => {
+    const indices = currencyStrength.value.indices
+    return currency ? indices.find(entry => entry.currency === currency) || null : indices.slice()
+  }
+
+  // Re-bases every index to 100 at the current prices, e.g. at the start of a session
+  const resetCurrencyStrength = () => {
+    currencyStrength.value = { baseline: new Map(), history: [], indices: [], updatedAt: null }
+  }
+
+  const getPriceModel = (symbol) => {
+    const target = priceTargets.value.get(symbol)
+    return {
+      ...getPriceProcess(symbol),
+      target: target
+        ? {
+            driftPerHour: target.driftPerHour,
+            targetVolatility: target.targetVolatility,
+            realizedVolatility: target.realizedVolatility,
+            correction: target.correction,
+            setAt: target.setAt
+          }
+        : null
+    }
+  }
+
+  // The process's fair value, or null for the random walk
+  const getProcessPrice = (symbol) => {
+    const process = priceProcessStates.value.get(symbol)
+    return process?.state.price || null
+  }
+
+  const getPriceProcess = (symbol) => {
+    const process = priceProcessStates.value.get(symbol)
+    if (!process) return null
+    return { symbol, model: process.model, settings: process.settings, price: getProcessPrice(symbol) }
+  }
+
+  // Compact numeric ids in creation order, carried on trades as buyerNo/sellerNo
+  let participantNo = 0
+
+  const addParticipant = (participant) => {
+    if (participant.no === null) participant.no = ++participantNo
+    participants.value.set(participant.id, participant)
+    marketStats.value.activeParticipants = participants.value.size
+  }
+
+  const getOrderBook = (symbol, poolId = null) => {
+    if (!poolId || poolId === PRIMARY_POOL) return symbols.value.get(symbol)
+    return poolBooks.value.get(symbol)?.get(poolId)
+  }
+
+  const getActivePoolIds = () => {
+    return config.liquidityPools?.enabled
+      ? config.liquidityPools.pools.map(pool => pool.id)
+      : [PRIMARY_POOL]
+  }
+
+  // [poolId, OrderBook] pairs for every active pool of a symbol
+  const getPoolBooks = (symbol) => {
+    return getActivePoolIds()
+      .map(poolId => [poolId, getOrderBook(symbol, poolId)])
+      .filter(([, book]) => book)
+  }
+
+  const hashId = (id) => {
+    let hash = 0
+    for (let i = 0; i < id.length; i++) {
+      hash = (hash * 31 + id.charCodeAt(i)) >>> 0
+    }
+    return hash
+  }
+
+  // Pools a participant posts liquidity in: one home pool, banks also in the next ones
+  const getParticipantPools = (participantId) => {
+    const poolIds = getActivePoolIds()
+    const home = hashId(participantId) % poolIds.length
+    const count = getParticipantType(participantId) === ParticipantType.BANK
+      ? Math.min(poolIds.length, config.liquidityPools?.bankPools ?? 1)
+      : 1
+    return Array.from({ length: count }, (_, i) => poolIds[(home + i) % poolIds.length])
+  }
+
+  const getBestPool = (symbol, side) => {
+    let best = null
+    for (const [poolId, book] of getPoolBooks(symbol)) {
+      const touch = side === OrderSide.BUY ? book.getBestAsk() : book.getBestBid()
+      if (touch === null) continue
+      if (!best || (side === OrderSide.BUY ? touch < best.touch : touch > best.touch)) {
+        best = { poolId, touch }
+      }
+    }
+    return best ? best.poolId : PRIMARY_POOL
+  }
+
+  const resolvePool = (command) => {
+    if (!config.liquidityPools?.enabled) return PRIMARY_POOL
+    if (command.poolId) return command.poolId
+    if (command.orderType === OrderType.MARKET || !command.orderType) {
+      return getBestPool(command.symbol, command.side)
+    }
+    const pools = getParticipantPools(command.participantId)
+    return pools[Math.floor(Math.random() * pools.length)]
+  }
+
+  // Command queue: every state change goes through here so user and agent
+  // orders are applied in a single deterministic sequence
+  const commandQueue = []
+  const heldCommands = [] // external commands waiting for the next step, see config.commandOrdering
+  const eventSubscribers = new Set()
+  let commandSequence = 0
+  let processingSequence = 0
+  let draining = false
+
+  // With a clientId, events reach the handler through that client's simulated network
+  // conditions (see setNetworkConditions). With `snapshot` (true or the options of
+  // buildSubscriptionSnapshot) the handler first gets a SubscriptionSnapshot of the current
+  // books and trades, then live events from that point on, so a client joining mid-session
+  // needs no separate bootstrap. Events with a higher sequence than the snapshot's are newer.
+  const subscribe = (handler, { clientId = null, snapshot = null } = {}) => {
+    const deliver = (event, options) => {
+      event = applyDataTier(clientId, event)
+      if (!event) return
+      deliverToClient(clientId, () => {
+        try {
+          handler(event)
+        } catch (error) {
+          console.warn('Engine event handler failed:', error)
+        }
+      }, null, options)
+    }
+    const subscriber = clientId === null ? handler : (event) => deliver(event)
+    if (snapshot) {
+      try {
+        const event = buildSubscriptionSnapshot(clientId, snapshot === true ? {} : snapshot)
+        // The snapshot is the client's starting state: it waits its turn behind the latency
+        // like any message, but is never dropped
+        if (clientId === null) handler(event)
+        else deliver(event, { reliable: true })
+      } catch (error) {
+        console.warn('Engine event handler failed:', error)
+      }
+    }
+    eventSubscribers.add(subscriber)
+    return () => eventSubscribers.delete(subscriber)
+  }
+
+  // Per symbol (all by default): the book to `depth` levels (one at L1), the book ticker and
+  // the newest `trades` trades, raw at L3 and as tape entries below, as the client's live
+  // events will be. extend(symbol) adds fields of the caller's, e.g. the market store's
+  // candles.
+  const buildSubscriptionSnapshot = (clientId, {
+    symbols: wanted = null,
+    depth = config.subscriptionSnapshots.depth,
+    trades = config.subscriptionSnapshots.trades,
+    extend = null
+  } = {}) => {
+    const tier = clientId === null ? MarketDataTier.L3 : getDataTier(clientId)
+    const levels = tier === MarketDataTier.L1 ? 1 : depth
+    const books = (wanted || Array.from(symbols.value.keys())).filter(symbol => symbols.value.has(symbol)).map(symbol => {
+      const book = symbols.value.get(symbol).snapshot()
+      // Fills still being netted arrive later as a TapePrint, so they are left out here
+      const openPrintId = tapeConflator.openPrintId(symbol)
+      return {
+        ...book,
+        bids: book.bids.slice(0, levels),
+        asks: book.asks.slice(0, levels),
+        ticker: bookTickers.get(symbol) || null,
+        trades: tier === MarketDataTier.L3
+          ? getRecentTrades(symbol, trades)
+          : getTradeTape(symbol, { limit: trades, viewerId: clientId }).filter(entry => entry.id !== openPrintId),
+        ...(extend ? extend(symbol) : {})
+      }
+    })
+    return { type: EngineEvent.SUBSCRIPTION_SNAPSHOT, sequence: processingSequence, timestamp: Date.now(), books }
+  }
+
+  const getDataTier = (clientId) => getClientDataTier(config.dataTiers, clientId)
+
+  const setDataTier = (clientId, tier) => {
+    if (!isMarketDataTier(tier)) return { success: false, error: `Unknown data tier ${tier}` }
+    config.dataTiers.clients[clientId] = tier
+    return { success: true }
+  }
+
+  // The event as the client's tier lets it see it, or null when it is withheld
+  const applyDataTier = (clientId, event) => {
+    return filterForDataTier(event, getDataTier(clientId), {
+      clientId,
+      own: getEventParticipants(event).includes(clientId),
+      tapeConflated: config.tapeConflation.enabled,
+      toTapeEntry: (trade) => toTapeEntry(trade, clientId)
+    })
+  }
+
+  // Injected network conditions per clientId (a participant id for order acknowledgements),
+  // see networkSimulation.js
+  const clientNetwork = createClientNetwork()
+
+  const setNetworkConditions = (clientId, conditions = {}) => clientNetwork.set(clientId, conditions)
+
+  const clearNetworkConditions = (clientId = null) => clientNetwork.clear(clientId)
+
+  const getNetworkConditions = () => clientNetwork.list()
+
+  const deliverToClient = (clientId, deliver, onDrop = null, options = {}) => clientNetwork.deliver(clientId, deliver, onDrop, options)
+
+  const redundantFeeds = createRedundantFeeds({ getSettings: () => config.redundantFeeds })
+
+  const subscribeFeed = (feedId, handler) => redundantFeeds.subscribe(feedId, handler)
+
+  const publishFeeds = (event) => redundantFeeds.publish(event)
+
+  const getFeedRetransmission = (fromSeq, toSeq) => redundantFeeds.getRetransmission(fromSeq, toSeq)
+
+  const getFeedStats = () => redundantFeeds.getStats()
+
+  const emit = (type, payload) => {
+    const event = { type, sequence: processingSequence, timestamp: Date.now(), ...payload }
+    for (const handler of eventSubscribers) {
+      try {
+        handler(event)
+      } catch (error) {
+        console.warn('Engine event handler failed:', error)
+      }
+    }
+    recordExecutionReports(event)
+    publishFeeds(event)
+  }
+
+  // participantId -> { lastSeq, reports }; reports carry a gapless per-participant seq
+  const executionReports = new Map()
+
+  const getEventParticipants = (event) => {
+    switch (event.type) {
+      case EngineEvent.ORDER_ACCEPTED:
+      case EngineEvent.ORDER_CANCELLED:
+        return [event.order.participantId]
+      case EngineEvent.TRADE_EXECUTED:
+        return event.trade.buyerId === event.trade.sellerId
+          ? [event.trade.buyerId]
+          : [event.trade.buyerId, event.trade.sellerId]
+      case EngineEvent.QUEUE_POSITION_CHANGED:
+        return [event.participantId]
+      case EngineEvent.CONDITIONAL_TRIGGERED:
+        return [event.conditional.participantId]
+      default:
+        return []
+    }
+  }
+
+  const recordExecutionReports = (event) => {
+    const tracked = config.executionReports?.participants || []
+    if (tracked.length === 0) return
+
+    for (const participantId of getEventParticipants(event)) {
+      if (!tracked.includes(participantId)) continue
+      let log = executionReports.get(participantId)
+      if (!log) {
+        log = { lastSeq: 0, reports: [] }
+        executionReports.set(participantId, log)
+      }
+      const report = { ...event, participantId, seq: ++log.lastSeq }
+      log.reports.push(report)
+      if (log.reports.length > (config.executionReports.maxReports ?? 5000)) log.reports.shift()
+      emit(EngineEvent.EXECUTION_REPORT, { participantId, report })
+    }
+  }
+
+  // Reports with seq > sinceSeq, oldest first. A client that processes them in order and
+  // remembers the last seq it handled sees every private event exactly once across
+  // reconnects; `missed` is true when reports it never saw were already trimmed.
+  const getExecutionReports = (participantId, { sinceSeq = 0, limit = 1000 } = {}) => {
+    const log = executionReports.get(participantId)
+    if (!log) return { reports: [], lastSeq: 0, firstSeq: null, missed: false }
+
+    const firstSeq = log.reports.length > 0 ? log.reports[0].seq : null
+    const start = firstSeq === null ? log.reports.length : Math.max(0, sinceSeq + 1 - firstSeq)
+    return {
+      reports: log.reports.slice(start, start + limit),
+      lastSeq: log.lastSeq,
+      firstSeq,
+      missed: firstSeq !== null && sinceSeq + 1 < firstSeq
+    }
+  }
+
+  const dispatch = (command) => {
+    return new Promise((resolve, reject) => {
+      // The command is applied either way; only the submitter's acknowledgement goes
+      // through its network conditions, so a dropped ack leaves it unsure of the outcome
+      const clientId = command.participantId ??
+        activeOrders.value.get(command.orderId)?.participantId ??
+        conditionalOrders.value.get(command.conditionalId)?.participantId
+      const acknowledge = clientId !== undefined && clientNetwork.has(clientId)
+        ? (settle) => (value) => deliverToClient(clientId, () => settle(value), () => {
+            const error = new Error(`Acknowledgement of ${command.type} dropped`)
+            error.dropped = true
+            reject(error)
+          })
+        : (settle) => settle
+      const entry = { command: { ...command }, resolve: acknowledge(resolve), reject: acknowledge(reject) }
+      if (isHeld(command, clientId)) {
+        heldCommands.push(entry)
+        return
+      }
+      entry.command.sequence = ++commandSequence
+      commandQueue.push(entry)
+      drainCommands()
+    })
+  }
+
+  const isHeld = (command, clientId) => {
+    return isRunning.value &&
+      config.commandOrdering?.enabled &&
+      command.type !== EngineCommand.TICK &&
+      config.commandOrdering.heldParticipants.includes(clientId)
+  }
+
+  // Applies held commands in the order they were submitted. Sequence numbers are assigned
+  // here so they follow the order commands were actually processed in.
+  const releaseHeldCommands = () => {
+    if (heldCommands.length === 0) return 0
+    const released = heldCommands.splice(0)
+    released.forEach(entry => {
+      entry.command.sequence = ++commandSequence
+      entry.command.tick = loopMetrics.value.ticks
+      commandQueue.push(entry)
+    })
+    drainCommands()
+    return released.length
+  }
+
+  const drainCommands = () => {
+    if (draining) return
+    draining = true
+    try {
+      while (commandQueue.length > 0) {
+        const { command, resolve, reject } = commandQueue.shift()
+        processingSequence = command.sequence
+        try {
+          resolve(handleCommand(command))
+        } catch (error) {
+          reject(error)
+        }
+        publishBookTickers()
+      }
+    } finally {
+      draining = false
+    }
+  }
+
+  // symbol -> last published top of book
+  const bookTickers = new Map()
+  let lastTickerUs = 0
+
+  // Microseconds since the epoch off the monotonic performance clock, strictly increasing
+  const monotonicMicros = () => {
+    const now = Math.floor((performance.timeOrigin + performance.now()) * 1000)
+    lastTickerUs = Math.max(now, lastTickerUs + 1)
+    return lastTickerUs
+  }
+
+  const levelVolume = (level) => level ? level.reduce((sum, order) => sum + order.amount, 0) : 0
+
+  const publishBookTickers = () => {
+    if (!config.bookTicker?.enabled) return
+    for (const [symbol, book] of symbols.value) {
+      const bid = book.getBestBid()
+      const ask = book.getBestAsk()
+      const bidSize = levelVolume(book.bids.get(bid))
+      const askSize = levelVolume(book.asks.get(ask))
+      const last = bookTickers.get(symbol)
+      if (last && last.bid === bid && last.ask === ask && last.bidSize === bidSize && last.askSize === askSize) continue
+
+      const ticker = {
+        symbol,
+        bid,
+        bidSize,
+        ask,
+        askSize,
+        updateId: (last?.updateId ?? 0) + 1,
+        timestampUs: monotonicMicros()
+      }
+      bookTickers.set(symbol, ticker)
+      emit(EngineEvent.BOOK_TICKER, { ticker })
+    }
+  }
+
+  // Last published top of book for one symbol, or all of them
+  const getBookTicker = (symbol = null) => {
+    if (symbol) return bookTickers.get(symbol) || null
+    return Array.from(bookTickers.values())
+  }
+
+  const handleCommand = (command) => {
+    switch (command.type) {
+      case EngineCommand.PLACE_ORDER:
+        return handlePlaceOrder(command)
+      case EngineCommand.PLACE_BATCH:
+        return handlePlaceBatch(command)
+      case EngineCommand.CANCEL_ORDER:
+        return handleCancelOrder(command)
+      case EngineCommand.CANCEL_ALL:
+        return handleCancelAll(command)
+      case EngineCommand.MASS_QUOTE:
+        return handleMassQuote(command)
+      case EngineCommand.PLACE_CONDITIONAL:
+        return handlePlaceConditional(command)
+      case EngineCommand.CANCEL_CONDITIONAL:
+        return handleCancelConditional(command)
+      case EngineCommand.TICK:
+        return handleTick()
+      case EngineCommand.SNAPSHOT:
+        return handleSnapshot(command)
+      case EngineCommand.START_AUCTION:
+        return handleStartAuction(command)
+      case EngineCommand.UNCROSS_AUCTION:
+        return handleUncrossAuction(command)
+      default:
+        throw new Error(`Unknown engine command ${command.type}`)
+    }
+  }
+
+  const placeOrder = (symbol, side, amount, participantId, orderType = OrderType.MARKET, price = null, options = {}) => {
+    return dispatch({
+      type: EngineCommand.PLACE_ORDER,
+      symbol,
+      side,
+      amount,
+      participantId,
+      orderType,
+      price,
+      clientOrderId: options.clientOrderId,
+      poolId: options.poolId,
+      auction: options.auction
+    })
+  }
+
+  // orders: [{ symbol, side, amount, orderType, price, clientOrderId }], all for participantId
+  const placeOrderBatch = (orders, participantId) => {
+    return dispatch({ type: EngineCommand.PLACE_BATCH, orders, participantId })
+  }
+
+  // order: { symbol, side, amount, orderType, price }, placed for participantId once
+  // condition: { symbol, operator, price, source = Last } holds. With notifyOnly the order
+  // isn't placed; the CONDITIONAL_TRIGGERED event lets the owner route it instead.
+  const placeConditionalOrder = (participantId, order, condition, options = {}) => {
+    return dispatch({
+      type: EngineCommand.PLACE_CONDITIONAL,
+      participantId,
+      order,
+      condition,
+      expiresAt: options.expiresAt ?? null,
+      notifyOnly: !!options.notifyOnly
+    })
+  }
+
+  const cancelConditionalOrder = (conditionalId) => {
+    return dispatch({ type: EngineCommand.CANCEL_CONDITIONAL, conditionalId })
+  }
+
+  const cancelOrder = (orderId) => {
+    return dispatch({ type: EngineCommand.CANCEL_ORDER, orderId })
+  }
+
+  // quotes: [{ symbol, bidPrice, bidSize, askPrice, askSize }]; a zero or missing size pulls that side
+  const massQuote = (participantId, quotes) => {
+    return dispatch({ type: EngineCommand.MASS_QUOTE, participantId, quotes })
+  }
+
+  // Cancels every resting order of a participant, optionally limited to one symbol and/or side
+  const cancelAllOrders = (participantId, { symbol = null, side = null } = {}) => {
+    return dispatch({ type: EngineCommand.CANCEL_ALL, participantId, symbol, side })
+  }
+
+  const takeSnapshot = (symbol = null) => {
+    return dispatch({ type: EngineCommand.SNAPSHOT, symbol })
+  }
+
+  // participantId:clientOrderId -> engine order id, so retried submissions aren't placed twice
+  const clientOrderIndex = new Map()
+
+  // participantId -> check(order) returning a refusal message or null. Runs on every order the
+  // participant places, whichever route it came in by (direct, batch, algo, quote, conditional).
+  const preTradeChecks = new Map()
+
+  const setPreTradeCheck = (participantId, check) => {
+    if (check) {
+      preTradeChecks.set(participantId, check)
+    } else {
+      preTradeChecks.delete(participantId)
+    }
+  }
+
+  const handlePlaceOrder = (command) => placeOrderWithTrades(command).orderId
+
+  // Places the order and returns { orderId, trades }: the fills it got on arrival, none for
+  // orders that wait for an auction or the fixing
+  const placeOrderWithTrades = (command) => {
+    const { symbol, side, amount, participantId, orderType, clientOrderId } = command
+    let { price } = command
+
+    const clientKey = clientOrderId ? `${participantId}:${clientOrderId}` : null
+    if (clientKey && clientOrderIndex.has(clientKey)) {
+      return { orderId: clientOrderIndex.get(clientKey), trades: [] }
+    }
+
+    if (!symbols.value.has(symbol)) throw new Error(`Symbol ${symbol} not found`)
+    const poolId = resolvePool(command)
+    const orderbook = getOrderBook(symbol, poolId)
+    if (!orderbook) throw new Error(`Pool ${poolId} not found for ${symbol}`)
+
+    // Refusals past this point are reported to the book's listeners as well as thrown
+    const refuse = (message) => {
+      orderbook.rejectOrder({ symbol, side, amount, price, participantId, orderType, clientOrderId, poolId, error: message }, BookRejectReason.ENGINE)
+      throw new Error(message)
+    }
+
+    // Synthetic flow comes from aggregate agents that aren't registered participants
+    if (!command.synthetic) {
+      const participant = participants.value.get(participantId)
+      if (!participant) refuse(`Participant ${participantId} not found`)
+      const refusal = preTradeChecks.get(participantId)?.({ symbol, side, amount, price, orderType, poolId })
+      if (refusal) refuse(refusal)
+    }
+
+    const auction = auctions.get(symbol)
+    if (orderType === OrderType.MOC || orderType === OrderType.TAS) {
+      if (!(amount > 0)) refuse('Amount must be positive')
+      if (!hasFixing(symbol)) refuse(`No fixing scheduled for ${symbol}`)
+
+      const order = new Order({ symbol, side, amount, price: null, participantId, orderType, clientOrderId })
+      if (orderType === OrderType.MOC && auction?.type === AuctionType.FIX) {
+        order.auctionId = auction.id
+        auction.orders.push(order)
+      } else {
+        closingOrders.push(order)
+      }
+      activeOrders.value.set(order.id, order)
+      indexClientOrder(clientKey, order.id)
+      acceptOrder(order)
+      return { orderId: order.id, trades: [] }
+    }
+    if (command.auction && !auction) refuse(`No auction open for ${symbol}`)
+    if (auction && (auction.halt || command.auction)) {
+      if (orderType !== OrderType.MARKET && orderType !== OrderType.LIMIT) refuse('Only market and limit orders can join an auction')
+      if (!(amount > 0)) refuse('Amount must be positive')
+      if (orderType === OrderType.LIMIT && !(price > 0)) refuse('Limit price must be positive')
+
+      const order = new Order({
+        symbol,
+        side,
+        amount,
+        price: orderType === OrderType.LIMIT ? price : null,
+        participantId,
+        orderType,
+        clientOrderId
+      })
+      order.auctionId = auction.id
+      auction.orders.push(order)
+      activeOrders.value.set(order.id, order)
+      indexClientOrder(clientKey, order.id)
+      acceptOrder(order)
+      return { orderId: order.id, trades: [] }
+    }
+
+    // Calculate price if not provided
+    if (!price) {
+      price = (side === OrderSide.BUY ? orderbook.getBestAsk() : orderbook.getBestBid()) ||
+        getReferencePrice(symbol)
+      if (!price) refuse(`No reference price for ${symbol}`)
+    }
+
+    const order = new Order({
+      symbol,
+      side,
+      amount,
+      price,
+      participantId,
+      orderType,
+      clientOrderId,
+      poolId
+    })
+
+    const arrivalBid = orderbook.getBestBid()
+    const arrivalAsk = orderbook.getBestAsk()
+    const before = config.selfCheck ? orderbook.snapshot() : null
+    const trades = orderbook.addOrder(order)
+    
+    if (trades) {
+      for (const trade of trades) {
+        executeTrade(trade)
+      }
+    }
+    recordOrderOutcome(order, amount, trades || [], arrivalBid, arrivalAsk)
+
+    if (config.selfCheck) {
+      runIntegrityCheck(orderbook, order, before)
+    }
+
+    // A limit remainder the book wouldn't rest (credit kept it from the touch it would cross,
+    // see processLimitOrder) is cancelled rather than left looking active
+    const filled = (trades || []).reduce((sum, trade) => sum + trade.volume, 0)
+    const unrested = orderType === OrderType.LIMIT && filled < amount && !orderbook.orderIndex.has(order.id)
+
+    if (!unrested) activeOrders.value.set(order.id, order)
+    publishQueueUpdates(symbol)
+    evaluateConditionalOrders(symbol)
+    indexClientOrder(clientKey, order.id)
+    acceptOrder(order)
+    if (unrested) emit(EngineEvent.ORDER_CANCELLED, { order, reason: BookRejectReason.WOULD_CROSS })
+    return { orderId: order.id, trades: trades || [] }
+  }
+
+  const acceptOrder = (order) => {
+    getVenueStatsEntry(order.symbol).orders += 1
+    emit(EngineEvent.ORDER_ACCEPTED, { order })
+  }
+
+  const indexClientOrder = (clientKey, orderId) => {
+    if (!clientKey) return
+    clientOrderIndex.set(clientKey, orderId)
+    if (clientOrderIndex.size > 10000) {
+      clientOrderIndex.delete(clientOrderIndex.keys().next().value)
+    }
+  }
+
+  let conditionalCounter = 0
+
+  const handlePlaceConditional = (command) => {
+    const { participantId, order, condition } = command
+    if (!participants.value.has(participantId)) throw new Error(`Participant ${participantId} not found`)
+    const orderError = validateOrderRequest(order)
+    if (orderError) throw new Error(orderError)
+    if (!symbols.value.has(condition?.symbol)) throw new Error(`Condition symbol ${condition?.symbol} not found`)
+    if (!Object.values(ConditionOperator).includes(condition.operator)) {
+      throw new Error(`Invalid condition operator ${condition.operator}`)
+    }
+    if (!(condition.price > 0)) throw new Error('Condition price must be positive')
+
+    const id = `cond_${++conditionalCounter}_${Date.now().toString(36)}`
+    conditionalOrders.value.set(id, {
+      id,
+      participantId,
+      order: { ...order, orderType: order.orderType || OrderType.MARKET },
+      condition: { source: PriceSource.LAST, ...condition },
+      notifyOnly: command.notifyOnly,
+      status: ConditionalStatus.PENDING,
+      createdAt: Date.now(),
+      expiresAt: command.expiresAt,
+      triggeredAt: null,
+      triggerPrice: null,
+      orderId: null
+    })
+
+    // Already satisfied conditions fire straight away
+    evaluateConditionalOrders(condition.symbol)
+    return id
+  }
+
+  const handleCancelConditional = (command) => {
+    const conditional = conditionalOrders.value.get(command.conditionalId)
+    if (!conditional || conditional.status !== ConditionalStatus.PENDING) return false
+    conditional.status = ConditionalStatus.CANCELLED
+    return true
+  }
+
+  const getConditionPrice = (symbol, source) => {
+    const summary = getPoolSummary(symbol)
+    switch (source) {
+      case PriceSource.BID:
+        return summary.bestBid
+      case PriceSource.ASK:
+        return summary.bestAsk
+      case PriceSource.MID:
+        return summary.bestBid && summary.bestAsk ? (summary.bestBid + summary.bestAsk) / 2 : null
+      default:
+        return lastTradePrices.get(symbol) ?? null
+    }
+  }
+
+  // Checks every pending conditional that watches `symbol` (all symbols when null).
+  // Triggered orders are queued as commands, so they run after the current one.
+  const evaluateConditionalOrders = (symbol = null, now = Date.now()) => {
+    for (const conditional of conditionalOrders.value.values()) {
+      if (conditional.status !== ConditionalStatus.PENDING) continue
+      if (symbol && conditional.condition.symbol !== symbol) continue
+
+      if (conditional.expiresAt && conditional.expiresAt <= now) {
+        conditional.status = ConditionalStatus.EXPIRED
+        continue
+      }
+
+      const { operator, price, source } = conditional.condition
+      const current = getConditionPrice(conditional.condition.symbol, source)
+      if (current === null) continue
+      const met = operator === ConditionOperator.ABOVE ? current > price : current < price
+      if (!met) continue
+
+      conditional.status = ConditionalStatus.TRIGGERED
+      conditional.triggeredAt = now
+      conditional.triggerPrice = current
+      emit(EngineEvent.CONDITIONAL_TRIGGERED, { conditional: { ...conditional } })
+
+      if (!conditional.notifyOnly) {
+        const { order } = conditional
+        placeOrder(order.symbol, order.side, order.amount, conditional.participantId, order.orderType, order.price ?? null)
+          .then(orderId => { conditional.orderId = orderId })
+          .catch(error => { conditional.error = error.message })
+      }
+    }
+  }
+
+  const getConditionalOrders = (participantId = null) => {
+    return Array.from(conditionalOrders.value.values())
+      .filter(conditional => !participantId || conditional.participantId === participantId)
+      .map(conditional => ({ ...conditional }))
+  }
+
+  const validateOrderRequest = (order) => {
+    if (!symbols.value.has(order.symbol)) return `Symbol ${order.symbol} not found`
+    if (!Object.values(OrderSide).includes(order.side)) return `Invalid side ${order.side}`
+    if (!(order.amount > 0)) return 'Amount must be positive'
+    const orderType = order.orderType || OrderType.MARKET
+    if (!Object.values(OrderType).includes(orderType)) return `Invalid order type ${orderType}`
+    const priceless = [OrderType.MARKET, OrderType.MOC, OrderType.TAS].includes(orderType)
+    if (!priceless && !(order.price > 0)) return `${orderType} orders need a positive price`
+    return null
+  }
+
+  // Each symbol's orders are validated together and placed back to back in one command.
+  // If one is refused while placing, the group's orders placed before it are cancelled and
+  // the rest aren't sent, so nothing of a failed group is left resting; fills those earlier
+  // orders already got stand and are reported with them. Results keep the input order.
+  const handlePlaceBatch = (command) => {
+    const { orders = [], participantId } = command
+    if (orders.length > config.maxBatchSize) {
+      throw new Error(`Batch of ${orders.length} orders exceeds the limit of ${config.maxBatchSize}`)
+    }
+    if (!participants.value.has(participantId)) {
+      throw new Error(`Participant ${participantId} not found`)
+    }
+
+    const results = new Array(orders.length)
+    const groups = new Map()
+    orders.forEach((order, index) => {
+      if (!groups.has(order.symbol)) groups.set(order.symbol, [])
+      groups.get(order.symbol).push(index)
+    })
+
+    for (const [symbol, indexes] of groups) {
+      const errors = indexes.map(index => validateOrderRequest(orders[index]))
+      if (errors.some(Boolean)) {
+        indexes.forEach((index, i) => {
+          results[index] = {
+            index,
+            success: false,
+            error: errors[i] || `Rejected with the rest of the ${symbol} group`
+          }
+        })
+        continue
+      }
+
+      const placed = []
+      const filled = new Map() // orderId -> amount filled while the group was placed
+      let refusal = null
+      for (const index of indexes) {
+        const order = orders[index]
+        if (refusal) {
+          results[index] = { index, success: false, error: `Not sent after ${refusal}` }
+          continue
+        }
+        try {
+          const { orderId, trades } = placeOrderWithTrades({
+            ...order,
+            orderType: order.orderType || OrderType.MARKET,
+            participantId
+          })
+          // An order of the group can also fill against one placed before it
+          trades.forEach(trade => [trade.buyOrderId, trade.sellOrderId].forEach(id => filled.set(id, (filled.get(id) || 0) + trade.volume)))
+          placed.push(index)
+          results[index] = { index, success: true, orderId, clientOrderId: order.clientOrderId || null }
+        } catch (error) {
+          refusal = `order ${index} in the ${symbol} group was refused`
+          results[index] = { index, success: false, error: error.message }
+        }
+      }
+
+      if (refusal) {
+        placed.forEach(index => {
+          const { orderId, clientOrderId } = results[index]
+          handleCancelOrder({ orderId })
+          results[index] = { index, success: false, orderId, clientOrderId, filledAmount: filled.get(orderId) || 0, error: `Cancelled after ${refusal}` }
+        })
+      }
+    }
+
+    return results
+  }
+
+  const handleCancelOrder = (command) => {
+    const order = activeOrders.value.get(command.orderId)
+    if (!order) return false
+
+    if (closingOrders.includes(order)) {
+      closingOrders.splice(closingOrders.indexOf(order), 1)
+      activeOrders.value.delete(command.orderId)
+      emit(EngineEvent.ORDER_CANCELLED, { order })
+      return true
+    }
+    if (order.auctionId) {
+      const auction = auctions.get(order.symbol)
+      if (auction) auction.orders = auction.orders.filter(entry => entry !== order)
+      activeOrders.value.delete(command.orderId)
+      emit(EngineEvent.ORDER_CANCELLED, { order })
+      return true
+    }
+
+    const orderbook = getOrderBook(order.symbol, order.poolId)
+    const cancelled = orderbook ? orderbook.cancelOrder(command.orderId) : null
+    activeOrders.value.delete(command.orderId)
+
+    if (cancelled) {
+      getVenueStatsEntry(order.symbol).cancels += 1
+      emit(EngineEvent.ORDER_CANCELLED, { order: cancelled })
+      publishQueueUpdates(order.symbol)
+    }
+    return cancelled !== null
+  }
+
+  // Participants whose resting orders get QUEUE_POSITION_CHANGED execution reports
+  const queueTrackedParticipants = new Set()
+  const lastQueuePositions = new Map() // orderId -> { symbol, key } of the last report
+  
+  const trackQueuePositions = (participantId, enabled = true) => {
+    if (enabled) {
+      queueTrackedParticipants.add(participantId)
+    } else {
+      queueTrackedParticipants.delete(participantId)
+    }
+  }
+
+  const getQueuePosition = (orderId) => {
+    const order = activeOrders.value.get(orderId)
+    const orderbook = order ? getOrderBook(order.symbol, order.poolId) : null
+    const queue = orderbook ? orderbook.getQueuePosition(orderId) : null
+    return queue ? { ...queue, poolId: order.poolId || PRIMARY_POOL } : null
+  }
+
+  const getQueuePositions = (participantId, symbol = null) => {
+    const result = []
+    for (const bookSymbol of symbols.value.keys()) {
+      if (symbol && bookSymbol !== symbol) continue
+      for (const [poolId, orderbook] of getPoolBooks(bookSymbol)) {
+        for (const bookSide of [orderbook.bids, orderbook.asks]) {
+          for (const orders of bookSide.values()) {
+            orders
+              .filter(order => order.participantId === participantId)
+              .forEach(order => result.push({ ...orderbook.getQueuePosition(order.id), poolId }))
+          }
+        }
+      }
+    }
+    return result
+  }
+
+  const publishQueueUpdates = (symbol) => {
+    if (queueTrackedParticipants.size === 0) return
+
+    const seen = new Set()
+    for (const participantId of queueTrackedParticipants) {
+      for (const queue of getQueuePositions(participantId, symbol)) {
+        seen.add(queue.orderId)
+        const key = `${queue.position}:${queue.volumeAhead}:${queue.remaining}`
+        if (lastQueuePositions.get(queue.orderId)?.key !== key) {
+          lastQueuePositions.set(queue.orderId, { symbol, key })
+          emit(EngineEvent.QUEUE_POSITION_CHANGED, { participantId, queue })
+        }
+      }
+    }
+
+    // Orders that left the book (filled or cancelled) stop being reported
+    for (const [orderId, last] of lastQueuePositions) {
+      if (last.symbol === symbol && !seen.has(orderId)) {
+        lastQueuePositions.delete(orderId)
+      }
+    }
+  }
+
+  const handleCancelAll = (command) => {
+    const { participantId, symbol, side } = command
+    const cancelled = []
+
+    for (const order of Array.from(activeOrders.value.values())) {
+      if (order.participantId !== participantId) continue
+      if (symbol && order.symbol !== symbol) continue
+      if (side && order.side !== side) continue
+
+      if (handleCancelOrder({ orderId: order.id })) {
+        cancelled.push(order.id)
+      }
+    }
+
+    return { cancelled, count: cancelled.length }
+  }
+
+  // symbol -> the auction collecting its orders; at most one per symbol
+  const auctions = new Map()
+  const auctionResults = ref([])
+  const startedAuctionWindows = new Set() // 'scheduleId:uncrossAt' already opened
+  const closingOrders = [] // MOC and TAS orders waiting for their symbol's next fix
+  let auctionCounter = 0
+
+  // A fix auction is open or scheduled for the symbol, so MOC/TAS orders will execute
+  const hasFixing = (symbol) => {
+    if (auctions.get(symbol)?.type === AuctionType.FIX) return true
+    return !!config.auctions?.enabled && (config.auctions.schedules || []).some(schedule =>
+      schedule.type === AuctionType.FIX && (!schedule.symbols || schedule.symbols.includes(symbol)))
+  }
+
+  const takeClosingOrders = (symbol, orderType) => {
+    const taken = closingOrders.filter(order => order.symbol === symbol && order.orderType === orderType)
+    taken.forEach(order => closingOrders.splice(closingOrders.indexOf(order), 1))
+    return taken
+  }
+
+  const getAuctionTime = () => simClock.value.simTime ?? Date.now()
+
+  const describeAuction = (auction) => ({
+    id: auction.id,
+    symbol: auction.symbol,
+    type: auction.type,
+    scheduleId: auction.scheduleId,
+    halt: auction.halt,
+    startedAt: auction.startedAt,
+    endsAt: auction.endsAt,
+    orders: auction.orders.length,
+    indicative: auction.indicative
+  })
+
+  const openAuction = (symbol, { type, durationMs, halt, scheduleId = null }) => {
+    if (!symbols.value.has(symbol)) throw new Error(`Symbol ${symbol} not found`)
+    if (auctions.has(symbol)) throw new Error(`An auction is already open for ${symbol}`)
+    if (!(durationMs > 0)) throw new Error('Auction duration must be positive')
+
+    const now = getAuctionTime()
+    const auction = {
+      id: `auction_${++auctionCounter}_${symbol}`,
+      symbol,
+      type,
+      scheduleId,
+      halt: !!halt,
+      startedAt: now,
+      endsAt: now + durationMs,
+      orders: [],
+      indicative: null,
+      indicativeAt: null
+    }
+    // Queued market-on-close orders take part in the fix like market orders
+    if (type === AuctionType.FIX) {
+      takeClosingOrders(symbol, OrderType.MOC).forEach(order => {
+        order.auctionId = auction.id
+        auction.orders.push(order)
+      })
+    }
+    auctions.set(symbol, auction)
+    emit(EngineEvent.AUCTION_STARTED, { auction: describeAuction(auction) })
+    return auction.id
+  }
+
+  const publishIndicative = (auction, now) => {
+    auction.indicative = computeUncross(auction.orders, getReferencePrice(auction.symbol))
+    auction.indicativeAt = now
+    emit(EngineEvent.AUCTION_INDICATIVE, {
+      symbol: auction.symbol,
+      auctionId: auction.id,
+      endsAt: auction.endsAt,
+      ...auction.indicative
+    })
+  }
+
+  // Trades everything that crosses at the uncross price. Unfilled limit orders carry over
+  // into continuous trading (where they may still match the book); market orders expire.
+  const closeAuction = (auction) => {
+    auctions.delete(auction.symbol)
+    const { symbol } = auction
+    const book = symbols.value.get(symbol)
+    const uncross = computeUncross(auction.orders, getReferencePrice(symbol))
+    const fills = uncross.matchedVolume > 0 ? allocateUncross(auction.orders, uncross.price, uncross.matchedVolume) : []
+
+    fills.forEach(({ buy, sell, volume }) => {
+      const trade = new Trade({
+        symbol,
+        buyerId: buy.participantId,
+        sellerId: sell.participantId,
+        buyOrderId: buy.id,
+        sellOrderId: sell.id,
+        price: uncross.price,
+        volume,
+        tradeType: 'Auction'
+      })
+      buy.amount -= volume
+      sell.amount -= volume
+      if (book.onMatch) book.onMatch(trade)
+      executeTrade(trade)
+    })
+    if (fills.length > 0) {
+      book.lastTradePrice = uncross.price
+      book.totalVolume += uncross.matchedVolume
+      book.version += 1
+    }
+
+    const settlement = auction.type === AuctionType.FIX ? settleAtFix(symbol, uncross.price ?? getReferencePrice(symbol)) : null
+
+    let carriedOver = 0
+    for (const order of auction.orders) {
+      activeOrders.value.delete(order.id)
+      if (order.amount <= 1e-9) continue
+      order.auctionId = null
+      if (order.orderType === OrderType.LIMIT) {
+        const trades = book.addOrder(order)
+        if (trades) trades.forEach(executeTrade)
+        if (book.orderIndex.has(order.id)) activeOrders.value.set(order.id, order)
+        carriedOver += 1
+      } else {
+        emit(EngineEvent.ORDER_CANCELLED, { order, reason: 'AuctionUnfilled' })
+      }
+    }
+
+    const result = {
+      auctionId: auction.id,
+      symbol,
+      type: auction.type,
+      scheduleId: auction.scheduleId,
+      startedAt: auction.startedAt,
+      endsAt: auction.endsAt,
+      uncrossedAt: getAuctionTime(),
+      ...uncross,
+      orders: auction.orders.length,
+      trades: fills.length,
+      carriedOver,
+      ...settlement
+    }
+    auctionResults.value.push(result)
+    const maxResults = config.auctions?.maxResults ?? 500
+    if (auctionResults.value.length > maxResults) {
+      auctionResults.value = auctionResults.value.slice(-maxResults)
+    }
+    emit(EngineEvent.AUCTION_UNCROSSED, { result })
+    publishQueueUpdates(symbol)
+    evaluateConditionalOrders(symbol)
+    return result
+  }
+
+  // Trade-at-settlement orders trade against each other, in time priority, at the fixing
+  // price; whatever finds no opposite TAS interest expires
+  const settleAtFix = (symbol, fixingPrice) => {
+    const orders = takeClosingOrders(symbol, OrderType.TAS)
+    const volume = (side) => orders.filter(order => order.side === side).reduce((sum, order) => sum + order.amount, 0)
+    const matched = Math.min(volume(OrderSide.BUY), volume(OrderSide.SELL))
+    const fills = fixingPrice > 0 && matched > 0 ? allocateUncross(orders, fixingPrice, matched) : []
+
+    fills.forEach(({ buy, sell, volume: fillVolume }) => {
+      buy.amount -= fillVolume
+      sell.amount -= fillVolume
+      executeTrade(new Trade({
+        symbol,
+        buyerId: buy.participantId,
+        sellerId: sell.participantId,
+        buyOrderId: buy.id,
+        sellOrderId: sell.id,
+        price: fixingPrice,
+        volume: fillVolume,
+        tradeType: 'Settlement'
+      }))
+    })
+    orders.forEach(order => {
+      activeOrders.value.delete(order.id)
+      if (order.amount > 1e-9) emit(EngineEvent.ORDER_CANCELLED, { order, reason: 'SettlementUnmatched' })
+    })
+    return { fixingPrice, settlementVolume: fills.length > 0 ? matched : 0, settlementTrades: fills.length }
+  }
+
+  // Opens scheduled windows that are due, uncrosses auctions whose window has ended and
+  // refreshes the indicative price of the rest
+  const advanceAuctions = () => {
+    const now = getAuctionTime()
+
+    if (config.auctions?.enabled) {
+      const date = new Date(now)
+      for (const schedule of config.auctions.schedules || []) {
+        const [hours, minutes] = schedule.time.split(':').map(Number)
+        const uncrossAt = Date.UTC(date.getUTCFullYear(), date.getUTCMonth(), date.getUTCDate(), hours, minutes)
+        if (!schedule.weekdays.includes(new Date(uncrossAt).getUTCDay())) continue
+        if (now < uncrossAt - schedule.windowMs || now >= uncrossAt) continue
+
+        const key = `${schedule.id}:${uncrossAt}`
+        if (startedAuctionWindows.has(key)) continue
+        startedAuctionWindows.add(key)
+        for (const symbol of schedule.symbols || config.symbols) {
+          if (!symbols.value.has(symbol) || auctions.has(symbol)) continue
+          openAuction(symbol, { type: schedule.type, durationMs: uncrossAt - now, halt: schedule.halt, scheduleId: schedule.id })
+        }
+      }
+    }
+
+    const interval = config.auctions?.indicativeIntervalMs ?? 1000
+    for (const auction of Array.from(auctions.values())) {
+      if (now >= auction.endsAt) {
+        closeAuction(auction)
+      } else if (auction.indicativeAt === null || now - auction.indicativeAt >= interval) {
+        publishIndicative(auction, now)
+      }
+    }
+  }
+
+  const handleStartAuction = (command) => {
+    return openAuction(command.symbol, { ...command, type: command.auctionType })
+  }
+
+  const handleUncrossAuction = (command) => {
+    const auction = auctions.get(command.symbol)
+    if (!auction) throw new Error(`No auction open for ${command.symbol}`)
+    return closeAuction(auction)
+  }
+
+  // Opens an auction outside the schedule, e.g. to test a strategy around a fix
+  const startAuction = (symbol, { type = AuctionType.FIX, durationMs = 60000, halt = false } = {}) => {
+    return dispatch({ type: EngineCommand.START_AUCTION, symbol, auctionType: type, durationMs, halt })
+  }
+
+  // Ends a symbol's auction now instead of at the end of its window
+  const uncrossAuction = (symbol) => {
+    return dispatch({ type: EngineCommand.UNCROSS_AUCTION, symbol })
+  }
+
+  // The open auction for a symbol with its current indicative uncross, or null
+  const getAuction = (symbol) => {
+    const auction = auctions.get(symbol)
+    if (!auction) return null
+    return {
+      ...describeAuction(auction),
+      indicative: computeUncross(auction.orders, getReferencePrice(symbol))
+    }
+  }
+
+  // MOC/TAS orders still waiting for a fix
+  const getClosingOrders = (symbol = null) => {
+    return closingOrders
+      .filter(order => !symbol || order.symbol === symbol)
+      .map(order => ({ ...order }))
+  }
+
+  const getAuctionResults = (symbol = null) => {
+    return symbol
+      ? auctionResults.value.filter(result => result.symbol === symbol)
+      : auctionResults.value.slice()
+  }
+
+  // participantId:symbol -> { bidOrderId, askOrderId } of the participant's live quote
+  const quoteRegistry = new Map()
+
+  const getQuote = (participantId, symbol) => {
+    return quoteRegistry.get(`${participantId}:${symbol}`) || null
+  }
+
+  // Cancel-replace of a participant's two-sided quote per symbol, inside a single command
+  const handleMassQuote = (command) => {
+    const { participantId, quotes = [] } = command
+    if (!participants.value.has(participantId)) {
+      throw new Error(`Participant ${participantId} not found`)
+    }
+
+    return quotes.map(quote => {
+      const { symbol, bidPrice, bidSize = 0, askPrice, askSize = 0 } = quote
+      if (!symbols.value.has(symbol)) {
+        return { symbol, success: false, error: `Symbol ${symbol} not found` }
+      }
+      if ((bidSize > 0 && !(bidPrice > 0)) || (askSize > 0 && !(askPrice > 0))) {
+        return { symbol, success: false, error: 'Quoted sides need a positive price' }
+      }
+      if (bidSize > 0 && askSize > 0 && bidPrice >= askPrice) {
+        return { symbol, success: false, error: `Bid ${bidPrice} must be below ask ${askPrice}` }
+      }
+
+      const key = `${participantId}:${symbol}`
+      const previous = quoteRegistry.get(key)
+      const cancelled = []
+      if (previous) {
+        for (const orderId of [previous.bidOrderId, previous.askOrderId]) {
+          if (orderId && handleCancelOrder({ orderId })) cancelled.push(orderId)
+        }
+      }
+
+      const place = (side, price, amount) => amount > 0
+        ? handlePlaceOrder({ symbol, side, amount, price, participantId, orderType: OrderType.LIMIT })
+        : null
+      const entry = {
+        bidOrderId: place(OrderSide.BUY, bidPrice, bidSize),
+        askOrderId: place(OrderSide.SELL, askPrice, askSize)
+      }
+
+      if (entry.bidOrderId || entry.askOrderId) {
+        quoteRegistry.set(key, entry)
+      } else {
+        quoteRegistry.delete(key)
+      }
+      return { symbol, success: true, ...entry, cancelled }
+    })
+  }
+
+  const handleTick = () => {
+    advanceSimClock()
+    advanceAuctions()
+    advancePopulation()
+    pruneMarketConditions()
+    advanceRegimes()
+    advancePriceProcesses()
+    adjustPriceTargets()
+    evaluateConditionalOrders()
+    sampleVolatility()
+    updateCurrencyStrength()
+    updateMarketStats()
+    sampleVenueStats()
+    publishRetailSentiment()
+
+    if (config.selfCheck) {
+      checkAllOrderBooks()
+    }
+
+    emit(EngineEvent.TICKED, { stats: { ...marketStats.value } })
+  }
+
+  const handleSnapshot = (command) => {
+    const books = command.symbol
+      ? [symbols.value.get(command.symbol)].filter(Boolean)
+      : Array.from(symbols.value.values())
+    const snapshots = books.map(book => book.snapshot())
+
+    emit(EngineEvent.SNAPSHOT_TAKEN, { snapshots })
+    return snapshots
+  }
+
+  const runIntegrityCheck = (orderbook, triggeringOrder, before) => {
+    const violations = orderbook.checkIntegrity()
+    if (violations.length === 0) return true
+
+    const report = {
+      timestamp: Date.now(),
+      symbol: orderbook.symbol,
+      violations,
+      event: { ...triggeringOrder },
+      diff: before ? diffSnapshots(before, orderbook.snapshot()) : null
+    }
+    integrityViolations.value.push(report)
+    if (integrityViolations.value.length > 500) {
+      integrityViolations.value = integrityViolations.value.slice(-250)
+    }
+    console.warn(`Order book integrity violation on ${orderbook.symbol}:`, violations, report.event)
+    return false
+  }
+
+  const checkAllOrderBooks = () => {
+    let healthy = true
+    for (const orderbook of symbols.value.values()) {
+      healthy = runIntegrityCheck(orderbook, { type: 'Tick' }, null) && healthy
+    }
+    return healthy
+  }
+
+  const executeTrade = (trade) => {
+    trade.buyerNo = findParticipant(trade.buyerId)?.no ?? null
+    trade.sellerNo = findParticipant(trade.sellerId)?.no ?? null
+    tradeHistory.value.push(trade)
+    let symbolTrades = tradesBySymbol.get(trade.symbol)
+    if (!symbolTrades) {
+      symbolTrades = []
+      tradesBySymbol.set(trade.symbol, symbolTrades)
+    }
+    symbolTrades.push(trade)
+    lastTradePrices.set(trade.symbol, trade.price)
+    getVenueStatsEntry(trade.symbol).trades += 1
+    marketStats.value.totalTrades += 1
+    marketStats.value.totalVolume += trade.volume
+
+    emit(EngineEvent.TRADE_EXECUTED, { trade })
+    if (config.tapeConflation.enabled) tapeConflator.add(toTapeEntry(trade))
+
+    // Book the fill against each side's lots; only realized P&L touches balance
+    const buyer = participants.value.get(trade.buyerId)
+    const seller = participants.value.get(trade.sellerId)
+
+    if (buyer) {
+      buyer.applyFill(trade, OrderSide.BUY, config.lotMethod)
+    }
+    if (seller) {
+      seller.applyFill(trade, OrderSide.SELL, config.lotMethod)
+    }
+
+    // Keep trade history manageable
+    if (tradeHistory.value.length > 10000) {
+      tradeHistory.value = tradeHistory.value.slice(-5000)
+    }
+    if (symbolTrades.length > 10000) {
+      tradesBySymbol.set(trade.symbol, symbolTrades.slice(-5000))
+    }
+  }
+
+  const sampleTradeSize = (participant) => {
+    return participant.getTypicalTradeSize(config.orderSizes?.[participant.participantType])
+  }
+
+  const simulateBankActivity = async () => {
+    const banks = Array.from(participants.value.values())
+      .filter(p => p.participantType === ParticipantType.BANK && p.active)
+
+    const activeBanks = banks.slice(0, Math.min(config.simulatedBankLimit, banks.length))
+    const seasonalVolume = createSeasonalVolumeLookup()
+
+    for (const bank of activeBanks) {
+      const symbol = getRandomSymbol()
+      const activityScale = loopMetrics.value.activityScale * seasonalVolume(symbol)
+      if (bank.shouldTrade(activityScale * getConditionFactors(symbol).liquidity)) {
+        const side = Math.random() < 0.5 ? OrderSide.BUY : OrderSide.SELL
+        const volume = sampleTradeSize(bank)
+        
+        try {
+          await placeOrder(symbol, side, volume, bank.id, OrderType.LIMIT, getMarketPrice(symbol, side))
+          bank.lastTradeTime = Date.now()
+        } catch (error) {
+          console.warn('Bank trade failed:', error.message)
+        }
+      }
+    }
+  }
+
+  // participantId -> { symbol, orders, fills, lastQuoteAt, lastMid } for quoting agents
+  const quoterStats = new Map()
+
+  // Counts all of a quoter's orders and fills, not only its quotes
+  subscribe((event) => {
+    if (event.type === EngineEvent.ORDER_ACCEPTED) {
+      const stats = quoterStats.get(event.order.participantId)
+      if (stats) stats.orders += 1
+    } else if (event.type === EngineEvent.TRADE_EXECUTED) {
+      for (const id of new Set([event.trade.buyerId, event.trade.sellerId])) {
+        const stats = quoterStats.get(id)
+        if (stats) stats.fills += 1
+      }
+    }
+  })
+
+  const simulateQuoteManagement = async () => {
+    const settings = config.quoteManagement
+    if (!settings?.enabled) return
+
+    const quoters = Array.from(participants.value.values())
+      .filter(p => p.active && settings.strategies?.[p.tradingStrategy])
+      .slice(0, settings.maxQuoters)
+    const symbolList = Array.from(symbols.value.keys())
+    const now = Date.now()
+
+    for (const quoter of quoters) {
+      const profile = settings.strategies[quoter.tradingStrategy]
+      if (!quoterStats.has(quoter.id)) {
+        quoterStats.set(quoter.id, {
+          symbol: symbolList[hashId(quoter.id) % symbolList.length],
+          orders: 0,
+          fills: 0,
+          lastQuoteAt: null,
+          lastMid: null
+        })
+      }
+      const stats = quoterStats.get(quoter.id)
+      const book = symbols.value.get(stats.symbol)
+      const bestBid = book?.getBestBid()
+      const bestAsk = book?.getBestAsk()
+      if (!bestBid || !bestAsk) continue
+
+      const pip = getPipSize(stats.symbol)
+      const mid = (bestBid + bestAsk) / 2
+      const stale = stats.lastQuoteAt === null ||
+        now - stats.lastQuoteAt >= profile.maxQuoteAgeMs ||
+        Math.abs(mid - stats.lastMid) >= profile.requotePips * pip
+      if (!stale || stats.orders >= profile.orderToTradeRatio * Math.max(1, stats.fills)) continue
+
+      const size = sampleTradeSize(quoter) * getSizeScale(stats.symbol)
+      try {
+        await massQuote(quoter.id, [{
+          symbol: stats.symbol,
+          bidPrice: bestBid - profile.offsetPips * pip,
+          bidSize: size,
+          askPrice: bestAsk + profile.offsetPips * pip,
+          askSize: size
+        }])
+        stats.lastQuoteAt = now
+        stats.lastMid = mid
+      } catch (error) {
+        console.warn('Quote update failed:', error.message)
+      }
+    }
+  }
+
+  // Orders sent per fill by each quoting agent, against its configured target
+  const getQuoterStats = () => {
+    return Array.from(quoterStats.entries()).map(([participantId, stats]) => {
+      const participant = participants.value.get(participantId)
+      const profile = config.quoteManagement?.strategies?.[participant?.tradingStrategy]
+      return {
+        participantId,
+        strategy: participant?.tradingStrategy ?? null,
+        symbol: stats.symbol,
+        orders: stats.orders,
+        fills: stats.fills,
+        orderToTradeRatio: stats.fills > 0 ? stats.orders / stats.fills : null,
+        targetRatio: profile?.orderToTradeRatio ?? null
+      }
+    })
+  }
+
+  const simulateTraderActivity = async () => {
+    const traders = Array.from(participants.value.values())
+      .filter(p => [ParticipantType.TRADER, ParticipantType.RETAIL_TRADER].includes(p.participantType) && p.active)
+
+    const activeTraders = traders.slice(0, Math.min(config.simulatedTraderLimit, traders.length))
+    const seasonalVolume = createSeasonalVolumeLookup()
+
+    // Persona retail agents come from beyond the trader slots
+    if (config.retailPersonas?.enabled) {
+      const personas = traders.slice(activeTraders.length).filter(p => p.persona)
+      activeTraders.push(...personas.slice(0, config.retailPersonas.simulatedLimit))
+    }
+
+    for (const trader of activeTraders) {
+      const symbol = getRandomSymbol()
+      const regimeFactor = getStrategyRegimeFactor(symbol, trader.tradingStrategy)
+      const scale = loopMetrics.value.activityScale * seasonalVolume(symbol) * getConditionFactors(symbol).activity * regimeFactor
+      const decision = trader.persona && config.retailPersonas?.enabled
+        ? decidePersonaOrder(trader, symbol, scale)
+        : trader.shouldTrade(scale)
+          ? { symbol, side: pickSide(symbol), volume: sampleTradeSize(trader) * getSizeScale(symbol) }
+          : null
+      if (!decision) continue
+
+      try {
+        await placeOrder(decision.symbol, decision.side, decision.volume, trader.id, OrderType.MARKET)
+        trader.lastTradeTime = Date.now()
+      } catch (error) {
+        console.warn('Trader trade failed:', error.message)
+      }
     }
   }
 
-  const addParticipant = (participant) => {
-    participants.value.set(participant.id, participant)
-    marketStats.value.activeParticipants = participants.value.size
+  // Open positions are managed first: winners past the persona's take profit are banked,
+  // losers past its stop are cut, and other losers may be averaged down. New entries
+  // follow a break of the recent traded range with probability breakoutChase.
+  const decidePersonaOrder = (trader, symbol, activityScale) => {
+    const profile = config.retailPersonas.profiles?.[trader.persona]
+    if (!profile) return null
+    if (Date.now() - trader.lastTradeTime < trader.getMinTradingInterval()) return null
+
+    for (const position of trader.positions.values()) {
+      const mid = symbols.value.get(position.symbol)?.getMidPrice()
+      if (!mid) continue
+      const direction = position.side === OrderSide.BUY ? 1 : -1
+      const pnlPips = direction * (mid - position.entryPrice) / getPipSize(position.symbol)
+      const closeSide = position.side === OrderSide.BUY ? OrderSide.SELL : OrderSide.BUY
+
+      if (pnlPips >= profile.takeProfitPips) {
+        return { symbol: position.symbol, side: closeSide, volume: position.volume, reason: 'takeProfit' }
+      }
+      if (profile.stopLossPips !== null && -pnlPips >= profile.stopLossPips) {
+        return { symbol: position.symbol, side: closeSide, volume: position.volume, reason: 'stopLoss' }
+      }
+      if (pnlPips < 0 && Math.random() < profile.averageDown * activityScale) {
+        return { symbol: position.symbol, side: position.side, volume: sampleTradeSize(trader) * getSizeScale(position.symbol), reason: 'averageDown' }
+      }
+    }
+
+    if (!trader.shouldTrade(activityScale)) return null
+    let side = pickSide(symbol)
+    const breakout = getRangeBreakout(symbol)
+    if (breakout && Math.random() < profile.breakoutChase) side = breakout
+    return { symbol, side, volume: sampleTradeSize(trader) * getSizeScale(symbol), reason: 'entry' }
   }
 
-  const getOrderBook = (symbol) => {
-    return symbols.value.get(symbol)
+  // BUY when the mid is above the recent traded range, SELL below it, else null
+  const getRangeBreakout = (symbol) => {
+    const trades = (tradesBySymbol.get(symbol) || []).slice(-config.retailPersonas.lookbackTrades)
+    const mid = symbols.value.get(symbol)?.getMidPrice()
+    if (trades.length < 10 || !mid) return null
+    const prices = trades.map(trade => trade.price)
+    if (mid > Math.max(...prices)) return OrderSide.BUY
+    if (mid < Math.min(...prices)) return OrderSide.SELL
+    return null
   }
 
-  const placeOrder = async (symbol, side, amount, participantId, orderType = OrderType.MARKET, price = null) => {
-    const orderbook = symbols.value.get(symbol)
-    if (!orderbook) throw new Error(`Symbol ${symbol} not found`)
+  // Per persona: agent count, net exposure and open winners/losers, so the disposition
+  // effect (winners closed, losers held) shows up in the numbers
+  const getPersonaBreakdown = () => {
+    const breakdown = new Map()
+    for (const participant of participants.value.values()) {
+      if (!participant.persona) continue
+      if (!breakdown.has(participant.persona)) {
+        breakdown.set(participant.persona, {
+          persona: participant.persona,
+          agents: 0,
+          activeAgents: 0,
+          openPositions: 0,
+          longVolume: 0,
+          shortVolume: 0,
+          openWinners: 0,
+          openLosers: 0,
+          realizedPnl: 0,
+          closedTrades: 0
+        })
+      }
+      const entry = breakdown.get(participant.persona)
+      entry.agents += 1
+      if (participant.active) entry.activeAgents += 1
+      entry.realizedPnl += participant.realizedPnl
+      entry.closedTrades += participant.ledger.length
+      for (const position of participant.positions.values()) {
+        const mid = symbols.value.get(position.symbol)?.getMidPrice() || position.currentPrice
+        const pnl = (position.side === OrderSide.BUY ? 1 : -1) * (mid - position.entryPrice)
+        entry.openPositions += 1
+        if (position.side === OrderSide.BUY) entry.longVolume += position.volume
+        else entry.shortVolume += position.volume
+        if (pnl > 0) entry.openWinners += 1
+        else if (pnl < 0) entry.openLosers += 1
+      }
+    }
+    return Array.from(breakdown.values())
+  }
 
-    const participant = participants.value.get(participantId)
-    if (!participant) throw new Error(`Participant ${participantId} not found`)
+  // Condition overlays skew agent behaviour for a while: buyBias shifts the taker side
+  // probability, volatility/liquidity/activity multiply quote noise, bank quoting and
+  // taker activity, and meanReversion pulls flow back towards the price at apply time.
+  let conditionCounter = 0
 
-    // Calculate price if not provided
-    if (!price) {
-      price = side === OrderSide.BUY ? orderbook.getBestAsk() || 1.0 : orderbook.getBestBid() || 1.0
-    }
+  const applyMarketCondition = (condition) => {
+    const id = `condition_${++conditionCounter}`
+    const symbol = condition.symbol || null
+    const book = symbol ? symbols.value.get(symbol) : null
 
-    const order = new Order({
+    marketConditions.value.set(id, {
+      id,
+      label: condition.label || 'Condition',
       symbol,
-      side,
-      amount,
-      price,
-      participantId,
-      orderType
+      buyBias: condition.buyBias ?? 0,
+      volatility: condition.volatility ?? 1,
+      liquidity: condition.liquidity ?? 1,
+      activity: condition.activity ?? 1,
+      meanReversion: condition.meanReversion ?? 0,
+      referencePrice: book?.getMidPrice() || config.basePrices[symbol] || null,
+      appliedAt: Date.now(),
+      expiresAt: condition.durationMs ? Date.now() + condition.durationMs : null
     })
+    return id
+  }
 
-    const trades = orderbook.addOrder(order)
-    
-    if (trades) {
-      for (const trade of trades) {
-        executeTrade(trade)
+  const removeMarketCondition = (id) => {
+    return marketConditions.value.delete(id)
+  }
+
+  // How strongly and which way each pair moves when `currency` strengthens. The reference
+  // pair (the first configured pair quoting the currency) moves fully; correlations are
+  // re-oriented so a positive value means "moves with the currency".
+  const getCurrencyImpacts = (currency) => {
+    const shock = config.newsShock
+    const orientation = (symbol) => (symbol.slice(0, 3) === currency ? 1 : symbol.slice(3, 6) === currency ? -1 : 0)
+    const affected = config.symbols.filter(symbol => orientation(symbol) !== 0)
+    if (affected.length === 0) return []
+
+    const reference = affected[0]
+    return affected.map(symbol => {
+      const sign = orientation(symbol)
+      let weight = 1
+      if (symbol !== reference) {
+        const correlation = getCorrelation(reference, symbol) * orientation(reference) * sign
+        weight = correlation !== 0 ? correlation : shock.uncorrelatedWeight
       }
+      return { symbol, sign, weight: Math.min(1, Math.max(shock.minWeight, weight)) }
+    })
+  }
+
+  // A news event on a currency rather than a single pair. direction +1 strengthens the
+  // currency, -1 weakens it; magnitude scales the shock. Returns the conditions it applied.
+  const simulateNewsEvent = ({ currency, direction = null, magnitude = 1, durationMs = null, label = null } = {}) => {
+    const impacts = getCurrencyImpacts(currency)
+    if (impacts.length === 0) return null
+
+    const shock = config.newsShock
+    const sign = direction ?? (Math.random() < 0.5 ? 1 : -1)
+    const scaled = (factor, weight) => 1 + (factor - 1) * weight * magnitude
+    return {
+      currency,
+      direction: sign,
+      magnitude,
+      impacts: impacts.map(impact => ({
+        ...impact,
+        conditionId: applyMarketCondition({
+          label: label || `${currency} news`,
+          symbol: impact.symbol,
+          buyBias: shock.buyBias * impact.sign * sign * impact.weight * magnitude,
+          volatility: scaled(shock.volatility, impact.weight),
+          liquidity: Math.max(0.05, scaled(shock.liquidity, impact.weight)),
+          activity: scaled(shock.activity, impact.weight),
+          durationMs: durationMs ?? shock.durationMs
+        })
+      }))
     }
+  }
 
-    activeOrders.value.set(order.id, order)
-    return order.id
+  const clearMarketConditions = () => {
+    marketConditions.value.clear()
+    regimeStates.value.clear()
   }
 
-  const executeTrade = (trade) => {
-    tradeHistory.value.push(trade)
-    marketStats.value.totalTrades += 1
-    marketStats.value.totalVolume += trade.volume
+  const pruneMarketConditions = (now = Date.now()) => {
+    for (const [id, condition] of marketConditions.value) {
+      if (condition.expiresAt !== null && condition.expiresAt <= now) {
+        marketConditions.value.delete(id)
+      }
+    }
+  }
 
-    // Update participant balances
-    const buyer = participants.value.get(trade.buyerId)
-    const seller = participants.value.get(trade.sellerId)
+  const getConditionFactors = (symbol = null) => {
+    const factors = { buyBias: 0, volatility: 1, liquidity: 1, activity: 1 }
+    const now = Date.now()
 
-    if (buyer) {
-      buyer.balance -= trade.price * trade.volume
-      buyer.updateEquity()
-    }
-    if (seller) {
-      seller.balance += trade.price * trade.volume
-      seller.updateEquity()
+    const overlays = Array.from(marketConditions.value.values())
+    const regime = config.regimes?.enabled ? regimeStates.value.get(symbol) : null
+    if (regime) {
+      overlays.push({
+        ...config.regimes.params[regime.regime],
+        symbol,
+        referencePrice: regime.referencePrice,
+        expiresAt: null
+      })
     }
 
-    // Keep trade history manageable
-    if (tradeHistory.value.length > 10000) {
-      tradeHistory.value = tradeHistory.value.slice(-5000)
+    for (const condition of overlays) {
+      if (condition.expiresAt !== null && condition.expiresAt <= now) continue
+      if (condition.symbol && condition.symbol !== symbol) continue
+
+      factors.buyBias += condition.buyBias ?? 0
+      factors.volatility *= condition.volatility ?? 1
+      factors.liquidity *= condition.liquidity ?? 1
+      factors.activity *= condition.activity ?? 1
+
+      if (condition.meanReversion && condition.referencePrice) {
+        const mid = symbols.value.get(symbol)?.getMidPrice()
+        if (mid) {
+          const deviation = (mid - condition.referencePrice) / condition.referencePrice
+          factors.buyBias -= condition.meanReversion * deviation * 1000
+        }
+      }
     }
+
+    factors.buyBias = Math.max(-0.45, Math.min(0.45, factors.buyBias))
+    return factors
   }
 
-  const simulateBankActivity = async () => {
-    const banks = Array.from(participants.value.values())
-      .filter(p => p.participantType === ParticipantType.BANK && p.active)
+  const enterRegime = (symbol, regime, now = Date.now()) => {
+    const previous = regimeStates.value.get(symbol)
+    const history = previous ? previous.history : []
+    history.push({ regime, since: now })
+    if (history.length > 100) history.shift()
 
-    const activeBanks = banks.slice(0, Math.min(50, banks.length))
+    regimeStates.value.set(symbol, {
+      regime,
+      since: now,
+      referencePrice: symbols.value.get(symbol)?.getMidPrice() || config.basePrices[symbol] || null,
+      history
+    })
+  }
 
-    for (const bank of activeBanks) {
-      if (bank.shouldTrade()) {
-        const symbol = getRandomSymbol()
-        const side = Math.random() < 0.5 ? OrderSide.BUY : OrderSide.SELL
-        const volume = bank.getTypicalTradeSize()
-        
-        try {
-          await placeOrder(symbol, side, volume, bank.id, OrderType.LIMIT, getMarketPrice(symbol, side))
-          bank.lastTradeTime = Date.now()
-        } catch (error) {
-          console.warn('Bank trade failed:', error.message)
+  const advanceRegimes = (now = Date.now()) => {
+    const regimes = config.regimes
+    if (!regimes?.enabled) return
+
+    for (const symbol of symbols.value.keys()) {
+      const state = regimeStates.value.get(symbol)
+      if (!state) {
+        enterRegime(symbol, MarketRegime.RANGE, now)
+        continue
+      }
+
+      // Exponential holding time: switch probability over the elapsed interval
+      const elapsed = now - (state.lastCheckedAt ?? state.since)
+      state.lastCheckedAt = now
+      const meanDuration = regimes.meanDurationMs[state.regime] || 300000
+      if (Math.random() < 1 - Math.exp(-elapsed / meanDuration)) {
+        const next = pickWeighted(regimes.transitions[state.regime])
+        if (next && next !== state.regime) {
+          enterRegime(symbol, next, now)
         }
       }
     }
   }
 
-  const simulateTraderActivity = async () => {
-    const traders = Array.from(participants.value.values())
-      .filter(p => [ParticipantType.TRADER, ParticipantType.RETAIL_TRADER].includes(p.participantType) && p.active)
+  const getStrategyRegimeFactor = (symbol, strategy) => {
+    if (!config.regimes?.enabled) return 1
+    const state = regimeStates.value.get(symbol)
+    return state ? config.regimes.strategyActivity?.[state.regime]?.[strategy] ?? 1 : 1
+  }
 
-    const activeTraders = traders.slice(0, Math.min(200, traders.length))
+  // True regime per symbol, for debugging and post-hoc analysis only
+  const getRegimeDebug = () => {
+    const result = {}
+    for (const [symbol, state] of regimeStates.value) {
+      result[symbol] = {
+        regime: state.regime,
+        since: state.since,
+        durationMs: Date.now() - state.since,
+        params: { ...config.regimes.params[state.regime] },
+        history: state.history.slice()
+      }
+    }
+    return { enabled: !!config.regimes?.enabled, symbols: result }
+  }
 
-    for (const trader of activeTraders) {
-      if (trader.shouldTrade()) {
+  // Folds the current mid into per-symbol bars; ATR uses Wilder smoothing and the
+  // baseline is a slow EMA of ATR
+  const sampleVolatility = (now = Date.now()) => {
+    const model = config.volatilityModel
+    if (!model?.enabled) return
+
+    for (const symbol of symbols.value.keys()) {
+      const mid = getReferencePrice(symbol)
+      if (!mid) continue
+
+      let state = volatilityStates.value.get(symbol)
+      if (!state) {
+        state = { bars: [], atr: null, baselineAtr: null }
+        volatilityStates.value.set(symbol, state)
+      }
+
+      const bar = state.bars[state.bars.length - 1]
+      if (bar && now - bar.start < model.barMs) {
+        bar.high = Math.max(bar.high, mid)
+        bar.low = Math.min(bar.low, mid)
+        bar.close = mid
+        continue
+      }
+
+      if (bar) closeVolatilityBar(state, model)
+      state.bars.push({ start: now, open: mid, high: mid, low: mid, close: mid })
+      if (state.bars.length > model.atrPeriod * 4) state.bars.shift()
+    }
+  }
+
+  const closeVolatilityBar = (state, model) => {
+    const bar = state.bars[state.bars.length - 1]
+    const prev = state.bars[state.bars.length - 2]
+    const trueRange = prev
+      ? Math.max(bar.high - bar.low, Math.abs(bar.high - prev.close), Math.abs(bar.low - prev.close))
+      : bar.high - bar.low
+
+    state.atr = state.atr === null ? trueRange : (state.atr * (model.atrPeriod - 1) + trueRange) / model.atrPeriod
+    const alpha = 2 / (model.baselinePeriod + 1)
+    state.baselineAtr = state.baselineAtr === null ? state.atr : state.baselineAtr + alpha * (state.atr - state.baselineAtr)
+  }
+
+  const getVolatilityRatio = (symbol) => {
+    const state = volatilityStates.value.get(symbol)
+    return state?.baselineAtr > 0 ? state.atr / state.baselineAtr : 1
+  }
+
+  // Multiplier on agent order size for a symbol; 1 when the model is off or still warming up
+  const getSizeScale = (symbol) => {
+    const model = config.volatilityModel
+    if (!model?.enabled) return 1
+    const scale = Math.pow(getVolatilityRatio(symbol), model.sizeElasticity)
+    return Math.min(model.maxSizeScale, Math.max(model.minSizeScale, scale))
+  }
+
+  const getVolatilityLevel = (ratio) => {
+    const levels = config.volatilityModel.levels
+    if (ratio >= levels.extreme) return VolatilityLevel.EXTREME
+    if (ratio >= levels.elevated) return VolatilityLevel.ELEVATED
+    if (ratio <= levels.quiet) return VolatilityLevel.QUIET
+    return VolatilityLevel.NORMAL
+  }
+
+  // Volatility model state for one symbol: ATR, realized vol of bar returns, the observable
+  // level and the intraday seasonal curve. The hidden regime stays in getRegimeDebug.
+  const getVolatilityState = (symbol) => {
+    if (!symbols.value.has(symbol)) return null
+    const state = volatilityStates.value.get(symbol)
+    const bars = state?.bars || []
+
+    const returns = bars.slice(1).map((bar, i) => Math.log(bar.close / bars[i].close))
+    const mean = returns.reduce((sum, r) => sum + r, 0) / (returns.length || 1)
+    const variance = returns.reduce((sum, r) => sum + Math.pow(r - mean, 2), 0) / (returns.length || 1)
+
+    const ratio = getVolatilityRatio(symbol)
+    const curves = { ...config.seasonality, ...config.seasonality?.symbols?.[symbol] }
+    return {
+      symbol,
+      atr: state?.atr ?? null,
+      atrPips: state?.atr != null ? state.atr / getPipSize(symbol) : null,
+      baselineAtr: state?.baselineAtr ?? null,
+      ratio,
+      level: getVolatilityLevel(ratio),
+      realizedVol: Math.sqrt(variance), // per bar, log returns
+      barMs: config.volatilityModel.barMs,
+      bars: bars.length,
+      conditionFactor: getConditionFactors(symbol).volatility,
+      seasonalFactor: getSeasonalFactors(symbol).volatility,
+      intradayCurve: (curves.volatility || []).slice(),
+      sizeScale: getSizeScale(symbol)
+    }
+  }
+
+  const getVolatilitySurface = () => {
+    return Array.from(symbols.value.keys()).map(getVolatilityState)
+  }
+
+  const pickSide = (symbol) => {
+    return Math.random() < 0.5 + getConditionFactors(symbol).buyBias ? OrderSide.BUY : OrderSide.SELL
+  }
+
+  const setAnchorPrice = (symbol, price) => {
+    if (symbols.value.has(symbol) && price > 0) {
+      anchorPrices.value.set(symbol, price)
+    }
+  }
+
+  const clearAnchorPrice = (symbol) => {
+    anchorPrices.value.delete(symbol)
+  }
+
+  const getCorrelation = (symbolA, symbolB) => {
+    if (symbolA === symbolB) return 1
+    return config.correlations[`${symbolA}/${symbolB}`] ??
+      config.correlations[`${symbolB}/${symbolA}`] ??
+      0
+  }
+
+  const getSeasonalFactors = (symbol = null, date = new Date()) => {
+    const seasonality = config.seasonality
+    if (!seasonality?.enabled) return { volume: 1, volatility: 1 }
+
+    const curves = { ...seasonality, ...(symbol ? seasonality.symbols?.[symbol] : null) }
+
+    // Interpolate between hourly points so activity doesn't step on the hour
+    const hour = date.getUTCHours() + date.getUTCMinutes() / 60
+    const interpolate = (curve) => {
+      if (!curve || curve.length !== 24) return 1
+      const index = Math.floor(hour) % 24
+      const next = (index + 1) % 24
+      const weight = hour - Math.floor(hour)
+      return curve[index] * (1 - weight) + curve[next] * weight
+    }
+
+    return {
+      volume: interpolate(curves.volume),
+      volatility: interpolate(curves.volatility)
+    }
+  }
+
+  // Seasonal volume factor by symbol, each looked up once per simulation pass
+  const createSeasonalVolumeLookup = () => {
+    const factors = new Map()
+    return (symbol) => {
+      if (!factors.has(symbol)) factors.set(symbol, getSeasonalFactors(symbol).volume)
+      return factors.get(symbol)
+    }
+  }
+
+  // Representative agents used to size synthesized orders, one per participant type
+  const aggregateAgents = new Map()
+
+  const getUnsimulatedCounts = () => {
+    const counts = { ...(population.value.counts || config.participantCounts) }
+    counts[ParticipantType.BANK] = Math.max(0, counts[ParticipantType.BANK] - config.simulatedBankLimit)
+
+    // Individually simulated traders are drawn from traders first, then retail
+    let traderSlots = config.simulatedTraderLimit
+    for (const type of [ParticipantType.TRADER, ParticipantType.RETAIL_TRADER]) {
+      const simulated = Math.min(traderSlots, counts[type])
+      counts[type] -= simulated
+      traderSlots -= simulated
+    }
+    if (config.retailPersonas?.enabled) {
+      const retail = ParticipantType.RETAIL_TRADER
+      counts[retail] -= Math.min(config.retailPersonas.simulatedLimit, counts[retail])
+    }
+    return counts
+  }
+
+  const synthesizeAggregateFlow = async () => {
+    if (!config.aggregateFlow?.enabled) return
+
+    // Arrivals are drawn at the busiest symbol's seasonal volume and thinned per symbol
+    // below, so each symbol trades at its own intraday profile
+    const seasonalVolume = createSeasonalVolumeLookup()
+    const peakVolume = Math.max(0, ...Array.from(symbols.value.keys()).map(seasonalVolume))
+    const scale = config.aggregateFlow.flowScale *
+      loopMetrics.value.activityScale *
+      peakVolume
+    let budget = config.aggregateFlow.maxOrdersPerTick
+
+    for (const [type, count] of Object.entries(getUnsimulatedCounts())) {
+      if (count <= 0 || budget <= 0) continue
+
+      if (!aggregateAgents.has(type)) {
+        aggregateAgents.set(type, new Participant({
+          id: `aggregate_${type.toLowerCase()}`,
+          no: ++participantNo,
+          name: `${type} Flow`,
+          participantType: type,
+          balance: 0
+        }))
+      }
+      const agent = aggregateAgents.get(type)
+
+      const rate = agent.getTradingProbability() * (config.activityRates?.[type] ?? 1)
+      const arrivals = Math.min(budget, samplePoisson(count * rate * scale))
+      budget -= arrivals
+
+      for (let i = 0; i < arrivals; i++) {
         const symbol = getRandomSymbol()
-        const side = Math.random() < 0.5 ? OrderSide.BUY : OrderSide.SELL
-        const volume = trader.getTypicalTradeSize()
-        
+        if (seasonalVolume(symbol) < peakVolume && Math.random() * peakVolume > seasonalVolume(symbol)) continue
+        const volume = sampleTradeSize(agent) * getSizeScale(symbol)
+        // Banks add passive liquidity, everyone else takes it
+        const isBank = type === ParticipantType.BANK
+        const side = isBank
+          ? (Math.random() < 0.5 ? OrderSide.BUY : OrderSide.SELL)
+          : pickSide(symbol)
         try {
-          await placeOrder(symbol, side, volume, trader.id, OrderType.MARKET)
-          trader.lastTradeTime = Date.now()
+          await dispatch({
+            type: EngineCommand.PLACE_ORDER,
+            symbol,
+            side,
+            amount: volume,
+            price: isBank ? getMarketPrice(symbol, side) : null,
+            participantId: agent.id,
+            orderType: isBank ? OrderType.LIMIT : OrderType.MARKET,
+            synthetic: true
+          })
+          marketStats.value.aggregateOrders += 1
         } catch (error) {
-          console.warn('Trader trade failed:', error.message)
+          console.warn('Aggregate flow order failed:', error.message)
         }
       }
     }
   }
 
+  const ADVERSARY_ID = 'adversary_agent'
+
+  // 'participantId:symbol:side' -> { orders: [{ time, amount }], pattern }
+  const adversarySeries = new Map()
+  let adversaryPositions = [] // open front-runs waiting to be unwound
+  const adversaryStats = { frontRuns: 0, unwinds: 0, anticipated: 0 }
+
+  const detectOrderPattern = (orders) => {
+    const settings = config.adversary
+    if (orders.length < Math.max(3, settings.minRepeats)) return null
+
+    const recent = orders.slice(-Math.max(3, settings.minRepeats))
+    const intervals = recent.slice(1).map((order, i) => order.time - recent[i].time)
+    const mean = intervals.reduce((sum, interval) => sum + interval, 0) / intervals.length
+    if (mean <= 0) return null
+    const variance = intervals.reduce((sum, interval) => sum + (interval - mean) ** 2, 0) / intervals.length
+    if (Math.sqrt(variance) / mean > settings.intervalTolerance) return null
+
+    const sizes = recent.map(order => order.amount)
+    const minSize = Math.min(...sizes)
+    const maxSize = Math.max(...sizes)
+    const last = recent[recent.length - 1]
+    return {
+      intervalMs: mean,
+      nextAt: last.time + mean,
+      amount: last.amount,
+      fixedSize: minSize > 0 && maxSize / minSize - 1 <= settings.sizeTolerance
+    }
+  }
+
+  const observeAdversaryTarget = (event) => {
+    if (event.type !== EngineEvent.ORDER_ACCEPTED || !config.adversary?.enabled) return
+    const order = event.order
+    if (!config.adversary.targets.includes(order.participantId)) return
+
+    const key = `${order.participantId}:${order.symbol}:${order.side}`
+    if (!adversarySeries.has(key)) adversarySeries.set(key, { orders: [], pattern: null })
+    const series = adversarySeries.get(key)
+    series.orders.push({ time: event.timestamp, amount: order.amount })
+    if (series.orders.length > config.adversary.history) series.orders.shift()
+
+    const previous = series.pattern
+    // An order the agent traded ahead of, arriving about when it was predicted
+    if (previous?.frontRunAt && Math.abs(event.timestamp - previous.frontRunAt) <= previous.intervalMs / 2) {
+      adversaryStats.anticipated += 1
+    }
+    const pattern = detectOrderPattern(series.orders)
+    series.pattern = pattern && {
+      participantId: order.participantId,
+      symbol: order.symbol,
+      side: order.side,
+      ...pattern,
+      detectedAt: previous?.detectedAt ?? event.timestamp,
+      frontRunAt: null
+    }
+  }
+  subscribe(observeAdversaryTarget)
+
+  const getAdversary = () => {
+    if (!participants.value.has(ADVERSARY_ID)) {
+      addParticipant(new Participant({
+        id: ADVERSARY_ID,
+        name: 'Adversarial Agent',
+        participantType: ParticipantType.HEDGE_FUND,
+        balance: 0
+      }))
+    }
+    return participants.value.get(ADVERSARY_ID)
+  }
+
+  const placeAdversaryOrder = async (symbol, side, amount) => {
+    await dispatch({
+      type: EngineCommand.PLACE_ORDER,
+      symbol,
+      side,
+      amount,
+      price: null,
+      participantId: ADVERSARY_ID,
+      orderType: OrderType.MARKET
+    })
+  }
+
+  const simulateAdversaryActivity = async () => {
+    const now = Date.now()
+
+    // Unwinds run even when disabled, so turning the agent off leaves it flat
+    const due = adversaryPositions.filter(position => now >= position.unwindAt || !config.adversary?.enabled)
+    adversaryPositions = adversaryPositions.filter(position => !due.includes(position))
+    for (const position of due) {
+      const side = position.side === OrderSide.BUY ? OrderSide.SELL : OrderSide.BUY
+      try {
+        await placeAdversaryOrder(position.symbol, side, position.amount)
+        adversaryStats.unwinds += 1
+      } catch (error) {
+        console.warn('Adversary unwind failed:', error.message)
+      }
+    }
+
+    if (!config.adversary?.enabled) return
+    const agent = getAdversary()
+
+    for (const [key, series] of adversarySeries) {
+      const pattern = series.pattern
+      if (!pattern) continue
+      // The target stopped or moved off its grid
+      if (now > pattern.nextAt + pattern.intervalMs * 2) {
+        adversarySeries.delete(key)
+        continue
+      }
+      if (pattern.frontRunAt || now < pattern.nextAt - config.adversary.leadMs || now >= pattern.nextAt) continue
+
+      const amount = pattern.amount * config.adversary.sizeMultiple
+      pattern.frontRunAt = pattern.nextAt
+      try {
+        await placeAdversaryOrder(pattern.symbol, pattern.side, amount)
+        agent.lastTradeTime = now
+        adversaryPositions.push({ symbol: pattern.symbol, side: pattern.side, amount, unwindAt: now + config.adversary.holdMs })
+        adversaryStats.frontRuns += 1
+      } catch (error) {
+        console.warn('Adversary order failed:', error.message)
+      }
+    }
+  }
+
+  // Switches the agent on or off and/or changes its settings (see config.adversary)
+  const setAdversary = (settings = {}) => {
+    Object.assign(config.adversary, settings)
+    if (!config.adversary.enabled) adversarySeries.clear()
+    return { ...config.adversary }
+  }
+
+  const getAdversaryReport = () => {
+    return {
+      enabled: config.adversary.enabled,
+      patterns: Array.from(adversarySeries.values())
+        .filter(series => series.pattern)
+        .map(series => ({ ...series.pattern })),
+      frontRuns: adversaryStats.frontRuns,
+      anticipated: adversaryStats.anticipated,
+      unwinds: adversaryStats.unwinds,
+      openPositions: adversaryPositions.length,
+      pnl: getPnlSummary(ADVERSARY_ID)
+    }
+  }
+
   const getRandomSymbol = () => {
     const symbolArray = Array.from(symbols.value.keys())
     return symbolArray[Math.floor(Math.random() * symbolArray.length)]
   }
 
+  // Best available fair price: external anchor, price process, book mid, configured base price
+  const getReferencePrice = (symbol) => {
+    return anchorPrices.value.get(symbol) ||
+      getProcessPrice(symbol) ||
+      symbols.value.get(symbol)?.getMidPrice() ||
+      config.basePrices[symbol] ||
+      null
+  }
+
   const getMarketPrice = (symbol, side) => {
     const orderbook = symbols.value.get(symbol)
-    if (!orderbook) return 1.0
+    if (!orderbook) return null
+
+    let basePrice = (side === OrderSide.BUY ? orderbook.getBestAsk() : orderbook.getBestBid()) ||
+      getReferencePrice(symbol)
+    if (!basePrice) return null
+
+    // Quote around an external anchor, or the symbol's price process, so agent depth
+    // follows it; the random walk has no fair value and leaves quotes on the touch
+    const anchor = anchorPrices.value.get(symbol) || getProcessPrice(symbol)
+    if (anchor) {
+      const halfSpread = (orderbook.getSpread() || anchor * 0.0001) / 2
+      basePrice = side === OrderSide.BUY ? anchor + halfSpread : anchor - halfSpread
+    }
 
-    const basePrice = side === OrderSide.BUY 
-      ? orderbook.getBestAsk() || config.basePrices[symbol] || 1.0
-      : orderbook.getBestBid() || config.basePrices[symbol] || 1.0
+    // Quote further from the touch while the market is stressed
+    const widening = getMarketStress(symbol).level * (config.stress?.spreadWideningPips ?? 0) * getPipSize(symbol)
+    basePrice += side === OrderSide.BUY ? -widening : widening
 
-    // Add small random variation
-    const variation = (Math.random() - 0.5) * 0.001
-    return basePrice * (1 + variation)
+    // Add small random variation, scaled by the time-of-day volatility curve and any
+    // active conditions, and lean quotes in the direction of the prevailing flow
+    const factors = getConditionFactors(symbol)
+    const volatility = getSeasonalFactors(symbol).volatility * factors.volatility
+    const variation = (Math.random() - 0.5) * 0.001 * volatility
+    const lean = factors.buyBias * 0.0005 * volatility
+    return basePrice * (1 + variation + lean)
+  }
+
+  // 0..1 stress level: the larger of loop load (how far adaptive throttling has cut
+  // activity) and news (condition volatility towards config.stress.newsVolatility)
+  const getMarketStress = (symbol = null) => {
+    const stress = config.stress
+    if (!stress?.enabled) return { level: 0, load: 0, news: 0 }
+
+    const minScale = config.minActivityScale
+    const load = minScale < 1
+      ? Math.min(1, Math.max(0, (1 - loopMetrics.value.activityScale) / (1 - minScale)))
+      : 0
+    const volatility = getConditionFactors(symbol).volatility
+    const news = stress.newsVolatility > 1
+      ? Math.min(1, Math.max(0, (volatility - 1) / (stress.newsVolatility - 1)))
+      : 0
+    return { level: Math.max(load, news), load, news }
   }
 
   const updateMarketStats = () => {
@@ -728,6 +4255,11 @@ export const useMarketEngineStore = defineStore('marketEngine', () => {
     for (const orderbook of symbols.value.values()) {
       totalLiquidity += orderbook.getTotalVolume()
     }
+    for (const pools of poolBooks.value.values()) {
+      for (const orderbook of pools.values()) {
+        if (config.liquidityPools?.enabled) totalLiquidity += orderbook.getTotalVolume()
+      }
+    }
     marketStats.value.liquidityIndex = totalLiquidity / symbols.value.size
 
     // Calculate volatility from recent trades
@@ -742,17 +4274,258 @@ export const useMarketEngineStore = defineStore('marketEngine', () => {
       }
     }
 
-    marketStats.value.activeParticipants = activeParticipantCount.value
+    marketStats.value.activeParticipants = activeParticipantCount.value
+
+    recordDepthSnapshot()
+  }
+
+  const getVenueStatsEntry = (symbol) => {
+    let entry = venueStats.get(symbol)
+    if (!entry) {
+      entry = {
+        since: Date.now(),
+        samples: 0, // ticks with a two-sided book
+        bidSizeSum: 0,
+        askSizeSum: 0,
+        quotedSpreadSum: 0,
+        quotedSpreadBpsSum: 0,
+        marketableOrders: 0,
+        marketableVolume: 0,
+        filledVolume: 0,
+        fullyFilled: 0,
+        effectiveSpreadSum: 0, // volume-weighted 2 * |fill - arrival mid|
+        effectiveSpreadBpsSum: 0,
+        effectiveVolume: 0,
+        orders: 0,
+        trades: 0,
+        cancels: 0
+      }
+      venueStats.set(symbol, entry)
+    }
+    return entry
+  }
+
+  // Touch size and quoted spread, once per tick on the consolidated book
+  const sampleVenueStats = () => {
+    for (const symbol of symbols.value.keys()) {
+      const snapshot = getMarketDataSnapshot(symbol, 1)
+      const bid = snapshot.depth.bids[0]
+      const ask = snapshot.depth.asks[0]
+      if (!bid || !ask) continue
+      const entry = getVenueStatsEntry(symbol)
+      entry.samples += 1
+      entry.bidSizeSum += bid[1]
+      entry.askSizeSum += ask[1]
+      entry.quotedSpreadSum += ask[0] - bid[0]
+      entry.quotedSpreadBpsSum += (ask[0] - bid[0]) / snapshot.ticker.mid * 10000
+    }
+  }
+
+  // An order is marketable if it could trade on arrival: any market order, or a limit
+  // priced through the opposite touch. Its fills are measured against the arrival mid.
+  const recordOrderOutcome = (order, amount, trades, arrivalBid, arrivalAsk) => {
+    const opposite = order.side === OrderSide.BUY ? arrivalAsk : arrivalBid
+    const marketable = order.orderType === OrderType.MARKET ||
+      (opposite !== null && (order.side === OrderSide.BUY ? order.price >= opposite : order.price <= opposite))
+    if (!marketable) return
+
+    const entry = getVenueStatsEntry(order.symbol)
+    const filled = trades.reduce((sum, trade) => sum + trade.volume, 0)
+    entry.marketableOrders += 1
+    entry.marketableVolume += amount
+    entry.filledVolume += Math.min(filled, amount)
+    if (filled >= amount) entry.fullyFilled += 1
+
+    if (arrivalBid === null || arrivalAsk === null) return
+    const mid = (arrivalBid + arrivalAsk) / 2
+    trades.forEach(trade => {
+      const effective = 2 * Math.abs(trade.price - mid)
+      entry.effectiveSpreadSum += effective * trade.volume
+      entry.effectiveSpreadBpsSum += effective / mid * 10000 * trade.volume
+      entry.effectiveVolume += trade.volume
+    })
+  }
+
+  // Market-quality statistics per symbol since the market started (or the last reset)
+  const getVenueStats = (symbol = null) => {
+    const describe = (name) => {
+      const entry = getVenueStatsEntry(name)
+      const ratio = (a, b) => b > 0 ? a / b : null
+      return {
+        symbol: name,
+        since: entry.since,
+        samples: entry.samples,
+        averageBidSize: ratio(entry.bidSizeSum, entry.samples),
+        averageAskSize: ratio(entry.askSizeSum, entry.samples),
+        averageTopOfBookSize: ratio(entry.bidSizeSum + entry.askSizeSum, 2 * entry.samples),
+        quotedSpread: ratio(entry.quotedSpreadSum, entry.samples),
+        quotedSpreadBps: ratio(entry.quotedSpreadBpsSum, entry.samples),
+        effectiveSpread: ratio(entry.effectiveSpreadSum, entry.effectiveVolume),
+        effectiveSpreadBps: ratio(entry.effectiveSpreadBpsSum, entry.effectiveVolume),
+        marketableOrders: entry.marketableOrders,
+        fillRatio: ratio(entry.filledVolume, entry.marketableVolume),
+        fullFillRate: ratio(entry.fullyFilled, entry.marketableOrders),
+        orders: entry.orders,
+        trades: entry.trades,
+        cancels: entry.cancels,
+        orderToTradeRatio: ratio(entry.orders, entry.trades),
+        cancelToTradeRatio: ratio(entry.cancels, entry.trades)
+      }
+    }
+    return symbol ? describe(symbol) : Array.from(symbols.value.keys()).map(describe)
+  }
+
+  // Trade sizes on the tape (one symbol or all) with the distributions fitted to them: a
+  // lognormal by the moments of log size, and a power-law tail exponent by the Hill
+  // estimator over the largest tailShare of trades. Histogram bins are quarter decades.
+  const getTradeSizeDistribution = (symbol = null, { tailShare = 0.2 } = {}) => {
+    const trades = symbol ? tradesBySymbol.get(symbol) || [] : tradeHistory.value
+    const sizes = trades.map(trade => trade.volume).filter(volume => volume > 0).sort((a, b) => a - b)
+    if (sizes.length === 0) return { symbol, trades: 0 }
+
+    const quantile = (p) => sizes[Math.min(sizes.length - 1, Math.floor(p * sizes.length))]
+    const logs = sizes.map(Math.log)
+    const mu = logs.reduce((sum, value) => sum + value, 0) / logs.length
+    const sigma = Math.sqrt(logs.reduce((sum, value) => sum + (value - mu) ** 2, 0) / Math.max(1, logs.length - 1))
+
+    const tailCount = Math.max(2, Math.floor(sizes.length * tailShare))
+    const tail = sizes.slice(-tailCount)
+    const xmin = tail[0]
+    const logExcess = tail.reduce((sum, size) => sum + Math.log(size / xmin), 0)
+
+    const histogram = new Map()
+    sizes.forEach(size => {
+      const bin = Math.floor(Math.log10(size) * 4)
+      histogram.set(bin, (histogram.get(bin) || 0) + 1)
+    })
+
+    return {
+      symbol,
+      trades: sizes.length,
+      mean: sizes.reduce((sum, size) => sum + size, 0) / sizes.length,
+      quantiles: { p10: quantile(0.1), p50: quantile(0.5), p90: quantile(0.9), p99: quantile(0.99) },
+      lognormal: { mu, sigma, median: Math.exp(mu) },
+      powerLaw: { alpha: logExcess > 0 ? (tail.length - 1) / logExcess : null, xmin, tailTrades: tail.length },
+      histogram: Array.from(histogram.entries())
+        .sort((a, b) => a[0] - b[0])
+        .map(([bin, count]) => ({ from: Math.pow(10, bin / 4), to: Math.pow(10, (bin + 1) / 4), count }))
+    }
+  }
+
+  const resetVenueStats = (symbol = null) => {
+    if (symbol) venueStats.delete(symbol)
+    else venueStats.clear()
+  }
+
+  const recordDepthSnapshot = () => {
+    const timestamp = Date.now()
+    for (const [symbol, orderbook] of symbols.value) {
+      const samples = depthHistory.value.get(symbol) || []
+      samples.push({
+        timestamp,
+        bidDepth: orderbook.getBids(10).reduce((sum, [, volume]) => sum + volume, 0),
+        askDepth: orderbook.getAsks(10).reduce((sum, [, volume]) => sum + volume, 0)
+      })
+      if (samples.length > 600) {
+        samples.splice(0, samples.length - 600)
+      }
+      depthHistory.value.set(symbol, samples)
+    }
+  }
+
+  // Average rate (volume per second) at which a book side has been refilled recently
+  const getReplenishmentRate = (symbol, side) => {
+    const samples = depthHistory.value.get(symbol) || []
+    if (samples.length < 2) return 0
+
+    const key = side === OrderSide.BUY ? 'askDepth' : 'bidDepth'
+    let added = 0
+    for (let i = 1; i < samples.length; i++) {
+      added += Math.max(0, samples[i][key] - samples[i - 1][key])
+    }
+    const elapsedSeconds = (samples[samples.length - 1].timestamp - samples[0].timestamp) / 1000
+    return elapsedSeconds > 0 ? added / elapsedSeconds : 0
+  }
+
+  const estimateMarketImpact = (symbol, side, size) => {
+    const orderbook = symbols.value.get(symbol)
+    if (!orderbook) throw new Error(`Symbol ${symbol} not found`)
+    if (!(size > 0)) throw new Error('Size must be positive')
+
+    const bestBid = orderbook.getBestBid()
+    const bestAsk = orderbook.getBestAsk()
+    const touch = side === OrderSide.BUY ? bestAsk : bestBid
+    const mid = bestBid && bestAsk ? (bestBid + bestAsk) / 2 : touch
+
+    const fill = orderbook.simulateFill(side, size)
+    const direction = side === OrderSide.BUY ? 1 : -1
+    const replenishmentRate = getReplenishmentRate(symbol, side)
+
+    // Whatever the visible book can't absorb waits for new liquidity to arrive
+    let estimatedTimeToFillMs = 0
+    if (fill.unfilled > 0) {
+      estimatedTimeToFillMs = replenishmentRate > 0
+        ? (fill.unfilled / replenishmentRate) * 1000
+        : null
+    }
+
+    return {
+      symbol,
+      side,
+      size,
+      bestPrice: touch,
+      midPrice: mid,
+      expectedVwap: fill.vwap,
+      worstPrice: fill.worstPrice,
+      slippageFromTouch: fill.vwap && touch ? (fill.vwap - touch) * direction : null,
+      slippageFromMid: fill.vwap && mid ? (fill.vwap - mid) * direction : null,
+      immediateFillRatio: fill.filled / size,
+      levelsConsumed: fill.levelsConsumed,
+      replenishmentRate,
+      estimatedTimeToFillMs
+    }
   }
 
   const initializeMarket = () => {
     console.log('Initializing market...')
+
+    const populationErrors = validatePopulationConfig(config)
+    if (populationErrors.length > 0) {
+      throw new Error(`Invalid population config: ${populationErrors.join('; ')}`)
+    }
     
     // Clear existing data
     symbols.value.clear()
     participants.value.clear()
     activeOrders.value.clear()
     tradeHistory.value.length = 0
+    tradesBySymbol.clear()
+    venueStats.clear()
+    retailSentiment.value = { symbols: [], updatedAt: null }
+    depthHistory.value.clear()
+    poolBooks.value.clear()
+    volatilityStates.value.clear()
+    conditionalOrders.value.clear()
+    priceProcessStates.value.clear()
+    priceTargets.value.clear()
+    resetCurrencyStrength()
+    lastTradePrices.clear()
+    creditUsage.value.clear()
+    marketConditions.value.clear()
+    marketDataCache.clear()
+    auctions.clear()
+    startedAuctionWindows.clear()
+    closingOrders.length = 0
+    auctionResults.value = []
+    adversarySeries.clear()
+    adversaryPositions = []
+    quoterStats.clear()
+    bookTickers.clear()
+    recentOrdersByParticipant.clear()
+    participantNo = 0
+    aggregateAgents.clear()
+    redundantFeeds.reset()
+    tapeConflator.clear()
 
     // Add symbols
     config.symbols.forEach(symbol => {
@@ -770,24 +4543,14 @@ export const useMarketEngineStore = defineStore('marketEngine', () => {
     addParticipant(userParticipant)
 
     // Generate market participants
-    let participantCounter = 0
+    agentCounter = 0
     
     Object.entries(config.participantCounts).forEach(([type, count]) => {
-      const balanceRange = config.balanceRanges[type]
-      
       for (let i = 0; i < count; i++) {
-        const balance = balanceRange.min + Math.random() * (balanceRange.max - balanceRange.min)
-        
-        const participant = new Participant({
-          id: `${type.toLowerCase()}_${participantCounter++}`,
-          name: `${type} ${i + 1}`,
-          participantType: type,
-          balance: balance
-        })
-        
-        addParticipant(participant)
+        addParticipant(createAgent(type, i))
       }
     })
+    population.value = { counts: { ...config.participantCounts }, ramp: null }
 
     // Initialize some liquidity in orderbooks
     initializeLiquidity()
@@ -795,51 +4558,323 @@ export const useMarketEngineStore = defineStore('marketEngine', () => {
     console.log(`Market initialized with ${symbols.value.size} symbols and ${participants.value.size} participants`)
   }
 
-  const initializeLiquidity = () => {
-    // Add initial orders to create liquidity
-    const banks = Array.from(participants.value.values())
-      .filter(p => p.participantType === ParticipantType.BANK)
-      .slice(0, 50)
+  let agentCounter = 0
+
+  const createAgent = (type, index) => {
+    return new Participant({
+      id: `${type.toLowerCase()}_${agentCounter++}`,
+      nameIndex: index,
+      participantType: type,
+      balance: sampleBalance(config.balanceRanges[type]),
+      tradingStrategy: pickWeighted(config.strategyMix?.[type]),
+      activityRate: config.activityRates?.[type],
+      persona: type === ParticipantType.RETAIL_TRADER && config.retailPersonas?.enabled
+        ? pickWeighted(config.retailPersonas.mix)
+        : null
+    })
+  }
+
+  // Moves the active population towards `counts` per type, or `scale` times the configured
+  // counts, ramping linearly over rampSeconds so liquidity thins or builds gradually.
+  // Types left out of `counts` keep their current size.
+  const scalePopulation = ({ scale = null, counts = null, rampSeconds = config.populationScaling.rampSeconds } = {}) => {
+    const errors = []
+    if (scale === null && !counts) errors.push('Either scale or counts is required')
+    if (scale !== null && !(scale >= 0 && Number.isFinite(scale))) errors.push('Scale must be a non-negative number')
+    Object.entries(counts || {}).forEach(([type, count]) => {
+      if (!Object.values(ParticipantType).includes(type)) errors.push(`Unknown participant type ${type}`)
+      else if (!Number.isInteger(count) || count < 0) errors.push(`${type}: count must be a non-negative integer`)
+    })
+    if (!(rampSeconds >= 0)) errors.push('Ramp must be a non-negative number of seconds')
+    if (errors.length > 0) return { success: false, errors }
+
+    const current = getPopulationCounts()
+    const targets = { ...current }
+    Object.values(ParticipantType).forEach(type => {
+      if (counts?.[type] !== undefined) targets[type] = counts[type]
+      else if (scale !== null) targets[type] = Math.round(config.participantCounts[type] * scale)
+    })
+
+    population.value.ramp = {
+      from: current,
+      targets,
+      startedAt: Date.now(),
+      durationMs: rampSeconds * 1000
+    }
+    advancePopulation()
+    return { success: true, errors: [], targets }
+  }
+
+  const getPopulationCounts = () => ({ ...(population.value.counts || config.participantCounts) })
+
+  // Applies the ramp's current step: deactivates the newest agents of a shrinking type
+  // (pulling their resting orders), re-activates or creates agents for a growing one
+  const advancePopulation = () => {
+    const ramp = population.value.ramp
+    if (!ramp) return
+
+    const progress = ramp.durationMs > 0 ? Math.min(1, (Date.now() - ramp.startedAt) / ramp.durationMs) : 1
+    const counts = getPopulationCounts()
+    let budget = config.populationScaling.maxChangePerTick
+    const retired = new Set()
+
+    for (const type of Object.keys(ramp.targets)) {
+      const desired = Math.round(ramp.from[type] + (ramp.targets[type] - ramp.from[type]) * progress)
+      const change = Math.max(-budget, Math.min(budget, desired - counts[type]))
+      if (change === 0) continue
+      budget -= Math.abs(change)
+
+      const agents = Array.from(participants.value.values())
+        .filter(p => p.participantType === type && p.id !== 'user_trader')
+      if (change < 0) {
+        agents.filter(p => p.active).slice(change).forEach(agent => {
+          agent.active = false
+          retired.add(agent.id)
+        })
+      } else {
+        const inactive = agents.filter(p => !p.active).slice(0, change)
+        inactive.forEach(agent => { agent.active = true })
+        for (let i = inactive.length; i < change; i++) {
+          addParticipant(createAgent(type, agents.length + i - inactive.length))
+        }
+      }
+      counts[type] += change
+    }
+    population.value.counts = counts
 
+    if (retired.size > 0) {
+      for (const order of Array.from(activeOrders.value.values())) {
+        if (retired.has(order.participantId)) handleCancelOrder({ orderId: order.id })
+      }
+    }
+
+    const done = Object.keys(ramp.targets).every(type => counts[type] === ramp.targets[type])
+    if (done && progress >= 1) population.value.ramp = null
+  }
+
+  const getPopulation = () => {
+    const ramp = population.value.ramp
+    return {
+      configured: { ...config.participantCounts },
+      active: getPopulationCounts(),
+      ramp: ramp
+        ? {
+            targets: { ...ramp.targets },
+            progress: ramp.durationMs > 0 ? Math.min(1, (Date.now() - ramp.startedAt) / ramp.durationMs) : 1,
+            startedAt: ramp.startedAt,
+            durationMs: ramp.durationMs
+          }
+        : null
+    }
+  }
+
+  const initializeLiquidity = () => {
     for (const symbol of config.symbols) {
-      const basePrice = config.basePrices[symbol] || 1.0
-      
-      banks.forEach((bank, index) => {
-        // Create bid orders
-        const bidPrice = basePrice * (1 - (Math.random() * 0.002))
-        const bidVolume = bank.getTypicalTradeSize() * 0.1
-        
-        placeOrder(symbol, OrderSide.BUY, bidVolume, bank.id, OrderType.LIMIT, bidPrice)
-          .catch(() => {}) // Ignore errors during initialization
-        
-        // Create ask orders
-        const askPrice = basePrice * (1 + (Math.random() * 0.002))
-        const askVolume = bank.getTypicalTradeSize() * 0.1
-        
-        placeOrder(symbol, OrderSide.SELL, askVolume, bank.id, OrderType.LIMIT, askPrice)
-          .catch(() => {}) // Ignore errors during initialization
-      })
+      for (const poolId of getActivePoolIds()) {
+        seedOrderBook(symbol, null, poolId)
+      }
+    }
+  }
+
+  // Places `levels` bid and ask levels around the reference price, sizes growing with distance
+  const seedOrderBook = (symbol, referencePrice = null, poolId = PRIMARY_POOL) => {
+    const orderbook = getOrderBook(symbol, poolId)
+    const seeding = config.bookSeeding
+    const mid = referencePrice || orderbook?.getMidPrice() || getReferencePrice(symbol)
+    if (!orderbook || !seeding || !mid) return 0
+
+    const makers = Array.from(participants.value.values())
+      .filter(p => p.participantType === ParticipantType.BANK && p.active)
+      .filter(p => poolId === PRIMARY_POOL || getParticipantPools(p.id).includes(poolId))
+      .slice(0, seeding.makers)
+    if (makers.length === 0) return 0
+
+    const pip = getPipSize(symbol)
+    const decimals = getPriceDecimals(symbol)
+    let placed = 0
+
+    for (let level = 0; level < seeding.levels; level++) {
+      const offset = (seeding.halfSpreadPips + level * seeding.levelSpacingPips) * pip
+      const size = seeding.baseSize * Math.pow(seeding.sizeGrowth, level)
+      const maker = makers[level % makers.length]
+
+      const bidPrice = Number((mid - offset).toFixed(decimals))
+      const askPrice = Number((mid + offset).toFixed(decimals))
+      // Never seed through the existing book when topping up a one-sided book
+      const bestBid = orderbook.getBestBid()
+      const bestAsk = orderbook.getBestAsk()
+
+      if (bestAsk === null || bidPrice < bestAsk) {
+        placeOrder(symbol, OrderSide.BUY, size, maker.id, OrderType.LIMIT, bidPrice, { poolId })
+          .catch(error => console.warn('Book seeding failed:', error.message))
+        placed += 1
+      }
+      if (bestBid === null || askPrice > bestBid) {
+        placeOrder(symbol, OrderSide.SELL, size, maker.id, OrderType.LIMIT, askPrice, { poolId })
+          .catch(error => console.warn('Book seeding failed:', error.message))
+        placed += 1
+      }
+    }
+
+    return placed
+  }
+
+  // Tops up books that were drained while the simulation was halted
+  const reseedThinBooks = () => {
+    const minLevels = config.bookSeeding?.minLevels ?? 1
+    const reseeded = []
+
+    for (const symbol of symbols.value.keys()) {
+      for (const [poolId, orderbook] of getPoolBooks(symbol)) {
+        if (orderbook.bids.size < minLevels || orderbook.asks.size < minLevels) {
+          if (seedOrderBook(symbol, null, poolId) > 0) {
+            reseeded.push(poolId === PRIMARY_POOL ? symbol : `${symbol}@${poolId}`)
+          }
+        }
+      }
+    }
+    return reseeded
+  }
+
+  const recordTick = (duration, tickInterval) => {
+    const metrics = loopMetrics.value
+    metrics.ticks += 1
+    metrics.lastTickAt = Date.now()
+    metrics.lastDurationMs = duration
+    metrics.averageDurationMs += (duration - metrics.averageDurationMs) / Math.min(metrics.ticks, 100)
+
+    if (duration > tickInterval) {
+      metrics.overruns += 1
+      metrics.totalOverrunMs += duration - tickInterval
+    }
+
+    if (!config.adaptiveThrottling) {
+      metrics.activityScale = 1
+      return
+    }
+
+    // Back off agent activity while the loop overruns, recover slowly when idle
+    if (duration > tickInterval) {
+      metrics.activityScale = Math.max(config.minActivityScale, metrics.activityScale * 0.8)
+    } else if (duration < tickInterval * 0.5) {
+      metrics.activityScale = Math.min(1, metrics.activityScale * 1.05)
+    }
+  }
+
+  const resetLoopMetrics = () => {
+    loopMetrics.value = {
+      ticks: 0,
+      lastDurationMs: 0,
+      averageDurationMs: 0,
+      overruns: 0,
+      totalOverrunMs: 0,
+      skippedCohorts: 0,
+      activityScale: 1,
+      lastTickAt: null
+    }
+  }
+
+  const setTickInterval = (intervalMs) => {
+    if (!Number.isFinite(intervalMs) || intervalMs < 1) return false
+    config.updateFrequency = intervalMs
+    return true
+  }
+
+  // Liveness of the simulation loop: stalled if no tick for several intervals
+  const getHealth = () => {
+    const tickInterval = config.updateFrequency / simulationSpeed.value
+    const lastTickAgeMs = loopMetrics.value.lastTickAt
+      ? Date.now() - loopMetrics.value.lastTickAt
+      : null
+
+    let status = 'stopped'
+    if (isRunning.value) {
+      status = lastTickAgeMs !== null && lastTickAgeMs <= Math.max(tickInterval * 10, 1000)
+        ? 'ok'
+        : 'stalled'
+    }
+
+    const participantCounts = {}
+    for (const participant of participants.value.values()) {
+      participantCounts[participant.participantType] = (participantCounts[participant.participantType] || 0) + 1
+    }
+
+    return {
+      status,
+      running: isRunning.value,
+      lastTickAgeMs,
+      ticks: loopMetrics.value.ticks,
+      participants: participants.value.size,
+      participantCounts,
+      pendingCommands: commandQueue.length,
+      eventSubscribers: eventSubscribers.size
+    }
+  }
+
+  // Readiness: books seeded with two-sided prices and population created
+  const getReadiness = () => {
+    const checks = {
+      symbols: symbols.value.size > 0,
+      participants: participants.value.size > 0,
+      booksSeeded: Array.from(symbols.value.values())
+        .every(book => book.getBestBid() !== null && book.getBestAsk() !== null)
+    }
+    return {
+      ready: Object.values(checks).every(Boolean),
+      checks
+    }
+  }
+
+  // One tick of agent flow. The live loop calls this on a timer; headless runs
+  // (scripts/ab-experiment.js) call it directly.
+  const runSimulationStep = async () => {
+    releaseHeldCommands()
+    await simulateBankActivity()
+    await simulateQuoteManagement()
+
+    // Under heavy throttling retail/trader flow only runs every other tick
+    const skipTraders = config.adaptiveThrottling &&
+      loopMetrics.value.activityScale < 0.5 &&
+      loopMetrics.value.ticks % 2 === 1
+    if (skipTraders) {
+      loopMetrics.value.skippedCohorts += 1
+    } else {
+      await simulateTraderActivity()
     }
+
+    await synthesizeAggregateFlow()
+    await simulateAdversaryActivity()
+
+    await dispatch({ type: EngineCommand.TICK })
   }
 
   const startSimulation = () => {
     if (isRunning.value) return
 
     isRunning.value = true
+
+    const reseeded = reseedThinBooks()
+    if (reseeded.length > 0) {
+      console.log(`Re-seeded order books: ${reseeded.join(', ')}`)
+    }
     
     const update = async () => {
       if (!isRunning.value) return
 
+      const tickStart = performance.now()
+      const tickInterval = config.updateFrequency / simulationSpeed.value
+
       try {
-        await simulateBankActivity()
-        await simulateTraderActivity()
-        updateMarketStats()
+        await runSimulationStep()
       } catch (error) {
         console.error('Simulation update error:', error)
       }
 
+      const duration = performance.now() - tickStart
+      recordTick(duration, tickInterval)
+
       if (isRunning.value) {
-        updateInterval.value = setTimeout(update, config.updateFrequency / simulationSpeed.value)
+        updateInterval.value = setTimeout(update, Math.max(0, tickInterval - duration))
       }
     }
 
@@ -853,23 +4888,477 @@ export const useMarketEngineStore = defineStore('marketEngine', () => {
       clearTimeout(updateInterval.value)
       updateInterval.value = null
     }
+    // Nothing will step again to apply them
+    releaseHeldCommands()
     console.log('Market simulation stopped')
   }
 
   const resetMarket = () => {
     stopSimulation()
+    resetLoopMetrics()
     initializeMarket()
   }
 
   const updateConfig = (newConfig) => {
+    const errors = validatePopulationConfig({ ...config, ...newConfig })
+    if (errors.length > 0) {
+      return { success: false, errors }
+    }
+
     Object.assign(config, newConfig)
+    return { success: true, errors: [] }
+  }
+
+  // Applied live configuration changes, oldest first: { seq, timestamp, source, diff }
+  const configChangeLog = ref([])
+  let configChangeSeq = 0
+
+  const isPlainObject = (value) => value !== null && typeof value === 'object' && !Array.isArray(value)
+
+  // Nested objects merge key by key; arrays and scalars are replaced
+  const mergeSettings = (base, changes) => {
+    const merged = { ...base }
+    Object.entries(changes).forEach(([key, value]) => {
+      merged[key] = isPlainObject(value) && isPlainObject(base?.[key]) ? mergeSettings(base[key], value) : value
+    })
+    return merged
+  }
+
+  // [{ path, from, to }] for every leaf that differs
+  const diffSettings = (before, after, path = '') => {
+    const keys = new Set([...Object.keys(before || {}), ...Object.keys(after || {})])
+    const changes = []
+    for (const key of keys) {
+      const from = before?.[key]
+      const to = after?.[key]
+      const at = path ? `${path}.${key}` : key
+      if (isPlainObject(from) && isPlainObject(to)) changes.push(...diffSettings(from, to, at))
+      else if (JSON.stringify(from) !== JSON.stringify(to)) changes.push({ path: at, from, to })
+    }
+    return changes
+  }
+
+  // Hot reload: merges `changes` into the running config and applies what can change live
+  // (price processes, matching, activity rates, population counts; everything read per
+  // tick takes effect on the next one). Nothing is applied if any check fails: unknown
+  // settings, a value of the wrong type, population rules or an invalid price process.
+  // Accepted changes are logged with their diff, see getConfigChangeLog.
+  const reloadConfig = (changes, { source = 'api' } = {}) => {
+    if (!isPlainObject(changes)) return { success: false, errors: ['Changes must be an object'], diff: [] }
+
+    const errors = []
+    Object.keys(changes).filter(key => !(key in config)).forEach(key => errors.push(`Unknown setting ${key}`))
+    const merged = mergeSettings(config, changes)
+    const diff = diffSettings(config, merged)
+    diff.forEach(({ path, from, to }) => {
+      if (from !== undefined && from !== null && to !== undefined && typeof from !== typeof to) {
+        errors.push(`${path}: expected ${typeof from}, got ${typeof to}`)
+      }
+    })
+    errors.push(...validatePopulationConfig(merged))
+    Object.entries(merged.priceProcesses || {}).forEach(([symbol, settings]) => {
+      try {
+        createPriceProcess(settings, config.basePrices[symbol] || 1)
+      } catch (error) {
+        errors.push(`priceProcesses.${symbol}: ${error.message}`)
+      }
+    })
+    if (errors.length > 0) return { success: false, errors, diff }
+    if (diff.length === 0) return { success: true, errors: [], diff }
+
+    const changedTop = new Set(diff.map(({ path }) => path.split('.')[0]))
+    const processBefore = Object.fromEntries(Array.from(symbols.value.keys()).map(symbol => [symbol, JSON.stringify(getPriceProcessSettings(symbol))]))
+    changedTop.forEach(key => { config[key] = merged[key] })
+
+    if (changedTop.has('priceProcesses')) {
+      for (const symbol of symbols.value.keys()) {
+        if (JSON.stringify(getPriceProcessSettings(symbol)) !== processBefore[symbol]) initPriceProcess(symbol)
+      }
+    }
+    if (changedTop.has('matchingAlgorithms') || changedTop.has('proRataLot')) {
+      getAllBooks().forEach(applyMatchingAlgorithm)
+    }
+    if (changedTop.has('activityRates')) {
+      for (const participant of participants.value.values()) {
+        const rate = config.activityRates?.[participant.participantType]
+        if (rate !== undefined && participant.id !== 'user_trader') participant.activityRate = rate
+      }
+    }
+    if (changedTop.has('participantCounts') && participants.value.size > 0) {
+      scalePopulation({ counts: { ...config.participantCounts } })
+    }
+
+    const entry = { seq: ++configChangeSeq, timestamp: Date.now(), source, diff }
+    configChangeLog.value.push(entry)
+    if (configChangeLog.value.length > 500) configChangeLog.value.shift()
+    console.log(`Config reloaded from ${source}: ${diff.map(({ path }) => path).join(', ')}`)
+    return { success: true, errors: [], diff, seq: entry.seq }
+  }
+
+  const getConfigChangeLog = ({ sinceSeq = 0 } = {}) => {
+    return configChangeLog.value.filter(entry => entry.seq > sinceSeq)
   }
 
   const getRecentTrades = (symbol, limit = 100) => {
-    return tradeHistory.value
-      .filter(t => t.symbol === symbol)
+    return (tradesBySymbol.get(symbol) || []).slice(-limit).reverse()
+  }
+
+  // Paged listings in the shared query shape (see listQuery.js), newest first by default
+  const listTrades = (query = {}) => {
+    const symbol = typeof query.filter?.symbol === 'string' ? query.filter.symbol : null
+    const trades = symbol ? tradesBySymbol.get(symbol) || [] : Array.from(tradesBySymbol.values()).flat()
+    return queryList(trades, { sort: '-timestamp', ...query })
+  }
+
+  const listOrders = (query = {}) => {
+    return queryList(Array.from(activeOrders.value.values()), { sort: '-timestamp', ...query })
+  }
+
+  const listParticipants = (query = {}) => {
+    const summaries = Array.from(participants.value.values()).map(p => ({
+      id: p.id,
+      name: p.name,
+      participantType: p.participantType,
+      tradingStrategy: p.tradingStrategy,
+      persona: p.persona,
+      balance: p.balance,
+      equity: p.equity,
+      realizedPnl: p.realizedPnl,
+      leverage: p.leverage,
+      active: p.active
+    }))
+    return queryList(summaries, { sort: 'id', ...query })
+  }
+
+  const creditLimits = new Map() // pair key -> explicit bilateral limit
+  const creditUsage = ref(new Map()) // pair key -> notional traded since the last reset
+
+  const creditPairKey = (a, b) => a < b ? `${a}|${b}` : `${b}|${a}`
+
+  const getCreditLimit = (a, b) => {
+    const key = creditPairKey(a, b)
+    if (creditLimits.has(key)) return creditLimits.get(key)
+
+    const lines = config.credit?.creditLines || {}
+    const lineA = lines[getParticipantType(a)] ?? Infinity
+    const lineB = lines[getParticipantType(b)] ?? Infinity
+    return Math.min(lineA, lineB)
+  }
+
+  const getAvailableCredit = (a, b) => {
+    if (a === b || !config.credit?.enabled) return Infinity
+    return Math.max(0, getCreditLimit(a, b) - (creditUsage.value.get(creditPairKey(a, b)) || 0))
+  }
+
+  const setCreditLimit = (a, b, limit) => {
+    if (!(limit >= 0)) return false
+    creditLimits.set(creditPairKey(a, b), limit)
+    return true
+  }
+
+  const clearCreditLimit = (a, b) => {
+    return creditLimits.delete(creditPairKey(a, b))
+  }
+
+  const resetCreditUsage = () => {
+    creditUsage.value.clear()
+  }
+
+  // Counterparties a participant has used credit with, largest usage first
+  const getCreditReport = (participantId) => {
+    const report = []
+    for (const [key, used] of creditUsage.value) {
+      const [a, b] = key.split('|')
+      if (a !== participantId && b !== participantId) continue
+      const counterparty = a === participantId ? b : a
+      const limit = getCreditLimit(participantId, counterparty)
+      report.push({ counterparty, counterpartyType: getParticipantType(counterparty), limit, used, available: Math.max(0, limit - used) })
+    }
+    return report.sort((x, y) => y.used - x.used)
+  }
+
+  const attachCreditHooks = (orderbook) => {
+    orderbook.matchLimit = (incoming, resting, price) => {
+      const available = getAvailableCredit(incoming.participantId, resting.participantId)
+      return available === Infinity ? Infinity : available / price
+    }
+    orderbook.onMatch = (trade) => {
+      if (!config.credit?.enabled || trade.buyerId === trade.sellerId) return
+      const key = creditPairKey(trade.buyerId, trade.sellerId)
+      creditUsage.value.set(key, (creditUsage.value.get(key) || 0) + trade.volume * trade.price)
+    }
+  }
+
+  const getPoolDepth = (symbol, poolId = PRIMARY_POOL, depth = 10) => {
+    const orderbook = getOrderBook(symbol, poolId)
+    return orderbook ? { bids: orderbook.getBids(depth), asks: orderbook.getAsks(depth) } : { bids: [], asks: [] }
+  }
+
+  // Depth summed across all active pools, as an aggregating broker would see it
+  const getConsolidatedDepth = (symbol, depth = 10) => {
+    const merge = (side) => {
+      const levels = new Map()
+      for (const [, orderbook] of getPoolBooks(symbol)) {
+        const bookSide = side === 'bids' ? orderbook.getBids(depth) : orderbook.getAsks(depth)
+        bookSide.forEach(([price, volume]) => levels.set(price, (levels.get(price) || 0) + volume))
+      }
+      return Array.from(levels.entries())
+        .sort((a, b) => side === 'bids' ? b[0] - a[0] : a[0] - b[0])
+        .slice(0, depth)
+    }
+    return { bids: merge('bids'), asks: merge('asks') }
+  }
+
+  // Per-pool top of book plus the consolidated best bid/offer
+  const getPoolSummary = (symbol) => {
+    const pools = getPoolBooks(symbol).map(([poolId, orderbook]) => ({
+      poolId,
+      name: config.liquidityPools?.pools.find(pool => pool.id === poolId)?.name || poolId,
+      bestBid: orderbook.getBestBid(),
+      bestAsk: orderbook.getBestAsk(),
+      spread: orderbook.getSpread(),
+      volume: orderbook.getTotalVolume()
+    }))
+    const bids = pools.map(pool => pool.bestBid).filter(price => price !== null)
+    const asks = pools.map(pool => pool.bestAsk).filter(price => price !== null)
+    return {
+      symbol,
+      pools,
+      bestBid: bids.length ? Math.max(...bids) : null,
+      bestAsk: asks.length ? Math.min(...asks) : null
+    }
+  }
+
+  // Per-tick market data, built once and shared by every consumer until one of the
+  // symbol's books changes. The message is frozen; serialize() is computed on first use
+  // and then reused, so publishing to many subscribers doesn't re-encode it.
+  const marketDataCache = new Map() // `${symbol}:${depth}` -> { version, message }
+
+  const getBooksVersion = (symbol) => getPoolBooks(symbol).map(([poolId, book]) => `${poolId}@${book.version}`).join(',')
+
+  const deepFreeze = (value) => {
+    if (value && typeof value === 'object' && !Object.isFrozen(value)) {
+      Object.values(value).forEach(deepFreeze)
+      Object.freeze(value)
+    }
+    return value
+  }
+
+  const getMarketDataSnapshot = (symbol, depth = 10) => {
+    if (!symbols.value.has(symbol)) return null
+    const key = `${symbol}:${depth}`
+    const version = getBooksVersion(symbol)
+    const cached = marketDataCache.get(key)
+    if (cached && cached.version === version) return cached.message
+
+    const summary = getPoolSummary(symbol)
+    const pools = Object.fromEntries(getPoolBooks(symbol).map(([poolId]) => [poolId, getPoolDepth(symbol, poolId, depth)]))
+    let serialized = null
+    const message = deepFreeze({
+      symbol,
+      version,
+      timestamp: Date.now(),
+      ticker: {
+        bid: summary.bestBid,
+        ask: summary.bestAsk,
+        mid: summary.bestBid && summary.bestAsk ? (summary.bestBid + summary.bestAsk) / 2 : null,
+        spread: summary.bestBid && summary.bestAsk ? summary.bestAsk - summary.bestBid : null,
+        last: lastTradePrices.get(symbol) ?? null
+      },
+      summary,
+      depth: getConsolidatedDepth(symbol, depth),
+      pools
+    })
+    marketDataCache.set(key, {
+      version,
+      message: Object.freeze({
+        ...message,
+        serialize: () => serialized ?? (serialized = JSON.stringify(message))
+      })
+    })
+    return marketDataCache.get(key).message
+  }
+
+  // Price ladder for one side of the book: per-level and running totals of volume and
+  // notional, best price first. Built from the shared snapshot, so it is reused until
+  // the books change. poolId 'consolidated' (the default) merges all active pools.
+  const getDepthLadder = (symbol, { levels = 50, cumulative = true, poolId = 'consolidated' } = {}) => {
+    const snapshot = getMarketDataSnapshot(symbol, levels)
+    if (!snapshot) return null
+    const depth = poolId === 'consolidated' ? snapshot.depth : snapshot.pools[poolId]
+    if (!depth) return null
+
+    const key = `ladder:${symbol}:${poolId}:${levels}:${cumulative}`
+    const cached = marketDataCache.get(key)
+    if (cached && cached.version === snapshot.version) return cached.message
+
+    const buildSide = (side) => {
+      let totalVolume = 0
+      let totalNotional = 0
+      return side.map(([price, volume]) => {
+        const level = { price, volume, notional: price * volume }
+        if (!cumulative) return level
+        totalVolume += volume
+        totalNotional += level.notional
+        return {
+          ...level,
+          cumulativeVolume: totalVolume,
+          cumulativeNotional: totalNotional,
+          vwap: totalNotional / totalVolume // average price to sweep down to this level
+        }
+      })
+    }
+
+    const message = deepFreeze({
+      symbol,
+      poolId,
+      levels,
+      cumulative,
+      version: snapshot.version,
+      timestamp: snapshot.timestamp,
+      mid: snapshot.ticker.mid,
+      spread: snapshot.ticker.spread,
+      bids: buildSide(depth.bids),
+      asks: buildSide(depth.asks)
+    })
+    marketDataCache.set(key, { version: snapshot.version, message })
+    return message
+  }
+
+  // Registered participants and the aggregate flow agents standing in for the rest
+  const findParticipant = (participantId) => {
+    const participant = participants.value.get(participantId)
+    if (participant) return participant
+    for (const agent of aggregateAgents.values()) {
+      if (agent.id === participantId) return agent
+    }
+    return null
+  }
+
+  const getParticipantType = (participantId) => findParticipant(participantId)?.participantType ?? null
+
+  // Public view of a trade. The viewer's own fills keep full detail; everyone else's
+  // counterparties are reduced according to config.tapePrivacy.
+  const toTapeEntry = (trade, viewerId = null) => {
+    const entry = {
+      id: trade.id,
+      symbol: trade.symbol,
+      price: trade.price,
+      volume: trade.volume,
+      timestamp: trade.timestamp,
+      aggressorSide: trade.aggressorSide
+    }
+
+    if (viewerId && (trade.buyerId === viewerId || trade.sellerId === viewerId)) {
+      return {
+        ...entry,
+        buyerId: trade.buyerId,
+        sellerId: trade.sellerId,
+        side: trade.buyerId === viewerId ? OrderSide.BUY : OrderSide.SELL,
+        orderId: trade.buyerId === viewerId ? trade.buyOrderId : trade.sellOrderId,
+        own: true
+      }
+    }
+
+    switch (config.tapePrivacy) {
+      case TapePrivacy.FULL:
+        return { ...entry, buyerId: trade.buyerId, sellerId: trade.sellerId }
+      case TapePrivacy.TYPE_TAGS:
+        return {
+          ...entry,
+          buyerType: getParticipantType(trade.buyerId),
+          sellerType: getParticipantType(trade.sellerId)
+        }
+      default:
+        return entry
+    }
+  }
+
+  const getMidPrices = () => {
+    const prices = new Map()
+    for (const [symbol, orderbook] of symbols.value) {
+      const mid = getReferencePrice(symbol) || orderbook.lastTradePrice
+      if (mid) prices.set(symbol, mid)
+    }
+    return prices
+  }
+
+  // Fills being netted into a print per symbol, published as TapePrint events (see tape.js)
+  const tapeConflator = createTapeConflator({
+    getWindowMs: () => config.tapeConflation.windowMs,
+    publish: (print) => emit(EngineEvent.TAPE_PRINT, { print })
+  })
+
+  // `units` = { volumeUnit, accountCurrency } reporting options, see instruments.js.
+  // Newest first; with tapeConflation enabled the entries are prints (see
+  // conflateTapeEntries) and `limit` counts prints.
+  const getTradeTape = (symbol, { limit = 100, viewerId = null, units = null } = {}) => {
+    const entries = config.tapeConflation.enabled
+      ? conflateTapeEntries((tradesBySymbol.get(symbol) || []).map(trade => toTapeEntry(trade, viewerId)), config.tapeConflation.windowMs)
+        .slice(-limit)
+        .reverse()
+      : getRecentTrades(symbol, limit).map(trade => toTapeEntry(trade, viewerId))
+    return units ? entries.map(entry => toReportUnits(entry, { ...units, prices: getMidPrices() })) : entries
+  }
+
+  const getOwnFills = (participantId, symbol = null, { limit = 100, units = null } = {}) => {
+    const entries = (symbol ? tradesBySymbol.get(symbol) || [] : tradeHistory.value)
+      .filter(t => (t.buyerId === participantId || t.sellerId === participantId) &&
+        (!symbol || t.symbol === symbol))
       .slice(-limit)
       .reverse()
+      .map(trade => toTapeEntry(trade, participantId))
+    return units ? entries.map(entry => toReportUnits(entry, { ...units, prices: getMidPrices() })) : entries
+  }
+
+  // participantId -> its last accepted orders, oldest first
+  const recentOrdersByParticipant = new Map()
+
+  subscribe((event) => {
+    if (event.type !== EngineEvent.ORDER_ACCEPTED) return
+    const { order } = event
+    let recent = recentOrdersByParticipant.get(order.participantId)
+    if (!recent) {
+      recent = []
+      recentOrdersByParticipant.set(order.participantId, recent)
+    }
+    recent.push({
+      id: order.id,
+      symbol: order.symbol,
+      side: order.side,
+      amount: order.amount,
+      price: order.price,
+      orderType: order.orderType,
+      timestamp: order.timestamp
+    })
+    if (recent.length > (config.participantInspection?.recentOrders ?? 20)) recent.shift()
+  })
+
+  // One agent in full for debugging: account, strategy, open positions and lots, P&L and
+  // its recent orders, newest first, marked Resting while they are still on the book
+  const inspectParticipant = (participantId) => {
+    const participant = participants.value.get(participantId)
+    if (!participant) return null
+    return {
+      id: participant.id,
+      name: participant.name,
+      participantType: participant.participantType,
+      tradingStrategy: participant.tradingStrategy,
+      riskTolerance: participant.riskTolerance,
+      persona: participant.persona,
+      activityRate: participant.activityRate,
+      active: participant.active,
+      balance: participant.balance,
+      equity: participant.equity,
+      marginUsed: participant.marginUsed,
+      leverage: participant.leverage,
+      positions: getParticipantPositions(participantId).map(position => ({ ...position })),
+      pnl: getPnlSummary(participantId),
+      recentOrders: (recentOrdersByParticipant.get(participantId) || []).slice().reverse().map(order => ({
+        ...order,
+        status: activeOrders.value.has(order.id) ? 'Resting' : 'Done'
+      }))
+    }
   }
 
   const getParticipantPositions = (participantId) => {
@@ -877,6 +5366,27 @@ export const useMarketEngineStore = defineStore('marketEngine', () => {
     return participant ? Array.from(participant.positions.values()) : []
   }
 
+  const getParticipantLedger = (participantId, symbol = null) => {
+    const participant = participants.value.get(participantId)
+    if (!participant) return []
+    return symbol
+      ? participant.ledger.filter(entry => entry.symbol === symbol)
+      : participant.ledger.slice()
+  }
+
+  const getPnlSummary = (participantId) => {
+    const participant = participants.value.get(participantId)
+    if (!participant) return null
+
+    return {
+      realizedPnl: participant.realizedPnl,
+      unrealizedPnl: participant.getUnrealizedPnl(),
+      openLots: Array.from(participant.lots.values()).reduce((sum, lots) => sum + lots.length, 0),
+      closedLots: participant.ledger.length,
+      lotMethod: config.lotMethod
+    }
+  }
+
   const getUserParticipant = () => {
     return participants.value.get('user_trader')
   }
@@ -889,6 +5399,110 @@ export const useMarketEngineStore = defineStore('marketEngine', () => {
     }
   }
 
+  const resetUserParticipant = (balance, leverage) => {
+    const userParticipant = getUserParticipant()
+    if (!userParticipant) return
+
+    userParticipant.balance = balance
+    userParticipant.leverage = leverage
+    userParticipant.marginUsed = 0
+    userParticipant.positions.clear()
+    userParticipant.lots.clear()
+    userParticipant.realizedPnl = 0
+    userParticipant.ledger = []
+    userParticipant.active = true
+    userParticipant.lastTradeTime = 0
+    userParticipant.updateEquity()
+
+    // Cancel resting user orders through their books, so nothing untracked is left to
+    // match, then drop the user's side of the trade history
+    const userOrderIds = Array.from(activeOrders.value.values())
+      .filter(order => order.participantId === userParticipant.id)
+      .map(order => order.id)
+    userOrderIds.forEach(orderId => handleCancelOrder({ orderId }))
+    const notUser = (t) => t.buyerId !== userParticipant.id && t.sellerId !== userParticipant.id
+    tradeHistory.value = tradeHistory.value.filter(notUser)
+    for (const [symbol, trades] of tradesBySymbol) {
+      tradesBySymbol.set(symbol, trades.filter(notUser))
+    }
+  }
+
+  // User accounts share the one user participant, so switching accounts swaps its trading
+  // state. saveUserParticipant moves the state out: balance, positions, lots, ledger and
+  // the open orders, which are taken off their books (keeping their ids) so an account that
+  // isn't active can't be filled. restoreUserParticipant puts a saved state back; resting
+  // orders rejoin their books at the back of the queue, and auction orders whose auction
+  // has since ended are cancelled.
+  const saveUserParticipant = () => {
+    const userParticipant = getUserParticipant()
+    if (!userParticipant) return null
+
+    const orders = []
+    for (const order of Array.from(activeOrders.value.values())) {
+      if (order.participantId !== userParticipant.id) continue
+      activeOrders.value.delete(order.id)
+      if (closingOrders.includes(order)) {
+        closingOrders.splice(closingOrders.indexOf(order), 1)
+        orders.push({ order, closing: true })
+      } else if (order.auctionId) {
+        const auction = auctions.get(order.symbol)
+        if (auction) auction.orders = auction.orders.filter(entry => entry !== order)
+        orders.push({ order, auctionId: order.auctionId })
+      } else {
+        const resting = getOrderBook(order.symbol, order.poolId)?.cancelOrder(order.id) || null
+        orders.push({ order, resting })
+        if (resting) publishQueueUpdates(order.symbol)
+      }
+    }
+
+    const state = {
+      balance: userParticipant.balance,
+      leverage: userParticipant.leverage,
+      marginUsed: userParticipant.marginUsed,
+      realizedPnl: userParticipant.realizedPnl,
+      lastTradeTime: userParticipant.lastTradeTime,
+      positions: userParticipant.positions,
+      lots: userParticipant.lots,
+      ledger: userParticipant.ledger,
+      orders
+    }
+    userParticipant.positions = new Map()
+    userParticipant.lots = new Map()
+    userParticipant.ledger = []
+    return state
+  }
+
+  const restoreUserParticipant = (state) => {
+    const userParticipant = getUserParticipant()
+    if (!userParticipant || !state) return
+
+    const { orders, ...fields } = state
+    Object.assign(userParticipant, fields, { active: true })
+    userParticipant.updateEquity()
+
+    orders.forEach(({ order, resting = null, closing = false, auctionId = null }) => {
+      if (closing) {
+        closingOrders.push(order)
+        activeOrders.value.set(order.id, order)
+      } else if (auctionId) {
+        const auction = auctions.get(order.symbol)
+        if (auction?.id === auctionId) {
+          auction.orders.push(order)
+          activeOrders.value.set(order.id, order)
+        } else {
+          emit(EngineEvent.ORDER_CANCELLED, { order })
+        }
+      } else {
+        activeOrders.value.set(order.id, order)
+        if (!resting) return
+        // The market may have moved through the order while it was parked
+        const trades = getOrderBook(order.symbol, order.poolId)?.addOrder(resting)
+        if (trades) trades.forEach(trade => executeTrade(trade))
+        publishQueueUpdates(order.symbol)
+      }
+    })
+  }
+
   return {
     // State
     symbols,
@@ -896,8 +5510,19 @@ export const useMarketEngineStore = defineStore('marketEngine', () => {
     activeOrders,
     tradeHistory,
     marketStats,
+    anchorPrices,
+    priceProcessStates,
+    priceTargets,
+    currencyStrength,
+    retailSentiment,
+    simClock,
+    depthHistory,
+    marketConditions,
+    regimeStates,
+    integrityViolations,
     isRunning,
     simulationSpeed,
+    loopMetrics,
     config,
 
     // Computed
@@ -908,18 +5533,123 @@ export const useMarketEngineStore = defineStore('marketEngine', () => {
     addSymbol,
     addParticipant,
     getOrderBook,
+    getPoolDepth,
+    getConsolidatedDepth,
+    getPoolSummary,
+    getMarketDataSnapshot,
+    getDepthLadder,
+    getVenueStats,
+    getTradeSizeDistribution,
+    getQuoterStats,
+    reloadConfig,
+    getConfigChangeLog,
+    scalePopulation,
+    getPersonaBreakdown,
+    getRetailSentiment,
+    getLiquidityMap,
+    getPopulation,
+    resetVenueStats,
+    getParticipantPools,
     placeOrder,
+    placeOrderBatch,
+    cancelOrder,
+    cancelAllOrders,
+    massQuote,
+    getQuote,
+    getQueuePosition,
+    getQueuePositions,
+    trackQueuePositions,
+    getExecutionReports,
+    setAdversary,
+    getAdversaryReport,
+    setNetworkConditions,
+    clearNetworkConditions,
+    getNetworkConditions,
+    takeSnapshot,
+    dispatch,
+    subscribe,
     initializeMarket,
     startSimulation,
+    runSimulationStep,
+    setPriceProcess,
+    getPriceProcess,
+    setMatchingAlgorithm,
+    getMatchingAlgorithm,
+    startAuction,
+    uncrossAuction,
+    getAuction,
+    getAuctionResults,
+    getClosingOrders,
+    addBookListener,
+    startLobsterSession,
+    stopLobsterSession,
+    getServerTime,
+    getDataTier,
+    setDataTier,
+    getBookTicker,
+    subscribeFeed,
+    getFeedRetransmission,
+    getFeedStats,
+    getCurrencyStrength,
+    resetCurrencyStrength,
+    setPriceModel,
+    clearPriceModel,
+    getPriceModel,
     stopSimulation,
+    getHealth,
+    getReadiness,
+    setTickInterval,
+    resetLoopMetrics,
     resetMarket,
     updateConfig,
     getRecentTrades,
+    listTrades,
+    listOrders,
+    listParticipants,
+    inspectParticipant,
+    getTradeTape,
+    getOwnFills,
+    getCreditLimit,
+    getAvailableCredit,
+    setCreditLimit,
+    clearCreditLimit,
+    setPreTradeCheck,
+    resetCreditUsage,
+    getCreditReport,
+    getMidPrices,
     getParticipantPositions,
+    getParticipantLedger,
+    getPnlSummary,
     getUserParticipant,
     updateUserBalance,
+    resetUserParticipant,
+    saveUserParticipant,
+    restoreUserParticipant,
     getRandomSymbol,
     getMarketPrice,
+    getReferencePrice,
+    seedOrderBook,
+    reseedThinBooks,
+    getCorrelation,
+    checkAllOrderBooks,
+    estimateMarketImpact,
+    getSeasonalFactors,
+    setAnchorPrice,
+    clearAnchorPrice,
+    applyMarketCondition,
+    removeMarketCondition,
+    simulateNewsEvent,
+    getCurrencyImpacts,
+    clearMarketConditions,
+    getConditionFactors,
+    getRegimeDebug,
+    getVolatilityState,
+    getVolatilitySurface,
+    getMarketStress,
+    placeConditionalOrder,
+    cancelConditionalOrder,
+    getConditionalOrders,
+    getSizeScale,
 
     // Classes for external use
     Order,
@@ -934,6 +5664,18 @@ export const useMarketEngineStore = defineStore('marketEngine', () => {
     ParticipantType,
     TradingStrategy,
     BrokerType,
-    ExecutionModel
+    ExecutionModel,
+    LotMethod,
+    BalanceDistribution,
+    MarketRegime,
+    VolatilityLevel,
+    ConditionOperator,
+    PriceSource,
+    ConditionalStatus,
+    TapePrivacy,
+    MarketDataTier,
+    PRIMARY_POOL,
+    EngineCommand,
+    EngineEvent
   }
 })
\ No newline at end of file
@@ -0,0 +1,178 @@
+// Simulated network between the engine and its consumers, for practicing against latency
+// and loss without a real connection.
+//
+// createClientNetwork: per-client latency, jitter and drop rate for event delivery and
+// order acknowledgements. Deliveries to one client stay in order, like a single connection.
+//
+// createRedundantFeeds: A/B market data feeds carrying the same messages with a shared seq.
+// Each feed loses dropRate of them on its own and, with gapRate chance per message, goes
+// silent for gapMs. Delivery takes latencyMs plus up to jitterMs, in order per feed. The
+// last retransmitBuffer messages can be fetched again.
+
+export const createClientNetwork = () => {
+  const conditions = new Map() // clientId -> { latencyMs, jitterMs, dropRate, lastDeliveryAt, delivered, dropped }
+
+  return {
+    set(clientId, { latencyMs = 0, jitterMs = 0, dropRate = 0 } = {}) {
+      if (!(latencyMs >= 0) || !(jitterMs >= 0)) return { success: false, error: 'latencyMs and jitterMs must be >= 0' }
+      if (!(dropRate >= 0 && dropRate <= 1)) return { success: false, error: 'dropRate must be between 0 and 1' }
+      const existing = conditions.get(clientId)
+      conditions.set(clientId, {
+        latencyMs,
+        jitterMs,
+        dropRate,
+        lastDeliveryAt: existing?.lastDeliveryAt ?? 0,
+        delivered: existing?.delivered ?? 0,
+        dropped: existing?.dropped ?? 0
+      })
+      return { success: true }
+    },
+
+    clear(clientId = null) {
+      if (clientId === null) conditions.clear()
+      else conditions.delete(clientId)
+    },
+
+    has(clientId) {
+      return conditions.has(clientId)
+    },
+
+    list() {
+      return Array.from(conditions, ([clientId, c]) => ({
+        clientId,
+        latencyMs: c.latencyMs,
+        jitterMs: c.jitterMs,
+        dropRate: c.dropRate,
+        delivered: c.delivered,
+        dropped: c.dropped
+      }))
+    },
+
+    // Runs deliver after the client's delay, or onDrop (if any) when the message is lost.
+    // reliable messages keep the delay and ordering but skip the drop roll.
+    deliver(clientId, deliver, onDrop = null, { reliable = false } = {}) {
+      const client = conditions.get(clientId)
+      if (!client) return deliver()
+
+      const now = Date.now()
+      const deliverAt = Math.max(now + client.latencyMs + Math.random() * client.jitterMs, client.lastDeliveryAt)
+      client.lastDeliveryAt = deliverAt
+      const dropped = !reliable && Math.random() < client.dropRate
+      if (dropped) client.dropped += 1
+      else client.delivered += 1
+
+      const run = dropped ? onDrop : deliver
+      if (!run) return
+      if (deliverAt <= now) run()
+      else setTimeout(run, deliverAt - now)
+    }
+  }
+}
+
+// getSettings returns the current { enabled, events, retransmitBuffer, feeds } (the engine's
+// redundantFeeds config), read on every publish so changes apply straight away
+export const createRedundantFeeds = ({ getSettings }) => {
+  const feeds = new Map() // feedId -> { subscribers, queue, timer, published, dropped, gaps, silentUntil, lastDeliveryAt }
+  const buffer = [] // last published messages, for retransmission
+  let sequence = 0
+
+  const getFeedState = (feedId) => {
+    if (!feeds.has(feedId)) {
+      feeds.set(feedId, { subscribers: new Set(), queue: [], timer: null, published: 0, dropped: 0, gaps: 0, silentUntil: 0, lastDeliveryAt: 0 })
+    }
+    return feeds.get(feedId)
+  }
+
+  const publishToFeed = (feedId, settings, message) => {
+    const state = getFeedState(feedId)
+    const now = Date.now()
+    if (now >= state.silentUntil && Math.random() < settings.gapRate) {
+      state.silentUntil = now + settings.gapMs
+      state.gaps += 1
+    }
+    if (now < state.silentUntil || Math.random() < settings.dropRate) {
+      state.dropped += 1
+      return
+    }
+    state.published += 1
+    if (state.subscribers.size === 0) return
+
+    const deliverAt = Math.max(now + settings.latencyMs + Math.random() * settings.jitterMs, state.lastDeliveryAt)
+    state.lastDeliveryAt = deliverAt
+    state.queue.push({ deliverAt, message: { feed: feedId, ...message } })
+    if (state.timer === null) scheduleFeedFlush(state)
+  }
+
+  // One timer per feed drains its queue in order; separate timers per message could fire
+  // out of order
+  const scheduleFeedFlush = (state) => {
+    const target = state.queue[0].deliverAt
+    state.timer = setTimeout(() => {
+      state.timer = null
+      const due = Math.max(target, Date.now())
+      while (state.queue.length > 0 && state.queue[0].deliverAt <= due) {
+        const { message } = state.queue.shift()
+        for (const handler of state.subscribers) {
+          try {
+            handler(message)
+          } catch (error) {
+            console.warn('Feed handler failed:', error)
+          }
+        }
+      }
+      if (state.queue.length > 0) scheduleFeedFlush(state)
+    }, Math.max(0, target - Date.now()))
+  }
+
+  return {
+    // Handler receives { feed, seq, event }; seq is shared by both feeds, so a consumer of A
+    // and B can merge them and spot what both lost
+    subscribe(feedId, handler) {
+      if (!getSettings()?.feeds?.[feedId]) throw new Error(`Unknown feed ${feedId}`)
+      const state = getFeedState(feedId)
+      state.subscribers.add(handler)
+      return () => state.subscribers.delete(handler)
+    },
+
+    publish(event) {
+      const settings = getSettings()
+      if (!settings?.enabled || !settings.events.includes(event.type)) return
+      const message = { seq: ++sequence, event }
+      buffer.push(message)
+      if (buffer.length > settings.retransmitBuffer) buffer.shift()
+      Object.entries(settings.feeds).forEach(([feedId, feedSettings]) => publishToFeed(feedId, feedSettings, message))
+    },
+
+    // Messages fromSeq..toSeq still in the buffer, for recovering what both feeds dropped
+    getRetransmission(fromSeq, toSeq = sequence) {
+      return buffer.filter(message => message.seq >= fromSeq && message.seq <= toSeq)
+    },
+
+    getStats() {
+      return {
+        lastSeq: sequence,
+        feeds: Object.keys(getSettings()?.feeds || {}).map(feedId => {
+          const state = getFeedState(feedId)
+          return {
+            feed: feedId,
+            published: state.published,
+            dropped: state.dropped,
+            gaps: state.gaps,
+            inGap: Date.now() < state.silentUntil,
+            subscribers: state.subscribers.size
+          }
+        })
+      }
+    },
+
+    // Starts the sequence over and empties the buffer and queues; subscribers stay
+    reset() {
+      buffer.length = 0
+      sequence = 0
+      for (const state of feeds.values()) {
+        clearTimeout(state.timer)
+        Object.assign(state, { queue: [], timer: null, published: 0, dropped: 0, gaps: 0, silentUntil: 0, lastDeliveryAt: 0 })
+      }
+    }
+  }
+}
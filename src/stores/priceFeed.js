@@ -0,0 +1,120 @@
+import { ref, computed } from 'vue'
+import { defineStore } from 'pinia'
+import { useMarketEngineStore } from './marketEngine'
+
+// External feeds are opt-in at build time (VITE_ENABLE_EXTERNAL_FEED=true)
+export const EXTERNAL_FEED_ENABLED = import.meta.env.VITE_ENABLE_EXTERNAL_FEED === 'true'
+
+// Feed Status
+export const FeedStatus = {
+  DISCONNECTED: 'Disconnected',
+  CONNECTING: 'Connecting',
+  CONNECTED: 'Connected',
+  ERROR: 'Error'
+}
+
+// Price Feed Store
+export const usePriceFeedStore = defineStore('priceFeed', () => {
+  const marketEngineStore = useMarketEngineStore()
+
+  // State
+  const status = ref(FeedStatus.DISCONNECTED)
+  const feedUrl = ref('')
+  const anchoredSymbols = ref([])
+  const lastQuotes = ref(new Map())
+  const lastError = ref(null)
+  const socket = ref(null)
+
+  // Computed
+  const isConnected = computed(() => status.value === FeedStatus.CONNECTED)
+
+  // Actions
+  const parseQuote = (message) => {
+    // Accepts { symbol, bid, ask } or { symbol, price }, with EUR/USD or EUR_USD spellings
+    const data = typeof message === 'string' ? JSON.parse(message) : message
+    const symbol = (data.symbol || data.instrument || '').replace(/[/_]/g, '').toUpperCase()
+    if (!symbol) return null
+
+    const bid = parseFloat(data.bid ?? data.price)
+    const ask = parseFloat(data.ask ?? data.price)
+    if (!Number.isFinite(bid) || !Number.isFinite(ask)) return null
+
+    return { symbol, bid, ask, mid: (bid + ask) / 2, timestamp: Date.now() }
+  }
+
+  const handleQuote = (quote) => {
+    if (!quote || !anchoredSymbols.value.includes(quote.symbol)) return
+
+    lastQuotes.value.set(quote.symbol, quote)
+    marketEngineStore.setAnchorPrice(quote.symbol, quote.mid)
+  }
+
+  const connect = (url, symbols) => {
+    if (!EXTERNAL_FEED_ENABLED) {
+      throw new Error('External price feeds are disabled in this build')
+    }
+
+    disconnect()
+
+    feedUrl.value = url
+    anchoredSymbols.value = symbols.map(s => s.toUpperCase())
+    status.value = FeedStatus.CONNECTING
+    lastError.value = null
+
+    const ws = new WebSocket(url)
+    ws.onopen = () => {
+      status.value = FeedStatus.CONNECTED
+      ws.send(JSON.stringify({ type: 'subscribe', symbols: anchoredSymbols.value }))
+    }
+    ws.onmessage = (event) => {
+      try {
+        handleQuote(parseQuote(event.data))
+      } catch (error) {
+        console.warn('Failed to parse feed message:', error.message)
+      }
+    }
+    ws.onerror = () => {
+      status.value = FeedStatus.ERROR
+      lastError.value = `Connection to ${url} failed`
+    }
+    ws.onclose = () => {
+      if (status.value !== FeedStatus.ERROR) {
+        status.value = FeedStatus.DISCONNECTED
+      }
+    }
+    socket.value = ws
+  }
+
+  const disconnect = () => {
+    if (socket.value) {
+      socket.value.onclose = null
+      socket.value.close()
+      socket.value = null
+    }
+    anchoredSymbols.value.forEach(symbol => marketEngineStore.clearAnchorPrice(symbol))
+    anchoredSymbols.value = []
+    lastQuotes.value.clear()
+    status.value = FeedStatus.DISCONNECTED
+  }
+
+  return {
+    // State
+    status,
+    feedUrl,
+    anchoredSymbols,
+    lastQuotes,
+    lastError,
+
+    // Computed
+    isConnected,
+
+    // Actions
+    parseQuote,
+    handleQuote,
+    connect,
+    disconnect,
+
+    // Enums
+    FeedStatus
+  }
+})
@@ -0,0 +1,140 @@
+// Price processes drive each symbol's fair value. A process is an object with
+// create(params, startPrice) -> state and step(state, dtSeconds) -> price (or null when
+// it has no opinion), plus an optional setVolatility(state, annualized) for models the
+// engine can steer towards a volatility target. The random walk is the engine's original
+// behaviour: no fair value, agents quote around the touch with noise, so the book wanders.
+
+// Price Process Models
+export const PriceProcessModel = {
+  RANDOM_WALK: 'RandomWalk',
+  ORNSTEIN_UHLENBECK: 'OrnsteinUhlenbeck',
+  JUMP_DIFFUSION: 'JumpDiffusion',
+  HISTORICAL_REPLAY: 'HistoricalReplay'
+}
+
+const SECONDS_PER_YEAR = 365 * 24 * 3600
+
+const sampleNormal = () => {
+  const u = 1 - Math.random()
+  const v = Math.random()
+  return Math.sqrt(-2 * Math.log(u)) * Math.cos(2 * Math.PI * v)
+}
+
+const randomWalk = {
+  create: () => ({ price: null }),
+  step: () => null
+}
+
+// Log-price mean reversion towards `mean` (the start price unless configured).
+// halfLifeSeconds sets the pull; volatility is annualized.
+const ornsteinUhlenbeck = {
+  create: (params, startPrice) => ({
+    price: startPrice,
+    mean: params.mean ?? startPrice,
+    theta: Math.LN2 / (params.halfLifeSeconds ?? 3600),
+    sigma: (params.volatility ?? 0.08) / Math.sqrt(SECONDS_PER_YEAR)
+  }),
+  step: (state, dt) => {
+    const x = Math.log(state.price)
+    const mu = Math.log(state.mean)
+    // Exact discretisation so large steps can't overshoot the mean
+    const decay = Math.exp(-state.theta * dt)
+    const sd = state.sigma * Math.sqrt((1 - decay * decay) / (2 * state.theta))
+    state.price = Math.exp(mu + (x - mu) * decay + sd * sampleNormal())
+    return state.price
+  },
+  setVolatility: (state, volatility) => {
+    state.sigma = volatility / Math.sqrt(SECONDS_PER_YEAR)
+  }
+}
+
+// Merton jump diffusion: geometric Brownian motion plus Poisson jumps with normally
+// distributed log size. jumpsPerHour is the jump intensity.
+const jumpDiffusion = {
+  create: (params, startPrice) => ({
+    price: startPrice,
+    sigma: (params.volatility ?? 0.08) / Math.sqrt(SECONDS_PER_YEAR),
+    drift: (params.drift ?? 0) / SECONDS_PER_YEAR,
+    jumpRate: (params.jumpsPerHour ?? 0.5) / 3600,
+    jumpMean: params.jumpMean ?? 0,
+    jumpStdev: params.jumpStdev ?? 0.002,
+    jumps: 0
+  }),
+  step: (state, dt) => {
+    let logReturn = (state.drift - state.sigma * state.sigma / 2) * dt + state.sigma * Math.sqrt(dt) * sampleNormal()
+    if (Math.random() < 1 - Math.exp(-state.jumpRate * dt)) {
+      logReturn += state.jumpMean + state.jumpStdev * sampleNormal()
+      state.jumps += 1
+    }
+    state.price *= Math.exp(logReturn)
+    return state.price
+  },
+  setVolatility: (state, volatility) => {
+    state.sigma = volatility / Math.sqrt(SECONDS_PER_YEAR)
+  }
+}
+
+// Replays recorded prices: `prices` is an array of numbers, one per `intervalSeconds`,
+// or of { price } / [t, price] entries. Holds the last price at the end unless `loop`.
+const historicalReplay = {
+  create: (params, startPrice) => {
+    const prices = (params.prices || [])
+      .map(entry => (Array.isArray(entry) ? entry[1] : typeof entry === 'object' ? entry.price : entry))
+      .filter(price => price > 0)
+    // Rescale so the replay starts at the current market, keeping its shape
+    const scale = params.rescale !== false && prices.length > 0 && startPrice ? startPrice / prices[0] : 1
+    return {
+      prices: prices.map(price => price * scale),
+      intervalSeconds: params.intervalSeconds ?? 1,
+      loop: params.loop ?? false,
+      elapsed: 0,
+      price: prices.length > 0 ? prices[0] * scale : startPrice
+    }
+  },
+  step: (state, dt) => {
+    if (state.prices.length === 0) return null
+    state.elapsed += dt
+    let index = Math.floor(state.elapsed / state.intervalSeconds)
+    if (index >= state.prices.length) {
+      index = state.loop ? index % state.prices.length : state.prices.length - 1
+    }
+    state.price = state.prices[index]
+    return state.price
+  }
+}
+
+export const priceProcesses = {
+  [PriceProcessModel.RANDOM_WALK]: randomWalk,
+  [PriceProcessModel.ORNSTEIN_UHLENBECK]: ornsteinUhlenbeck,
+  [PriceProcessModel.JUMP_DIFFUSION]: jumpDiffusion,
+  [PriceProcessModel.HISTORICAL_REPLAY]: historicalReplay
+}
+
+// Custom models can be added alongside the built-in ones
+export const registerPriceProcess = (model, process) => {
+  if (typeof process?.create !== 'function' || typeof process?.step !== 'function') {
+    throw new Error(`Price process ${model} needs create() and step()`)
+  }
+  priceProcesses[model] = process
+}
+
+export const createPriceProcess = (settings = {}, startPrice) => {
+  const model = settings.model || PriceProcessModel.RANDOM_WALK
+  const process = priceProcesses[model]
+  if (!process) throw new Error(`Unknown price process: ${model}`)
+  return { model, settings, state: process.create(settings, startPrice) }
+}
+
+export const stepPriceProcess = (instance, dtSeconds) => {
+  return priceProcesses[instance.model].step(instance.state, dtSeconds)
+}
+
+// Returns false when the model has no volatility to steer
+export const setProcessVolatility = (instance, volatility) => {
+  const process = priceProcesses[instance.model]
+  if (typeof process.setVolatility !== 'function') return false
+  process.setVolatility(instance.state, volatility)
+  return true
+}
+
+export const annualizeVolatility = (perPeriod, periodSeconds) => perPeriod * Math.sqrt(SECONDS_PER_YEAR / periodSeconds)
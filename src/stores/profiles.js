@@ -0,0 +1,90 @@
+// Named configuration profiles: bundles of engine config overrides for the usual ways the
+// simulator is run, so a setup works without tuning each knob. Overrides merge into the
+// defaults like any other config (nested objects key by key). Selected with --profile in
+// the scripts and VITE_PROFILE in the app; explicit --config overrides still apply on top.
+
+import { ParticipantType } from './marketEngine.js'
+import { StorageMode } from './storage.js'
+
+// Configuration Profiles
+export const ConfigProfile = {
+  DEMO: 'demo',
+  CLASSROOM: 'classroom',
+  RESEARCH: 'research',
+  LOAD_TEST: 'load-test'
+}
+
+export const configProfiles = {
+  // A lively market that starts fast on a laptop
+  [ConfigProfile.DEMO]: {
+    description: 'Small population, fast ticks, nothing to set up',
+    config: {
+      participantCounts: {
+        [ParticipantType.BANK]: 50,
+        [ParticipantType.TRADER]: 500,
+        [ParticipantType.HEDGE_FUND]: 10,
+        [ParticipantType.CORPORATION]: 20,
+        [ParticipantType.GOVERNMENT]: 5,
+        [ParticipantType.RETAIL_TRADER]: 1000
+      },
+      updateFrequency: 100,
+      adversary: { enabled: false }
+    }
+  },
+  // Slow enough to follow on a projector, with user orders sequenced deterministically
+  [ConfigProfile.CLASSROOM]: {
+    description: 'Few agents, slower ticks, deterministic user ordering, book self-checks',
+    config: {
+      participantCounts: {
+        [ParticipantType.BANK]: 20,
+        [ParticipantType.TRADER]: 100,
+        [ParticipantType.HEDGE_FUND]: 5,
+        [ParticipantType.CORPORATION]: 10,
+        [ParticipantType.GOVERNMENT]: 2,
+        [ParticipantType.RETAIL_TRADER]: 200
+      },
+      updateFrequency: 250,
+      selfCheck: true,
+      commandOrdering: { enabled: true },
+      adversary: { enabled: false }
+    }
+  },
+  // Full population with a fixed workload per tick, so runs are comparable
+  [ConfigProfile.RESEARCH]: {
+    description: 'Full population, no adaptive throttling, execution reports kept, accounts stored locally',
+    config: {
+      updateFrequency: 100,
+      adaptiveThrottling: false,
+      executionReports: { maxReports: 50000 },
+      storage: { mode: StorageMode.LOCAL }
+    }
+  },
+  // Engine throughput: full population at a fast tick with throttling on
+  [ConfigProfile.LOAD_TEST]: {
+    description: 'Full population at a 50ms tick, throttling on, redundant feeds off',
+    config: {
+      updateFrequency: 50,
+      adaptiveThrottling: true,
+      redundantFeeds: { enabled: false },
+      adversary: { enabled: false }
+    }
+  }
+}
+
+const unknownProfile = (name) => `Unknown profile ${name}, use one of ${Object.keys(configProfiles).join(', ')}`
+
+// Config overrides of a profile; throws for unknown names
+export const getProfileConfig = (name) => {
+  const profile = configProfiles[name]
+  if (!profile) throw new Error(unknownProfile(name))
+  return structuredClone(profile.config)
+}
+
+// Applies a profile to an engine store through reloadConfig, so it is validated and shows
+// up in the config change log
+export const applyProfile = (engine, name) => {
+  if (!configProfiles[name]) {
+    return { success: false, errors: [unknownProfile(name)], diff: [] }
+  }
+  return engine.reloadConfig(getProfileConfig(name), { source: `profile ${name}` })
+}
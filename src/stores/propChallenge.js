@@ -0,0 +1,91 @@
+// Prop-firm style evaluation rules. Drawdown limits are measured against the
+// initial balance, the way most evaluation programs define them.
+
+// Challenge Status
+export const ChallengeStatus = {
+  ACTIVE: 'Active',
+  PASSED: 'Passed',
+  FAILED: 'Failed'
+}
+
+export const challengePresets = {
+  standard: {
+    name: 'Standard $100k Evaluation',
+    balance: 100000,
+    leverage: 100,
+    profitTarget: 0.1,
+    maxDailyDrawdown: 0.05,
+    maxTotalDrawdown: 0.1,
+    minTradingDays: 4
+  },
+  verification: {
+    name: 'Verification $100k',
+    balance: 100000,
+    leverage: 100,
+    profitTarget: 0.05,
+    maxDailyDrawdown: 0.05,
+    maxTotalDrawdown: 0.1,
+    minTradingDays: 4
+  },
+  aggressive: {
+    name: 'Aggressive $200k',
+    balance: 200000,
+    leverage: 100,
+    profitTarget: 0.2,
+    maxDailyDrawdown: 0.1,
+    maxTotalDrawdown: 0.2,
+    minTradingDays: 5
+  }
+}
+
+// Evaluates the rules against the current account snapshot. Once failed or passed
+// the status sticks, so a later recovery can't undo a breach.
+export const evaluateChallenge = (challenge, { equity, dayStartEquity, tradingDays }) => {
+  const rules = challenge.rules
+  const initial = rules.balance
+
+  const dailyLoss = Math.max(0, (dayStartEquity ?? initial) - equity)
+  const totalLoss = Math.max(0, initial - equity)
+  const profit = equity - initial
+
+  const results = [
+    {
+      rule: 'maxDailyDrawdown',
+      limit: initial * rules.maxDailyDrawdown,
+      value: dailyLoss,
+      passed: dailyLoss < initial * rules.maxDailyDrawdown
+    },
+    {
+      rule: 'maxTotalDrawdown',
+      limit: initial * rules.maxTotalDrawdown,
+      value: totalLoss,
+      passed: totalLoss < initial * rules.maxTotalDrawdown
+    },
+    {
+      rule: 'profitTarget',
+      limit: initial * rules.profitTarget,
+      value: profit,
+      passed: profit >= initial * rules.profitTarget
+    },
+    {
+      rule: 'minTradingDays',
+      limit: rules.minTradingDays,
+      value: tradingDays,
+      passed: tradingDays >= rules.minTradingDays
+    }
+  ]
+
+  let status = challenge.status
+  let failureReason = challenge.failureReason
+  if (status === ChallengeStatus.ACTIVE) {
+    const breach = results.find(r => r.rule.startsWith('max') && !r.passed)
+    if (breach) {
+      status = ChallengeStatus.FAILED
+      failureReason = `${breach.rule} breached (${breach.value.toFixed(2)} >= ${breach.limit.toFixed(2)})`
+    } else if (results.every(r => r.passed)) {
+      status = ChallengeStatus.PASSED
+    }
+  }
+
+  return { status, failureReason, results }
+}
@@ -0,0 +1,80 @@
+// Pre-close risk reports. Shortly before each sim business day closes, every account gets
+// its exposure, margin under the close multiplier and the P&L of adverse opening gaps.
+// Stops don't protect against a gap: they fill at the gapped open.
+
+// Risk Report Types
+export const RiskReportType = {
+  OVERNIGHT: 'Overnight',
+  WEEKEND: 'Weekend'
+}
+
+// Notice type on the account channel
+export const RISK_REPORT_NOTICE = 'RiskReport'
+
+export const riskReportSettings = {
+  warningRatio: 0.9, // fraction of the sim day elapsed when the report goes out
+  stopOutLevel: 50, // margin level % at which the broker would liquidate
+  [RiskReportType.OVERNIGHT]: { marginMultiplier: 1, gaps: [0.005, 0.01] },
+  [RiskReportType.WEEKEND]: { marginMultiplier: 2, gaps: [0.01, 0.02, 0.05] }
+}
+
+const direction = (side) => (side === 'Buy' ? 1 : -1)
+
+// calculatePnl(position, price) and getNotional(symbol, units) come from the account's store
+export const buildRiskReport = ({ accountId, account, positions, type, simDate, calculatePnl, getNotional }) => {
+  const settings = riskReportSettings[type]
+
+  const exposureBySymbol = new Map()
+  positions.forEach(position => {
+    const units = direction(position.side) * position.volume
+    const entry = exposureBySymbol.get(position.symbol) || { symbol: position.symbol, netUnits: 0, grossUnits: 0 }
+    entry.netUnits += units
+    entry.grossUnits += position.volume
+    exposureBySymbol.set(position.symbol, entry)
+  })
+  const exposures = Array.from(exposureBySymbol.values()).map(entry => ({
+    ...entry,
+    notional: getNotional(entry.symbol, Math.abs(entry.netUnits))
+  }))
+
+  const marginAfter = account.margin_used * settings.marginMultiplier
+  const marginLevel = (equity) => (marginAfter > 0 ? equity / marginAfter * 100 : Infinity)
+
+  // Every position gaps against itself at once: the worst case for the book
+  const scenarios = settings.gaps.map(gap => {
+    let pnlChange = 0
+    let stopsGapped = 0
+    positions.forEach(position => {
+      const gapped = position.current_price * (1 - direction(position.side) * gap)
+      pnlChange += calculatePnl(position, gapped) - position.unrealized_pnl
+      if (position.stop_loss && direction(position.side) * (gapped - position.stop_loss) < 0) {
+        stopsGapped++
+      }
+    })
+    const equityAfter = account.equity + pnlChange
+    return {
+      gap,
+      pnlChange,
+      equityAfter,
+      marginLevelAfter: marginLevel(equityAfter),
+      stopsGapped,
+      wouldStopOut: marginLevel(equityAfter) < riskReportSettings.stopOutLevel
+    }
+  })
+
+  return {
+    accountId,
+    type,
+    simDate,
+    generatedAt: new Date().toISOString(),
+    exposures,
+    equity: account.equity,
+    marginUsed: account.margin_used,
+    marginMultiplier: settings.marginMultiplier,
+    marginAfter,
+    freeMarginAfter: account.equity - marginAfter,
+    marginLevelAfter: marginLevel(account.equity),
+    scenarios,
+    worstCase: scenarios[scenarios.length - 1] || null
+  }
+}
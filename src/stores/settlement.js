@@ -0,0 +1,95 @@
+// Spot FX settlement. A deal exchanges the pair's two currencies on its value date,
+// T+2 business days after the trade date (T+1 for USDCAD). Open positions are rolled
+// tom-next each sim day so they never reach delivery; that roll is where swap comes from.
+
+// Settlement Status
+export const SettlementStatus = {
+  PENDING: 'Pending',
+  SETTLED: 'Settled'
+}
+
+// Cash Flow Reasons
+export const SettlementReason = {
+  TRADE: 'Trade',
+  CLOSE: 'Close',
+  ROLL_OUT: 'RollOut', // reverses the tom leg of a rolled position
+  ROLL_IN: 'RollIn' // re-opens it at the new spot date
+}
+
+const SPOT_DAYS = { USDCAD: 1 }
+
+const isWeekend = (date) => {
+  const day = date.getUTCDay()
+  return day === 0 || day === 6
+}
+
+// Dates are 'YYYY-MM-DD' strings in sim time
+export const addBusinessDays = (date, days) => {
+  const d = new Date(`${date}T00:00:00Z`)
+  let added = 0
+  while (added < days) {
+    d.setUTCDate(d.getUTCDate() + 1)
+    if (!isWeekend(d)) added++
+  }
+  return d.toISOString().slice(0, 10)
+}
+
+// Today if it is a business day, otherwise the next one
+export const toBusinessDay = (date) => {
+  const d = new Date(`${date}T00:00:00Z`)
+  while (isWeekend(d)) d.setUTCDate(d.getUTCDate() + 1)
+  return d.toISOString().slice(0, 10)
+}
+
+export const getSpotDate = (symbol, tradeDate) => addBusinessDays(tradeDate, SPOT_DAYS[symbol] ?? 2)
+
+// Calendar nights between two value dates; a roll over the weekend carries three
+export const nightsBetween = (fromDate, toDate) => {
+  return Math.round((Date.parse(`${toDate}T00:00:00Z`) - Date.parse(`${fromDate}T00:00:00Z`)) / 86400000)
+}
+
+// Receive base and pay quote for a buy, the reverse for a sell
+export const createCashFlows = ({ symbol, side, volume, price, tradeDate, valueDate, reason, positionId }) => {
+  const sign = side === 'Buy' ? 1 : -1
+  return [
+    { currency: symbol.slice(0, 3), amount: sign * volume },
+    { currency: symbol.slice(3, 6), amount: -sign * volume * price }
+  ].map(flow => ({
+    ...flow,
+    symbol,
+    positionId,
+    reason,
+    price,
+    tradeDate,
+    valueDate,
+    status: SettlementStatus.PENDING,
+    settledOn: null
+  }))
+}
+
+// Marks every pending flow valued on or before simDate as settled
+export const settleDueFlows = (ledger, simDate) => {
+  let settled = 0
+  ledger.forEach(flow => {
+    if (flow.status === SettlementStatus.PENDING && flow.valueDate <= simDate) {
+      flow.status = SettlementStatus.SETTLED
+      flow.settledOn = simDate
+      settled++
+    }
+  })
+  return settled
+}
+
+// Net pending cash per currency, broken down by value date
+export const summarizeUnsettled = (ledger) => {
+  const byCurrency = new Map()
+  ledger
+    .filter(flow => flow.status === SettlementStatus.PENDING)
+    .forEach(flow => {
+      const entry = byCurrency.get(flow.currency) || { currency: flow.currency, net: 0, byValueDate: {} }
+      entry.net += flow.amount
+      entry.byValueDate[flow.valueDate] = (entry.byValueDate[flow.valueDate] || 0) + flow.amount
+      byCurrency.set(flow.currency, entry)
+    })
+  return Array.from(byCurrency.values())
+}
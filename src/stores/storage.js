@@ -0,0 +1,85 @@
+// Key-value storage behind the state that can outlive a page load (account ledgers,
+// journal imports). Values are stored as JSON under `${namespace}:${key}`, so both backends
+// hand back copies rather than live objects.
+//
+// memory keeps everything in the page and needs nothing, which is how the simulator has
+// always behaved: a reload starts over. local writes to the browser's localStorage so
+// accounts survive a reload. Where localStorage is unavailable (Node, storage disabled)
+// local falls back to memory and the storage reports durable: false.
+
+// Storage Modes
+export const StorageMode = {
+  MEMORY: 'memory',
+  LOCAL: 'local'
+}
+
+const createMemoryBackend = () => {
+  const entries = new Map()
+  return {
+    getItem: (key) => entries.has(key) ? entries.get(key) : null,
+    setItem: (key, value) => entries.set(key, value),
+    removeItem: (key) => entries.delete(key),
+    keys: () => Array.from(entries.keys())
+  }
+}
+
+const createLocalBackend = () => {
+  try {
+    const local = globalThis.localStorage
+    if (!local) return null
+    // Storage can exist and still refuse writes (disabled, private browsing)
+    local.setItem('__storage_probe__', '1')
+    local.removeItem('__storage_probe__')
+    return {
+      getItem: (key) => local.getItem(key),
+      setItem: (key, value) => local.setItem(key, value),
+      removeItem: (key) => local.removeItem(key),
+      keys: () => Array.from({ length: local.length }, (_, i) => local.key(i))
+    }
+  } catch {
+    return null
+  }
+}
+
+export const createStorage = ({ mode = StorageMode.MEMORY, namespace = 'fxmarketsim' } = {}) => {
+  if (!Object.values(StorageMode).includes(mode)) {
+    throw new Error(`Unknown storage mode ${mode}, use one of ${Object.values(StorageMode).join(', ')}`)
+  }
+  const durableBackend = mode === StorageMode.LOCAL ? createLocalBackend() : null
+  const backend = durableBackend || createMemoryBackend()
+  const prefix = `${namespace}:`
+
+  return {
+    mode: durableBackend ? mode : StorageMode.MEMORY,
+    requestedMode: mode,
+    namespace,
+    durable: durableBackend !== null,
+    get(key, fallback = null) {
+      const raw = backend.getItem(prefix + key)
+      if (raw === null) return fallback
+      try {
+        return JSON.parse(raw)
+      } catch {
+        return fallback
+      }
+    },
+    // false when the backend refused the write (e.g. localStorage quota exceeded)
+    set(key, value) {
+      try {
+        backend.setItem(prefix + key, JSON.stringify(value))
+        return true
+      } catch {
+        return false
+      }
+    },
+    remove(key) {
+      backend.removeItem(prefix + key)
+    },
+    // Keys in this namespace starting with `keyPrefix`, without the namespace
+    keys(keyPrefix = '') {
+      return backend.keys()
+        .filter(key => key.startsWith(prefix + keyPrefix))
+        .map(key => key.slice(prefix.length))
+    }
+  }
+}
@@ -0,0 +1,79 @@
+// Public trade tape conflation. Consecutive tape entries in a symbol at the same price and
+// aggressor side, within windowMs of the first, are netted into one print: the first entry
+// with the summed volume, fills (how many were netted) and lastTimestamp. The viewer's own
+// fills (entries marked own) are never netted.
+//
+// Entries are tape entries as the engine's toTapeEntry builds them:
+// { id, symbol, price, volume, timestamp, aggressorSide, own?, buyerId?, sellerId?, ... }
+
+// Counterparty fields of a tape entry; a print keeps one only if every fill in it agrees
+const TAPE_COUNTERPARTY_FIELDS = ['buyerId', 'sellerId', 'buyerType', 'sellerType']
+
+const startTapePrint = (entry) => ({ ...entry, fills: 1, lastTimestamp: entry.timestamp })
+
+const canConflate = (print, entry, windowMs) => !print.own && !entry.own &&
+  entry.symbol === print.symbol &&
+  entry.price === print.price &&
+  entry.aggressorSide === print.aggressorSide &&
+  entry.timestamp - print.timestamp < windowMs
+
+const addToTapePrint = (print, entry) => {
+  print.volume += entry.volume
+  print.fills += 1
+  print.lastTimestamp = entry.timestamp
+  TAPE_COUNTERPARTY_FIELDS.forEach(field => {
+    if (field in print && print[field] !== entry[field]) print[field] = null
+  })
+  return print
+}
+
+// Conflates a finished list of entries, oldest first
+export const conflateTapeEntries = (entries, windowMs) => {
+  const prints = []
+  entries.forEach(entry => {
+    const open = prints[prints.length - 1]
+    if (open && canConflate(open, entry, windowMs)) addToTapePrint(open, entry)
+    else prints.push(startTapePrint(entry))
+  })
+  return prints
+}
+
+// Conflates live fills as they arrive. Each symbol has at most one open print collecting
+// them; it goes to publish(print) once a fill can't join it or its window runs out.
+// getWindowMs is read per fill, so a changed window applies to the next print.
+export const createTapeConflator = ({ getWindowMs, publish }) => {
+  const open = new Map() // symbol -> { print, timer }
+
+  const flush = (symbol) => {
+    const entry = open.get(symbol)
+    if (!entry) return
+    clearTimeout(entry.timer)
+    open.delete(symbol)
+    publish(entry.print)
+  }
+
+  return {
+    add(entry) {
+      const windowMs = getWindowMs()
+      const current = open.get(entry.symbol)
+      if (current && canConflate(current.print, entry, windowMs)) {
+        addToTapePrint(current.print, entry)
+        return
+      }
+      flush(entry.symbol)
+      open.set(entry.symbol, {
+        print: startTapePrint(entry),
+        timer: setTimeout(() => flush(entry.symbol), windowMs)
+      })
+    },
+    // Id of the print still collecting fills in the symbol, null when there is none
+    openPrintId(symbol) {
+      return open.get(symbol)?.print.id ?? null
+    },
+    // Discards the open prints without publishing them
+    clear() {
+      for (const entry of open.values()) clearTimeout(entry.timer)
+      open.clear()
+    }
+  }
+}
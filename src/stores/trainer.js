@@ -0,0 +1,255 @@
+import { ref, computed } from 'vue'
+import { defineStore } from 'pinia'
+import { useMarketEngineStore } from './marketEngine'
+import { useMarketStore } from './market'
+import { AccountEventType } from './accountLedger'
+
+// Training Event Types
+export const TrainingEventType = {
+  NEWS_SPIKE: 'NewsSpike',
+  TREND_DAY: 'TrendDay',
+  RANGE_DAY: 'RangeDay',
+  LIQUIDITY_GAP: 'LiquidityGap'
+}
+
+// Trainer Status
+export const TrainerStatus = {
+  IDLE: 'Idle',
+  RUNNING: 'Running',
+  REVIEW: 'Review'
+}
+
+// Market condition applied for each event type. Directional events get a random sign.
+// News spikes hit a currency: every pair quoting it reacts, weighted by correlation.
+export const trainingEventProfiles = {
+  [TrainingEventType.NEWS_SPIKE]: {
+    label: 'News spike',
+    durationMs: 20000,
+    buyBias: 0.4,
+    volatility: 4,
+    liquidity: 0.5,
+    activity: 3,
+    directional: true,
+    currencyWide: true
+  },
+  [TrainingEventType.TREND_DAY]: {
+    label: 'Trend day',
+    durationMs: 180000,
+    buyBias: 0.15,
+    volatility: 1.2,
+    activity: 1.2,
+    directional: true
+  },
+  [TrainingEventType.RANGE_DAY]: {
+    label: 'Range day',
+    durationMs: 180000,
+    volatility: 0.6,
+    meanReversion: 1
+  },
+  [TrainingEventType.LIQUIDITY_GAP]: {
+    label: 'Liquidity gap',
+    durationMs: 30000,
+    volatility: 2.5,
+    liquidity: 0.15,
+    activity: 0.8
+  }
+}
+
+// Trainer Store
+export const useTrainerStore = defineStore('trainer', () => {
+  const marketEngineStore = useMarketEngineStore()
+  const marketStore = useMarketStore()
+
+  // State
+  const status = ref(TrainerStatus.IDLE)
+  const session = ref(null)
+  const timers = []
+  // Kept private until the session ends so the schedule stays unknown to the trainee
+  let eventLog = []
+  const revealedEvents = ref([])
+
+  // Computed
+  const isRunning = computed(() => status.value === TrainerStatus.RUNNING)
+  const firedEventCount = computed(() => session.value?.firedEvents ?? 0)
+
+  // Actions
+  const clearTimers = () => {
+    timers.forEach(timer => clearTimeout(timer))
+    timers.length = 0
+  }
+
+  const getMidPrice = (symbol) => {
+    return marketEngineStore.getOrderBook(symbol)?.getMidPrice() ?? null
+  }
+
+  const fireEvent = (entry) => {
+    const profile = trainingEventProfiles[entry.type]
+    const direction = profile.directional ? (Math.random() < 0.5 ? 1 : -1) : 0
+
+    entry.direction = direction > 0 ? 'up' : direction < 0 ? 'down' : null
+    entry.startedAt = Date.now()
+    entry.priceAtStart = getMidPrice(entry.symbol)
+    if (profile.currencyWide) {
+      // Direction is the currency's; each pair's move follows its side of the pair
+      const news = marketEngineStore.simulateNewsEvent({
+        currency: entry.currency,
+        direction,
+        durationMs: profile.durationMs,
+        label: profile.label
+      })
+      entry.symbols = news.impacts.map(impact => impact.symbol)
+      entry.conditionIds = news.impacts.map(impact => impact.conditionId)
+      entry.direction = `${entry.currency} ${entry.direction}`
+    } else {
+      entry.conditionIds = [marketEngineStore.applyMarketCondition({
+        ...profile,
+        symbol: entry.symbol,
+        buyBias: (profile.buyBias ?? 0) * direction
+      })]
+    }
+    session.value.firedEvents += 1
+
+    timers.push(setTimeout(() => finishEvent(entry), profile.durationMs))
+  }
+
+  const finishEvent = (entry) => {
+    if (entry.endedAt) return
+    entry.conditionIds.forEach(id => marketEngineStore.removeMarketCondition(id))
+    entry.endedAt = Date.now()
+    entry.priceAtEnd = getMidPrice(entry.symbol)
+  }
+
+  const startSession = (options = {}) => {
+    if (isRunning.value) {
+      return { success: false, error: 'A training session is already running' }
+    }
+
+    const durationMs = (options.durationMinutes ?? 30) * 60000
+    const eventCount = options.eventCount ?? 4
+    const symbolPool = options.symbols?.length ? options.symbols : marketEngineStore.config.symbols
+    const typePool = options.types?.length ? options.types : Object.values(TrainingEventType)
+    const startedAt = Date.now()
+
+    eventLog = []
+    revealedEvents.value = []
+    session.value = { startedAt, endsAt: startedAt + durationMs, eventCount, firedEvents: 0 }
+
+    for (let i = 0; i < eventCount; i++) {
+      const type = typePool[Math.floor(Math.random() * typePool.length)]
+      const profile = trainingEventProfiles[type]
+      // Leave room for the event to play out before the session ends
+      const latestStart = Math.max(0, durationMs - profile.durationMs)
+      const offset = Math.floor(Math.random() * latestStart)
+
+      const symbol = symbolPool[Math.floor(Math.random() * symbolPool.length)]
+      const entry = {
+        id: i + 1,
+        type,
+        label: profile.label,
+        symbol,
+        symbols: [symbol],
+        currency: profile.currencyWide ? (Math.random() < 0.5 ? symbol.slice(0, 3) : symbol.slice(3, 6)) : null,
+        scheduledAt: startedAt + offset,
+        startedAt: null,
+        endedAt: null,
+        direction: null,
+        priceAtStart: null,
+        priceAtEnd: null,
+        conditionIds: []
+      }
+      eventLog.push(entry)
+      timers.push(setTimeout(() => fireEvent(entry), offset))
+    }
+
+    timers.push(setTimeout(() => endSession(), durationMs))
+    status.value = TrainerStatus.RUNNING
+    return { success: true, data: { startedAt, endsAt: session.value.endsAt, eventCount } }
+  }
+
+  const endSession = () => {
+    if (!isRunning.value) return []
+
+    clearTimers()
+    eventLog.filter(entry => entry.startedAt).forEach(finishEvent)
+
+    session.value.endedAt = Date.now()
+    revealedEvents.value = eventLog
+      .filter(entry => entry.startedAt)
+      .sort((a, b) => a.startedAt - b.startedAt)
+      .map(entry => {
+        const revealed = { ...entry }
+        delete revealed.conditionIds
+        return revealed
+      })
+    status.value = TrainerStatus.REVIEW
+    return revealedEvents.value
+  }
+
+  // Pairs each revealed event with the trainee's activity on that symbol during it
+  const getSessionReview = () => {
+    if (status.value !== TrainerStatus.REVIEW) return []
+
+    const ledger = marketStore.getAccountLedger()
+    return revealedEvents.value.map(entry => {
+      const during = ledger.filter(event => {
+        const time = new Date(event.timestamp).getTime()
+        return time >= entry.startedAt && time <= entry.endedAt
+      })
+      const opened = during.filter(e => e.type === AccountEventType.POSITION_OPENED && entry.symbols.includes(e.position?.symbol))
+      const closed = during.filter(e => e.type === AccountEventType.POSITION_CLOSED && entry.symbols.includes(e.symbol))
+
+      return {
+        ...entry,
+        positionsOpened: opened.length,
+        positionsClosed: closed.length,
+        realizedPnl: closed.reduce((sum, e) => sum + (e.realizedPnl || 0), 0)
+      }
+    })
+  }
+
+  // Instructor override: force a drift and/or volatility on a symbol for as long as needed
+  const setPriceModel = (symbol, { driftPerHour = 0, targetVolatility = null } = {}) => {
+    return marketEngineStore.setPriceModel(symbol, { driftPerHour, targetVolatility })
+  }
+
+  const clearPriceModel = (symbol) => marketEngineStore.clearPriceModel(symbol)
+
+  const getPriceModels = () => {
+    return Array.from(marketEngineStore.priceTargets.keys()).map(symbol => marketEngineStore.getPriceModel(symbol))
+  }
+
+  const resetTrainer = () => {
+    if (isRunning.value) {
+      clearTimers()
+      eventLog.filter(entry => entry.startedAt).forEach(finishEvent)
+    }
+    eventLog = []
+    revealedEvents.value = []
+    session.value = null
+    status.value = TrainerStatus.IDLE
+  }
+
+  return {
+    // State
+    status,
+    session,
+    revealedEvents,
+
+    // Computed
+    isRunning,
+    firedEventCount,
+
+    // Actions
+    startSession,
+    endSession,
+    getSessionReview,
+    resetTrainer,
+    setPriceModel,
+    clearPriceModel,
+    getPriceModels,
+
+    // Enums
+    TrainingEventType,
+    TrainerStatus
+  }
+})